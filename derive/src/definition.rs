@@ -11,7 +11,7 @@ use proc_macro_error::emit_error;
 use quote::quote;
 use syn::{
     ext::IdentExt,
-    parse::{Parse, ParseStream},
+    parse::{Lookahead1, Parse, ParseStream},
     punctuated::Punctuated,
     spanned::Spanned
 };
@@ -31,25 +31,71 @@ pub enum VariantKind {
     Nested { ty: syn::Ident },
     Tuple(Struct),
     Named(Struct),
+    Other { ty: syn::Type },
 }
 
 #[derive(Debug, Clone)]
 pub enum FieldMode {
     Argument,
     Property { name: Option<String> },
-    Arguments,
+    Arguments(Bounds),
     Properties,
-    Children,
+    Children { key: Option<MapKey>, bounds: Bounds },
     Child,
     Flatten,
+    Other(OtherKind),
+}
+
+/// Number of items allowed by `arguments(min = .., max = ..)` and
+/// `children(min = .., max = ..)`
+#[derive(Debug, Clone, Default)]
+pub struct Bounds {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+/// What is used as a key of the `children(key = ..)` map
+#[derive(Debug, Clone, Copy)]
+pub enum MapKey {
+    Name,
+    Argument,
+}
+
+/// What is captured by the `other` field
+#[derive(Debug, Clone)]
+pub enum OtherKind {
+    Arguments,
+    Properties,
+    Children,
+}
+
+/// Check run on the decoded value
+#[derive(Debug, Clone)]
+pub enum Validation {
+    Custom(syn::Path),
+    Range { min: Option<syn::Expr>, max: Option<syn::Expr> },
+    NonEmpty,
+    Pattern(syn::LitStr),
+}
+
+/// Functions set by `decode_with`, `encode_with` or `with`
+#[derive(Debug, Clone, Default)]
+pub struct Codec {
+    pub decode: Option<syn::Path>,
+    pub encode: Option<syn::Path>,
 }
 
 #[derive(Debug)]
 pub enum Attr {
     Skip,
+    Other(Option<OtherKind>),
     FieldMode(FieldMode),
     Unwrap(FieldAttrs),
     Default(Option<syn::Expr>),
+    Validate(Validation),
+    With(Codec),
+    SkipEncodeIf(syn::Path),
+    EncodeDefaults,
 }
 
 #[derive(Debug, Clone)]
@@ -57,11 +103,15 @@ pub struct FieldAttrs {
     pub mode: Option<FieldMode>,
     pub unwrap: Option<Box<FieldAttrs>>,
     pub default: Option<Option<syn::Expr>>,
+    pub validate: Vec<Validation>,
+    pub codec: Codec,
+    pub skip_encode_if: Option<syn::Path>,
 }
 
 #[derive(Debug, Clone)]
 pub struct VariantAttrs {
     pub skip: bool,
+    pub other: bool,
 }
 
 #[derive(Clone)]
@@ -75,7 +125,10 @@ pub struct Field {
     pub span: Span,
     pub attr: AttrAccess,
     pub tmp_name: syn::Ident,
-    pub ty: syn::Type
+    pub ty: syn::Type,
+    pub validate: Vec<Validation>,
+    pub codec: Codec,
+    pub skip_encode_if: Option<syn::Path>,
 }
 
 pub struct Arg {
@@ -85,6 +138,7 @@ pub struct Arg {
 
 pub struct VarArgs {
     pub field: Field,
+    pub bounds: Bounds,
 }
 
 pub struct Prop {
@@ -97,15 +151,21 @@ pub struct VarProps {
     pub field: Field,
 }
 
+pub struct Other {
+    pub field: Field,
+}
+
 pub enum ChildMode {
     Normal,
     Multi,
+    Map(MapKey),
     Flatten,
 }
 
 pub struct Child {
     pub field: Field,
     pub mode: ChildMode,
+    pub bounds: Bounds,
     pub unwrap: Option<Box<FieldAttrs>>,
     pub default: Option<Option<syn::Expr>>,
 }
@@ -122,6 +182,8 @@ pub struct ExtraField {
 #[derive(Clone)]
 pub struct TraitProps {
     pub span_type: Option<syn::Type>,
+    pub validate: Vec<syn::Path>,
+    pub encode_defaults: bool,
 }
 
 pub struct Struct {
@@ -135,6 +197,9 @@ pub struct Struct {
     pub has_arguments: bool,
     pub has_properties: bool,
     pub children: Vec<Child>,
+    pub other_arguments: Option<Other>,
+    pub other_properties: Option<Other>,
+    pub other_children: Option<Other>,
     pub extra_fields: Vec<ExtraField>,
 }
 
@@ -147,6 +212,9 @@ pub struct StructBuilder {
     pub properties: Vec<Prop>,
     pub var_props: Option<VarProps>,
     pub children: Vec<Child>,
+    pub other_arguments: Option<Other>,
+    pub other_properties: Option<Other>,
+    pub other_children: Option<Other>,
     pub extra_fields: Vec<ExtraField>,
 }
 
@@ -171,16 +239,44 @@ pub struct Enum {
 
 impl TraitProps {
     fn pick_from(attrs: &mut Vec<(Attr, Span)>) -> TraitProps {
-        let props = TraitProps {
+        let mut props = TraitProps {
             span_type: None,
+            validate: Vec::new(),
+            encode_defaults: false,
         };
         for attr in mem::take(attrs) {
-            attrs.push(attr)
+            match attr {
+                (Attr::Validate(Validation::Custom(path)), _) => {
+                    props.validate.push(path);
+                }
+                (Attr::Validate(_), span) => {
+                    emit_error!(span,
+                        "only `validate = path` is supported on containers");
+                }
+                (Attr::EncodeDefaults, _) => props.encode_defaults = true,
+                attr => attrs.push(attr),
+            }
         }
         props
     }
 }
 
+/// Rejects invalid `#[kfl(pattern = "..")]` when the derive expands
+fn check_pattern(pattern: &syn::LitStr) -> syn::Result<()> {
+    use regex_syntax::Error;
+
+    regex_syntax::Parser::new().parse(&pattern.value()).map_err(|e| {
+        let reason = match &e {
+            Error::Parse(e) => e.kind().to_string(),
+            Error::Translate(e) => e.kind().to_string(),
+            _ => e.to_string(),
+        };
+        syn::Error::new(pattern.span(),
+                        format!("invalid pattern: {}", reason))
+    })?;
+    Ok(())
+}
+
 fn err_pair(s1: &Field, s2: &Field, t1: &str, t2: &str)
     -> syn::Error
 {
@@ -223,6 +319,27 @@ impl Enum {
             if attrs.skip {
                 continue;
             }
+            if attrs.other {
+                let ty = match &var.fields {
+                    syn::Fields::Unnamed(u) if u.unnamed.len() == 1 => {
+                        u.unnamed[0].ty.clone()
+                    }
+                    _ => {
+                        return Err(syn::Error::new(var.span(),
+                            "`other` variant must have a single unnamed \
+                             field, try `Other(kfl::ast::Node)`"));
+                    }
+                };
+                if variants.iter().any(|v: &Variant| {
+                    matches!(v.kind, VariantKind::Other { .. })
+                }) {
+                    return Err(syn::Error::new(var.span(),
+                        "only single `other` variant is allowed"));
+                }
+                variants.push(
+                    Variant::new(var.ident, attrs, VariantKind::Other { ty })?);
+                continue;
+            }
             let kind = match var.fields {
                 syn::Fields::Named(n) => {
                     Struct::new(var.ident.clone(),
@@ -280,6 +397,9 @@ impl StructBuilder {
             properties: Vec::new(),
             var_props: None::<VarProps>,
             children: Vec::new(),
+            other_arguments: None,
+            other_properties: None,
+            other_children: None,
             extra_fields: Vec::new(),
         }
     }
@@ -289,20 +409,49 @@ impl StructBuilder {
             trait_props: self.trait_props,
             generics: self.generics,
             has_arguments:
-                !self.arguments.is_empty() || self.var_args.is_some(),
+                !self.arguments.is_empty() || self.var_args.is_some()
+                || self.other_arguments.is_some(),
             has_properties:
-                !self.properties.is_empty() || self.var_props.is_some(),
+                !self.properties.is_empty() || self.var_props.is_some()
+                || self.other_properties.is_some(),
             arguments: self.arguments,
             var_args: self.var_args,
             properties: self.properties,
             var_props: self.var_props,
             children: self.children,
+            other_arguments: self.other_arguments,
+            other_properties: self.other_properties,
+            other_children: self.other_children,
             extra_fields: self.extra_fields,
         }
     }
-    pub fn add_field(&mut self, field: Field, attrs: &FieldAttrs)
+    pub fn add_field(&mut self, mut field: Field, attrs: &FieldAttrs)
         -> syn::Result<&mut Self>
     {
+        for validation in &attrs.validate {
+            if let Validation::Pattern(pattern) = validation {
+                check_pattern(pattern)?;
+            }
+        }
+        field.validate = attrs.validate.clone();
+        field.codec = attrs.codec.clone();
+        field.skip_encode_if = attrs.skip_encode_if.clone();
+        if field.codec.decode.is_some() || field.codec.encode.is_some() {
+            let supported = match &attrs.mode {
+                Some(FieldMode::Argument | FieldMode::Property { .. }
+                     | FieldMode::Properties | FieldMode::Child) => true,
+                Some(FieldMode::Arguments(_)) => !matches!(field.ty,
+                    syn::Type::Tuple(_) | syn::Type::Array(_)),
+                Some(FieldMode::Children { key, .. }) => key.is_none(),
+                _ => false,
+            };
+            if !supported {
+                return Err(syn::Error::new(field.span,
+                    "`decode_with`, `encode_with` and `with` are only \
+                     supported for `argument`, `arguments`, `property`, \
+                     `properties`, `child` and `children`"));
+            }
+        }
         match &attrs.mode {
             Some(FieldMode::Argument) => {
                 if let Some(prev) = &self.var_args {
@@ -315,14 +464,21 @@ impl StructBuilder {
                     default: attrs.default.clone(),
                 });
             }
-            Some(FieldMode::Arguments) => {
+            Some(FieldMode::Arguments(bounds)) => {
                 if let Some(prev) = &self.var_args {
                     return Err(err_pair(&field, &prev.field,
                         "only single `arguments` allowed",
                         "previous `arguments` is defined here"));
                 }
+                if let Some(prev) = &self.other_arguments {
+                    return Err(err_pair(&field, &prev.field,
+                        "`arguments` can't be used with `other(arguments)`",
+                        "`other(arguments)` is defined here"));
+                }
+                bounds.check(&field)?;
                 self.var_args = Some(VarArgs {
                     field,
+                    bounds: bounds.clone(),
                 });
             }
             Some(FieldMode::Property { name }) => {
@@ -353,6 +509,11 @@ impl StructBuilder {
                         "only single `properties` is allowed",
                         "previous `properties` is defined here"));
                 }
+                if let Some(prev) = &self.other_properties {
+                    return Err(err_pair(&field, &prev.field,
+                        "`properties` can't be used with `other(properties)`",
+                        "`other(properties)` is defined here"));
+                }
                 self.var_props = Some(VarProps {
                     field,
                 });
@@ -361,14 +522,17 @@ impl StructBuilder {
                 self.children.push(Child {
                     field,
                     mode: ChildMode::Normal,
+                    bounds: Bounds::default(),
                     unwrap: attrs.unwrap.clone(),
                     default: attrs.default.clone(),
                 });
             }
-            Some(FieldMode::Children) => {
+            Some(FieldMode::Children { key, bounds }) => {
+                bounds.check(&field)?;
                 self.children.push(Child {
                     field,
-                    mode: ChildMode::Multi,
+                    mode: key.map_or(ChildMode::Multi, ChildMode::Map),
+                    bounds: bounds.clone(),
                     unwrap: attrs.unwrap.clone(),
                     default: attrs.default.clone(),
                 });
@@ -377,10 +541,45 @@ impl StructBuilder {
                 self.children.push(Child {
                     field: field.clone(),
                     mode: ChildMode::Flatten,
+                    bounds: Bounds::default(),
                     unwrap: None,
                     default: None,
                 });
             }
+            Some(FieldMode::Other(OtherKind::Arguments)) => {
+                if let Some(prev) = &self.var_args {
+                    return Err(err_pair(&field, &prev.field,
+                        "`other(arguments)` can't be used with `arguments`",
+                        "`arguments` is defined here"));
+                }
+                if let Some(prev) = &self.other_arguments {
+                    return Err(err_pair(&field, &prev.field,
+                        "only single `other(arguments)` is allowed",
+                        "previous `other(arguments)` is defined here"));
+                }
+                self.other_arguments = Some(Other { field });
+            }
+            Some(FieldMode::Other(OtherKind::Properties)) => {
+                if let Some(prev) = &self.var_props {
+                    return Err(err_pair(&field, &prev.field,
+                        "`other(properties)` can't be used with `properties`",
+                        "`properties` is defined here"));
+                }
+                if let Some(prev) = &self.other_properties {
+                    return Err(err_pair(&field, &prev.field,
+                        "only single `other(properties)` is allowed",
+                        "previous `other(properties)` is defined here"));
+                }
+                self.other_properties = Some(Other { field });
+            }
+            Some(FieldMode::Other(OtherKind::Children)) => {
+                if let Some(prev) = &self.other_children {
+                    return Err(err_pair(&field, &prev.field,
+                        "only single `other` is allowed",
+                        "previous `other` is defined here"));
+                }
+                self.other_children = Some(Other { field });
+            }
             None => {
                 self.extra_fields.push(ExtraField {
                     field,
@@ -414,6 +613,9 @@ impl Struct {
         res.extend(self.properties.iter().map(|p| &p.field));
         res.extend(self.var_props.iter().map(|p| &p.field));
         res.extend(self.children.iter().map(|c| &c.field));
+        res.extend(self.other_arguments.iter().map(|o| &o.field));
+        res.extend(self.other_properties.iter().map(|o| &o.field));
+        res.extend(self.other_children.iter().map(|o| &o.field));
         res.extend(self.extra_fields.iter().map(|f| &f.field));
         return res;
     }
@@ -489,6 +691,9 @@ impl FieldAttrs {
             mode: None,
             unwrap: None,
             default: None,
+            validate: Vec::new(),
+            codec: Codec::default(),
+            skip_encode_if: None,
         }
     }
     fn update(&mut self, attrs: impl IntoIterator<Item=(Attr, Span)>) {
@@ -517,6 +722,39 @@ impl FieldAttrs {
                     }
                     self.default = Some(value);
                 }
+                Other(kind) => {
+                    if self.mode.is_some() {
+                        emit_error!(span,
+                            "only single attribute that defines mode of the \
+                            field is allowed. Perhaps you mean `unwrap`?");
+                    }
+                    let kind = kind.unwrap_or(OtherKind::Children);
+                    self.mode = Some(self::FieldMode::Other(kind));
+                }
+                Validate(validation) => self.validate.push(validation),
+                With(codec) => {
+                    if codec.decode.is_some() {
+                        if self.codec.decode.is_some() {
+                            emit_error!(span,
+                                "only single decode function is allowed");
+                        }
+                        self.codec.decode = codec.decode;
+                    }
+                    if codec.encode.is_some() {
+                        if self.codec.encode.is_some() {
+                            emit_error!(span,
+                                "only single encode function is allowed");
+                        }
+                        self.codec.encode = codec.encode;
+                    }
+                }
+                SkipEncodeIf(path) => {
+                    if self.skip_encode_if.is_some() {
+                        emit_error!(span,
+                            "only single `skip_encode_if` is allowed");
+                    }
+                    self.skip_encode_if = Some(path);
+                }
                 _ => emit_error!(span,
                     "this attribute is not supported on fields"),
             }
@@ -528,6 +766,7 @@ impl VariantAttrs {
     fn new() -> VariantAttrs {
         VariantAttrs {
             skip: false,
+            other: false,
         }
     }
     fn update(&mut self, attrs: impl IntoIterator<Item=(Attr, Span)>) {
@@ -536,6 +775,9 @@ impl VariantAttrs {
         for (attr, span) in attrs {
             match attr {
                 Skip => self.skip = true,
+                Other(None) => self.other = true,
+                Other(Some(_)) => emit_error!(span,
+                    "`other` on enum variants takes no arguments"),
                 _ => emit_error!(span, "not supported on enum variants"),
             }
         }
@@ -565,6 +807,36 @@ fn parse_attrs(input: ParseStream)
         input, Attr::parse)
 }
 
+impl Bounds {
+    /// Parses `min = N` or `max = N`, returns `false` if neither is next
+    fn parse(&mut self, input: ParseStream, lookahead: &Lookahead1)
+        -> syn::Result<bool>
+    {
+        let dest = if lookahead.peek(kw::min) {
+            let _kw: kw::min = input.parse()?;
+            &mut self.min
+        } else if lookahead.peek(kw::max) {
+            let _kw: kw::max = input.parse()?;
+            &mut self.max
+        } else {
+            return Ok(false);
+        };
+        let _eq: syn::Token![=] = input.parse()?;
+        let value: syn::LitInt = input.parse()?;
+        *dest = Some(value.base10_parse()?);
+        Ok(true)
+    }
+    fn check(&self, field: &Field) -> syn::Result<()> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min > max => {
+                Err(syn::Error::new(field.span,
+                    "`min` must not be greater than `max`"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 impl Attr {
     fn parse(input: ParseStream) -> syn::Result<(Self, Span)> {
         Self::_parse(input).map(|a| (a, input.span()))
@@ -576,7 +848,22 @@ impl Attr {
             Ok(Attr::FieldMode(FieldMode::Argument))
         } else if lookahead.peek(kw::arguments) {
             let _kw: kw::arguments = input.parse()?;
-            Ok(Attr::FieldMode(FieldMode::Arguments))
+            let mut bounds = Bounds::default();
+            if !input.is_empty() && !input.lookahead1().peek(syn::Token![,]) {
+                let parens;
+                syn::parenthesized!(parens in input);
+                loop {
+                    let lookahead = parens.lookahead1();
+                    if !bounds.parse(&parens, &lookahead)? {
+                        return Err(lookahead.error())
+                    }
+                    if parens.is_empty() {
+                        break;
+                    }
+                    let _comma: syn::Token![,] = parens.parse()?;
+                }
+            }
+            Ok(Attr::FieldMode(FieldMode::Arguments(bounds)))
         } else if lookahead.peek(kw::property) {
             let _kw: kw::property = input.parse()?;
             let mut name = None;
@@ -599,18 +886,39 @@ impl Attr {
             Ok(Attr::FieldMode(FieldMode::Properties))
         } else if lookahead.peek(kw::children) {
             let _kw: kw::children = input.parse()?;
+            let mut key = None;
+            let mut bounds = Bounds::default();
             if !input.is_empty() && !input.lookahead1().peek(syn::Token![,]) {
                 let parens;
                 syn::parenthesized!(parens in input);
-                let lookahead = parens.lookahead1();
-                if lookahead.peek(kw::name) {
-                    let _kw: kw::name = parens.parse()?;
-                    let _eq: syn::Token![=] = parens.parse()?;
-                } else {
-                    return Err(lookahead.error())
+                loop {
+                    let lookahead = parens.lookahead1();
+                    if lookahead.peek(kw::name) {
+                        let _kw: kw::name = parens.parse()?;
+                        let _eq: syn::Token![=] = parens.parse()?;
+                    } else if lookahead.peek(kw::key) {
+                        let _kw: kw::key = parens.parse()?;
+                        let _eq: syn::Token![=] = parens.parse()?;
+                        let lookahead = parens.lookahead1();
+                        if lookahead.peek(kw::name) {
+                            let _kw: kw::name = parens.parse()?;
+                            key = Some(MapKey::Name);
+                        } else if lookahead.peek(kw::argument) {
+                            let _kw: kw::argument = parens.parse()?;
+                            key = Some(MapKey::Argument);
+                        } else {
+                            return Err(lookahead.error())
+                        }
+                    } else if !bounds.parse(&parens, &lookahead)? {
+                        return Err(lookahead.error())
+                    }
+                    if parens.is_empty() {
+                        break;
+                    }
+                    let _comma: syn::Token![,] = parens.parse()?;
                 }
             }
-            Ok(Attr::FieldMode(FieldMode::Children))
+            Ok(Attr::FieldMode(FieldMode::Children { key, bounds }))
         } else if lookahead.peek(kw::child) {
             let _kw: kw::child = input.parse()?;
             Ok(Attr::FieldMode(FieldMode::Child))
@@ -622,9 +930,98 @@ impl Attr {
             let chunk = parens.call(parse_attrs)?;
             attrs.update(chunk);
             Ok(Attr::Unwrap(attrs))
+        } else if lookahead.peek(kw::skip_encode_if) {
+            let _kw: kw::skip_encode_if = input.parse()?;
+            let _eq: syn::Token![=] = input.parse()?;
+            let path: syn::Path = input.parse()?;
+            Ok(Attr::SkipEncodeIf(path))
         } else if lookahead.peek(kw::skip) {
             let _kw: kw::skip = input.parse()?;
             Ok(Attr::Skip)
+        } else if lookahead.peek(kw::encode_defaults) {
+            let _kw: kw::encode_defaults = input.parse()?;
+            Ok(Attr::EncodeDefaults)
+        } else if lookahead.peek(kw::validate) {
+            let _kw: kw::validate = input.parse()?;
+            let _eq: syn::Token![=] = input.parse()?;
+            let path: syn::Path = input.parse()?;
+            Ok(Attr::Validate(Validation::Custom(path)))
+        } else if lookahead.peek(kw::range) {
+            let _kw: kw::range = input.parse()?;
+            let parens;
+            syn::parenthesized!(parens in input);
+            let (mut min, mut max) = (None, None);
+            loop {
+                let lookahead = parens.lookahead1();
+                let dest = if lookahead.peek(kw::min) {
+                    let _kw: kw::min = parens.parse()?;
+                    &mut min
+                } else if lookahead.peek(kw::max) {
+                    let _kw: kw::max = parens.parse()?;
+                    &mut max
+                } else {
+                    return Err(lookahead.error())
+                };
+                let _eq: syn::Token![=] = parens.parse()?;
+                *dest = Some(parens.parse()?);
+                if parens.is_empty() {
+                    break;
+                }
+                let _comma: syn::Token![,] = parens.parse()?;
+            }
+            Ok(Attr::Validate(Validation::Range { min, max }))
+        } else if lookahead.peek(kw::decode_with) {
+            let _kw: kw::decode_with = input.parse()?;
+            let _eq: syn::Token![=] = input.parse()?;
+            let path: syn::Path = input.parse()?;
+            Ok(Attr::With(Codec { decode: Some(path), encode: None }))
+        } else if lookahead.peek(kw::encode_with) {
+            let _kw: kw::encode_with = input.parse()?;
+            let _eq: syn::Token![=] = input.parse()?;
+            let path: syn::Path = input.parse()?;
+            Ok(Attr::With(Codec { decode: None, encode: Some(path) }))
+        } else if lookahead.peek(kw::with) {
+            let _kw: kw::with = input.parse()?;
+            let _eq: syn::Token![=] = input.parse()?;
+            let module: syn::Path = input.parse()?;
+            let function = |name: &str| {
+                let mut path = module.clone();
+                path.segments.push(syn::Ident::new(name, module.span()).into());
+                path
+            };
+            Ok(Attr::With(Codec {
+                decode: Some(function("decode")),
+                encode: Some(function("encode")),
+            }))
+        } else if lookahead.peek(kw::non_empty) {
+            let _kw: kw::non_empty = input.parse()?;
+            Ok(Attr::Validate(Validation::NonEmpty))
+        } else if lookahead.peek(kw::pattern) {
+            let _kw: kw::pattern = input.parse()?;
+            let _eq: syn::Token![=] = input.parse()?;
+            let pattern: syn::LitStr = input.parse()?;
+            Ok(Attr::Validate(Validation::Pattern(pattern)))
+        } else if lookahead.peek(kw::other) {
+            let _kw: kw::other = input.parse()?;
+            if !input.is_empty() && !input.lookahead1().peek(syn::Token![,]) {
+                let parens;
+                syn::parenthesized!(parens in input);
+                let lookahead = parens.lookahead1();
+                if lookahead.peek(kw::arguments) {
+                    let _kw: kw::arguments = parens.parse()?;
+                    Ok(Attr::Other(Some(OtherKind::Arguments)))
+                } else if lookahead.peek(kw::properties) {
+                    let _kw: kw::properties = parens.parse()?;
+                    Ok(Attr::Other(Some(OtherKind::Properties)))
+                } else if lookahead.peek(kw::children) {
+                    let _kw: kw::children = parens.parse()?;
+                    Ok(Attr::Other(Some(OtherKind::Children)))
+                } else {
+                    Err(lookahead.error())
+                }
+            } else {
+                Ok(Attr::Other(None))
+            }
         } else if lookahead.peek(kw::flatten) {
             let _kw: kw::flatten = input.parse()?;
             Ok(Attr::FieldMode(FieldMode::Flatten))
@@ -658,7 +1055,10 @@ impl Field {
                 span: field.span(),
                 attr: AttrAccess::Named(id.clone()),
                 tmp_name: id.clone(),
-                ty: field.ty.clone()
+                ty: field.ty.clone(),
+                validate: Vec::new(),
+                codec: Codec::default(),
+                skip_encode_if: None,
             })
             .unwrap_or_else(|| Field {
                 span: field.span(),
@@ -667,7 +1067,10 @@ impl Field {
                     &format!("field{}", idx),
                     Span::mixed_site(),
                 ),
-                ty: field.ty.clone()
+                ty: field.ty.clone(),
+                validate: Vec::new(),
+                codec: Codec::default(),
+                skip_encode_if: None,
             })
     }
     pub fn from_self(&self) -> TokenStream {
@@ -0,0 +1,283 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec
+};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+};
+
+/// Nodes written inline with KDL syntax
+///
+/// Nodes end with `;`, with a block of children or where the name of the next
+/// node starts, as newlines are not visible to the macro.
+pub struct Document {
+    pub nodes: Vec<Node>,
+}
+
+pub struct Node {
+    pub type_name: Option<String>,
+    pub name: String,
+    pub arguments: Vec<Value>,
+    pub properties: Vec<(String, Value)>,
+    pub children: Option<Vec<Node>>,
+}
+
+pub struct Value {
+    pub type_name: Option<String>,
+    pub literal: String,
+    pub quoted: bool,
+}
+
+enum Entry {
+    Argument(Value),
+    Property(String, Value),
+}
+
+fn peek_slashdash(input: ParseStream) -> bool {
+    input.peek(syn::Token![/]) && input.peek2(syn::Token![-])
+}
+
+/// Parses an identifier, possibly with dashes like `max-size`, or a string
+fn parse_name(input: ParseStream) -> syn::Result<String> {
+    if input.peek(syn::LitStr) {
+        let name: syn::LitStr = input.parse()?;
+        return Ok(name.value());
+    }
+    let mut name = syn::Ident::parse_any(input)
+        .map_err(|e| syn::Error::new(e.span(), "expected a name"))?
+        .unraw().to_string();
+    while input.peek(syn::Token![-]) && input.peek2(syn::Ident::peek_any) {
+        let _dash: syn::Token![-] = input.parse()?;
+        let part = syn::Ident::parse_any(input)?;
+        name.push('-');
+        name.push_str(&part.unraw().to_string());
+    }
+    Ok(name)
+}
+
+fn parse_type(input: ParseStream) -> syn::Result<Option<String>> {
+    if input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in input);
+        let name = parse_name(&content)?;
+        if !content.is_empty() {
+            return Err(content.error("expected `)`"));
+        }
+        Ok(Some(name))
+    } else {
+        Ok(None)
+    }
+}
+
+fn number(literal: &str, suffix: &str, span: proc_macro2::Span)
+    -> syn::Result<String>
+{
+    if suffix.is_empty() {
+        Ok(literal.to_string())
+    } else {
+        Err(syn::Error::new(span,
+            format!("unexpected suffix `{}`, KDL numbers have no type suffix",
+                    suffix)))
+    }
+}
+
+impl Parse for Value {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let type_name = parse_type(input)?;
+        let negative = if input.peek(syn::Token![-]) {
+            let _minus: syn::Token![-] = input.parse()?;
+            true
+        } else {
+            false
+        };
+        let lookahead = input.lookahead1();
+        let (literal, quoted) = if !negative && lookahead.peek(syn::LitStr) {
+            let value: syn::LitStr = input.parse()?;
+            (value.value(), true)
+        } else if lookahead.peek(syn::LitInt) {
+            let value: syn::LitInt = input.parse()?;
+            let text = value.to_string();
+            let digits = &text[..text.len() - value.suffix().len()];
+            (number(digits, value.suffix(), value.span())?, false)
+        } else if lookahead.peek(syn::LitFloat) {
+            let value: syn::LitFloat = input.parse()?;
+            let text = value.to_string();
+            let digits = &text[..text.len() - value.suffix().len()];
+            (number(digits, value.suffix(), value.span())?, false)
+        } else if !negative && lookahead.peek(syn::LitBool) {
+            let value: syn::LitBool = input.parse()?;
+            (value.value.to_string(), false)
+        } else if !negative && input.peek(syn::Ident)
+            && input.fork().parse::<syn::Ident>()? == "null"
+        {
+            let _null: syn::Ident = input.parse()?;
+            ("null".to_string(), false)
+        } else {
+            return Err(lookahead.error());
+        };
+        let literal = if negative { format!("-{}", literal) } else { literal };
+        Ok(Value { type_name, literal, quoted })
+    }
+}
+
+/// Returns `true` if the next tokens start a new node rather than an entry
+fn peek_node_start(input: ParseStream) -> bool {
+    let ahead = input.fork();
+    if parse_type(&ahead).is_err() {
+        return false;
+    }
+    if !ahead.peek(syn::Ident::peek_any) {
+        return false;
+    }
+    match ahead.fork().call(syn::Ident::parse_any) {
+        Ok(ident) if ident == "null" => return false,
+        Ok(ident) if ident == "true" || ident == "false" => return false,
+        _ => {}
+    }
+    parse_name(&ahead).is_ok() && !ahead.peek(syn::Token![=])
+}
+
+impl Parse for Entry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ahead = input.fork();
+        let is_property = (ahead.peek(syn::LitStr)
+                           || ahead.peek(syn::Ident::peek_any))
+            && parse_name(&ahead).is_ok()
+            && ahead.peek(syn::Token![=]);
+        if is_property {
+            let name = parse_name(input)?;
+            let _eq: syn::Token![=] = input.parse()?;
+            Ok(Entry::Property(name, input.parse()?))
+        } else {
+            Ok(Entry::Argument(input.parse()?))
+        }
+    }
+}
+
+fn parse_children(input: ParseStream) -> syn::Result<Vec<Node>> {
+    let content;
+    syn::braced!(content in input);
+    Ok(content.parse::<Document>()?.nodes)
+}
+
+impl Parse for Node {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let type_name = parse_type(input)?;
+        let name = parse_name(input)?;
+        let mut node = Node {
+            type_name,
+            name,
+            arguments: Vec::new(),
+            properties: Vec::new(),
+            children: None,
+        };
+        loop {
+            let skip = peek_slashdash(input);
+            if skip {
+                let ahead = input.fork();
+                let _slash: syn::Token![/] = ahead.parse()?;
+                let _dash: syn::Token![-] = ahead.parse()?;
+                if peek_node_start(&ahead) {
+                    // `/-` comments out the next node
+                    return Ok(node);
+                }
+                let _slash: syn::Token![/] = input.parse()?;
+                let _dash: syn::Token![-] = input.parse()?;
+            }
+            if input.is_empty() || input.peek(syn::Token![;]) {
+                if skip {
+                    return Err(input.error("expected an entry after `/-`"));
+                }
+                if !input.is_empty() {
+                    let _semi: syn::Token![;] = input.parse()?;
+                }
+                return Ok(node);
+            }
+            if input.peek(syn::token::Brace) {
+                let children = parse_children(input)?;
+                if skip {
+                    continue;
+                }
+                node.children = Some(children);
+                if input.peek(syn::Token![;]) {
+                    let _semi: syn::Token![;] = input.parse()?;
+                }
+                return Ok(node);
+            }
+            if !skip && peek_node_start(input) {
+                return Ok(node);
+            }
+            match input.parse::<Entry>()? {
+                _ if skip => {}
+                Entry::Argument(value) => node.arguments.push(value),
+                Entry::Property(name, value) => {
+                    node.properties.push((name, value));
+                }
+            }
+        }
+    }
+}
+
+impl Parse for Document {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut nodes = Vec::new();
+        while !input.is_empty() {
+            if input.peek(syn::Token![;]) {
+                let _semi: syn::Token![;] = input.parse()?;
+                continue;
+            }
+            if peek_slashdash(input) {
+                let _slash: syn::Token![/] = input.parse()?;
+                let _dash: syn::Token![-] = input.parse()?;
+                let _node: Node = input.parse()?;
+                continue;
+            }
+            nodes.push(input.parse()?);
+        }
+        Ok(Document { nodes })
+    }
+}
+
+fn emit_value(value: &Value) -> TokenStream {
+    let literal = &value.literal;
+    let scalar = if value.quoted {
+        quote!(::kfl::ast::Scalar::string(#literal))
+    } else {
+        quote!(::kfl::ast::Scalar::bare(#literal))
+    };
+    match &value.type_name {
+        Some(type_name) => quote!(#scalar.with_type(#type_name)),
+        None => scalar,
+    }
+}
+
+fn emit_node(node: &Node) -> TokenStream {
+    let name = &node.name;
+    let mut tokens = quote!(::kfl::ast::Node::build(#name));
+    if let Some(type_name) = &node.type_name {
+        tokens = quote!(#tokens.typed(#type_name));
+    }
+    for value in &node.arguments {
+        let value = emit_value(value);
+        tokens = quote!(#tokens.arg(#value));
+    }
+    for (name, value) in &node.properties {
+        let value = emit_value(value);
+        tokens = quote!(#tokens.prop(#name, #value));
+    }
+    if let Some(children) = &node.children {
+        let children = children.iter().map(emit_node);
+        tokens = quote!(#tokens.with_children([#(#children),*]));
+    }
+    tokens
+}
+
+pub fn emit_kdl(document: &Document) -> TokenStream {
+    let nodes = document.nodes.iter().map(emit_node);
+    quote!(::kfl::__private::vec![#(#nodes),*])
+}
@@ -2,10 +2,24 @@ syn::custom_keyword!(argument);
 syn::custom_keyword!(arguments);
 syn::custom_keyword!(child);
 syn::custom_keyword!(children);
+syn::custom_keyword!(decode_with);
 syn::custom_keyword!(default);
+syn::custom_keyword!(encode_defaults);
+syn::custom_keyword!(encode_with);
 syn::custom_keyword!(flatten);
+syn::custom_keyword!(key);
+syn::custom_keyword!(max);
+syn::custom_keyword!(min);
 syn::custom_keyword!(name);
+syn::custom_keyword!(non_empty);
+syn::custom_keyword!(other);
+syn::custom_keyword!(pattern);
 syn::custom_keyword!(properties);
 syn::custom_keyword!(property);
+syn::custom_keyword!(range);
 syn::custom_keyword!(skip);
+syn::custom_keyword!(skip_encode_if);
+syn::custom_keyword!(units);
 syn::custom_keyword!(unwrap);
+syn::custom_keyword!(validate);
+syn::custom_keyword!(with);
@@ -2,6 +2,7 @@
 extern crate alloc;
 
 mod definition;
+mod kdl;
 mod kw;
 mod new_type;
 mod node;
@@ -115,6 +116,16 @@ pub fn encode_scalar_derive(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Builds a `Vec<kfl::ast::Node>` from KDL written inline
+///
+/// Nodes are separated by `;`, by their children block or by the name of
+/// the next node. Invalid syntax is reported at compile time.
+#[proc_macro]
+pub fn kdl(input: TokenStream) -> TokenStream {
+    let document = syn::parse_macro_input!(input as kdl::Document);
+    kdl::emit_kdl(&document).into()
+}
+
 pub(crate) fn to_kebab_case(ident: &syn::Ident) -> String {
     heck::ToKebabCase::to_kebab_case(format!("{}", ident).as_str())
 }
@@ -6,10 +6,13 @@ use alloc::{
 };
 
 use proc_macro2::{TokenStream, Span};
-use quote::{format_ident, quote};
+use quote::quote;
 use syn::ext::IdentExt;
 
-use crate::definition::{Struct, ExtraKind, ChildMode};
+use crate::definition::{
+    Struct, Bounds, Child, ExtraKind, ChildMode, Field, MapKey, TraitProps,
+    Validation
+};
 
 pub(crate) struct Common<'a> {
     pub object: &'a Struct,
@@ -48,14 +51,20 @@ pub fn emit_decode_struct(s: &Struct, named: bool, partial: bool)
         let assignments = fields.iter().map(|(_, v)| v);
         quote!(#s_name(#(#assignments),*))
     };
+    let value = syn::Ident::new("value", Span::mixed_site());
+    let validate_container = validate_container(&s.trait_props, &value,
+                                                &node, &ctx);
     let mut extra_traits = Vec::new();
     if partial {
-        if has_only_children(&s) {
+        if is_partial_compatible(&s) {
             let node = syn::Ident::new("node", Span::mixed_site());
             let decode_partial = decode_partial(&s, &node, &ctx)?;
-            // let name = syn::Ident::new("name", Span::mixed_site());
-            // let scalar = syn::Ident::new("scalar", Span::mixed_site());
-            // let insert_property = insert_property(&common, &name, &scalar)?;
+            let name = syn::Ident::new("name", Span::mixed_site());
+            let scalar = syn::Ident::new("scalar", Span::mixed_site());
+            let insert_property = insert_property(&s, &name, &scalar, &ctx)?;
+            let inserted = syn::Ident::new("inserted", Span::mixed_site());
+            let missing_property = missing_property(&s, &inserted);
+            let apply_defaults = apply_defaults(&s, &inserted);
             extra_traits.push(quote! {
                 impl #impl_gen ::kfl::traits::DecodePartial
                     for #s_name #type_gen
@@ -68,14 +77,24 @@ pub fn emit_decode_struct(s: &Struct, named: bool, partial: bool)
                     {
                         #decode_partial
                     }
-                    // fn insert_property(&mut self,
-                    //     #name: &Box<str>,
-                    //     #scalar: &::kfl::ast::Scalar,
-                    //     #ctx: &mut ::kfl::context::Context)
-                    //     -> Result<bool, ::kfl::errors::DecodeError>
-                    // {
-                    //     #insert_property
-                    // }
+                    fn insert_property(&mut self,
+                        #name: &str,
+                        #scalar: &::kfl::ast::Scalar,
+                        #ctx: &mut ::kfl::context::Context)
+                        -> Result<bool, ::kfl::errors::DecodeError>
+                    {
+                        #insert_property
+                    }
+                    fn missing_property(#inserted: &dyn Fn(&str) -> bool)
+                        -> Option<&'static str>
+                    {
+                        #missing_property
+                    }
+                    fn apply_defaults(&mut self,
+                        #inserted: &dyn Fn(&str) -> bool)
+                    {
+                        #apply_defaults
+                    }
                 }
             });
         } else {
@@ -97,16 +116,204 @@ pub fn emit_decode_struct(s: &Struct, named: bool, partial: bool)
                     .map(|lst| &lst[..]).unwrap_or(&[]);
                 #decode_children
                 #assign_extra
-                Ok(#struct_expression)
+                let #value = #struct_expression;
+                #validate_container
+                Ok(#value)
+            }
+        }
+    })
+}
+
+/// Emits an expression checking `value` with `#[kfl(validate = ..)]`,
+/// `range`, `non_empty` and `pattern` of the field
+///
+/// The expression evaluates to `Result<(), DecodeError>` where errors point
+/// to `span`. Fields of type `Option<T>` are checked only if there is a
+/// value. Returns `None` if the field has no validations.
+pub(crate) fn validate(field: &Field, value: TokenStream, span: TokenStream)
+    -> Option<TokenStream>
+{
+    if field.validate.is_empty() {
+        return None;
+    }
+    let val = syn::Ident::new("value", Span::mixed_site());
+    let span_ident = syn::Ident::new("span", Span::mixed_site());
+    let checks = field.validate.iter().map(|validation| match validation {
+        Validation::Custom(path) => quote! {
+            ::kfl::decode::validate_with(#val, #path, &#span_ident)?;
+        },
+        Validation::Range { min, max } => {
+            let min = min.as_ref().map_or(quote!(None), |e| quote!(Some(#e)));
+            let max = max.as_ref().map_or(quote!(None), |e| quote!(Some(#e)));
+            quote! {
+                ::kfl::decode::check_range(#val, #min, #max, &#span_ident)?;
             }
         }
+        Validation::NonEmpty => quote! {
+            if #val.is_empty() {
+                return Err(::kfl::errors::DecodeError::conversion(
+                    #span_ident, "value must not be empty"));
+            }
+        },
+        Validation::Pattern(pattern) => quote! {
+            ::kfl::__check_pattern!(#val, #pattern, &#span_ident)?;
+        },
+    });
+    let ty = &field.ty;
+    let checks = match (nullable_inner(ty), option_inner(ty)) {
+        (Some(inner), _) => quote! {
+            let #val: &#ty = #value;
+            if let Some(Some(#val)) = #val {
+                let #val: &#inner = #val;
+                #(#checks)*
+            }
+        },
+        (None, Some(inner)) => quote! {
+            let #val: &#ty = #value;
+            if let Some(#val) = #val {
+                let #val: &#inner = #val;
+                #(#checks)*
+            }
+        },
+        (None, None) => quote! {
+            let #val: &#ty = #value;
+            #(#checks)*
+        },
+    };
+    Some(quote! {
+        (|| -> Result<(), ::kfl::errors::DecodeError> {
+            let #span_ident = #span;
+            #checks
+            Ok(())
+        })()
     })
 }
 
+/// Emits the statement running container `#[kfl(validate = ..)]` functions
+pub(crate) fn validate_container(props: &TraitProps, value: &syn::Ident,
+                                 node: &syn::Ident, ctx: &syn::Ident)
+    -> TokenStream
+{
+    let checks = props.validate.iter().map(|path| quote! {
+        ::kfl::decode::validate_with(&#value, #path, &#ctx.span(&#node))?;
+    });
+    quote!(#(#checks)*)
+}
+
+fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    let path = match ty {
+        syn::Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match &args.args[0] {
+                syn::GenericArgument::Type(inner) => Some(inner),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the item type of a `#[kfl(children)]` collection
+///
+/// `Box<[T]>` only implements `IntoIterator` since Rust 1.80, so its item
+/// is taken from the type itself.
+fn children_item(ty: &syn::Type) -> TokenStream {
+    if let syn::Type::Path(path) = ty {
+        let segment = path.path.segments.last();
+        if let Some(syn::PathArguments::AngleBracketed(args)) =
+            segment.filter(|s| s.ident == "Box").map(|s| &s.arguments)
+        {
+            if let Some(syn::GenericArgument::Type(syn::Type::Slice(slice)))
+                = args.args.first()
+            {
+                let elem = &slice.elem;
+                return quote!(#elem);
+            }
+        }
+    }
+    quote!(<#ty as IntoIterator>::Item)
+}
+
+/// Returns `T` of `Option<Option<T>>`, the type of nullable fields that
+/// tell a missing value apart from `null`
+fn nullable_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    option_inner(ty).and_then(option_inner)
+}
+
+fn quote_option(value: &Option<usize>) -> TokenStream {
+    match value {
+        Some(value) => quote!(Some(#value)),
+        None => quote!(None),
+    }
+}
+
 fn decode_scalar(value: &syn::Ident, ctx: &syn::Ident) -> TokenStream {
     quote!(::kfl::traits::DecodeScalar::decode(#value, #ctx))
 }
 
+/// Emits decoding of the `argument` or `property` field, `decode_with`
+/// functions of `Option<T>` fields decode `T`
+///
+/// Fields of type `Option<Option<T>>` decode `null` into `Some(None)`, so
+/// that only a missing value is `None`.
+fn decode_field(field: &Field, value: &syn::Ident, ctx: &syn::Ident)
+    -> TokenStream
+{
+    let decode = match &field.codec.decode {
+        Some(path) if option_inner(&field.ty).is_some() => quote! {
+            ::kfl::decode::decode_option_with(#value, #ctx, #path)
+        },
+        Some(path) => quote!(#path(#value, #ctx)),
+        None => decode_scalar(value, ctx),
+    };
+    if nullable_inner(&field.ty).is_some() {
+        quote!(#decode.map(Some))
+    } else {
+        decode
+    }
+}
+
+/// Emits decoding of a single item of the `arguments` or `properties` field
+fn decode_item(field: &Field, value: &syn::Ident, ctx: &syn::Ident)
+    -> TokenStream
+{
+    match &field.codec.decode {
+        Some(path) => quote!(#path(#value, #ctx)),
+        None => decode_scalar(value, ctx),
+    }
+}
+
+/// Emits encoding of the `argument` or `property` field, `encode_with`
+/// functions of `Option<T>` fields encode `T`
+fn encode_field(field: &Field, value: TokenStream, ctx: &syn::Ident)
+    -> TokenStream
+{
+    match &field.codec.encode {
+        Some(path) if option_inner(&field.ty).is_some() => quote! {
+            ::kfl::decode::encode_option_with(#value, #ctx, #path)
+        },
+        Some(path) => quote!(#path(#value, #ctx)),
+        None => quote!(::kfl::traits::EncodeScalar::encode(#value, #ctx)),
+    }
+}
+
+/// Emits encoding of a single item of the `arguments` or `properties` field
+fn encode_item(field: &Field, value: &syn::Ident, ctx: &syn::Ident)
+    -> TokenStream
+{
+    match &field.codec.encode {
+        Some(path) => quote!(#path(#value, #ctx)),
+        None => quote!(::kfl::traits::EncodeScalar::encode(#value, #ctx)),
+    }
+}
+
 fn check_type(ident: &syn::Ident, node: &syn::Ident, ctx: &syn::Ident)
     -> TokenStream
 {
@@ -143,10 +350,21 @@ pub(crate) fn decode_arguments(s: &Struct, node: &syn::Ident, ctx: &syn::Ident)
     decoder.push(quote! {
         let mut #iter_args = #node.arguments.iter();
     });
+    // Optional arguments only take values that are not needed by the
+    // required ones, so they can be declared in any order
+    let optional_args = syn::Ident::new("optional_args", Span::mixed_site());
+    if s.arguments.iter().any(|a| a.default.is_some()) {
+        let required = s.arguments.iter()
+            .filter(|a| a.default.is_none()).count();
+        decoder.push(quote! {
+            let mut #optional_args = #node.arguments.len()
+                .saturating_sub(#required);
+        });
+    }
     for argument in &s.arguments {
         let field = &argument.field.tmp_name;
         let val = syn::Ident::new("val", Span::mixed_site());
-        let decode_scalar = decode_scalar(&val, ctx);
+        let decode_scalar = decode_field(&argument.field, &val, ctx);
         match &argument.default {
             None => {
                 let error = if argument.field.is_indexed() {
@@ -162,6 +380,11 @@ pub(crate) fn decode_arguments(s: &Struct, node: &syn::Ident, ctx: &syn::Ident)
                         })?;
                     let #field = #decode_scalar?;
                 });
+                if let Some(check) = validate(&argument.field, quote!(&#field),
+                                              quote!(#ctx.span(&#val)))
+                {
+                    decoder.push(quote!(#check?;));
+                }
             }
             Some(default_value) => {
                 let default = if let Some(expr) = default_value {
@@ -169,20 +392,74 @@ pub(crate) fn decode_arguments(s: &Struct, node: &syn::Ident, ctx: &syn::Ident)
                 } else {
                     quote!(::std::default::Default::default())
                 };
+                let value = syn::Ident::new("value", Span::mixed_site());
+                let check = validate(&argument.field, quote!(&#value),
+                                     quote!(#ctx.span(&#val)))
+                    .map(|check| quote!(#check?;));
                 decoder.push(quote! {
-                    let #field = #iter_args.next().map(|#val| #decode_scalar)
-                            .transpose()?.unwrap_or_else(|| #default);
+                    let #field = if #optional_args > 0 {
+                        #optional_args -= 1;
+                        #iter_args.next().map(|#val| {
+                            let #value = #decode_scalar?;
+                            #check
+                            Ok::<_, ::kfl::errors::DecodeError>(#value)
+                        }).transpose()?
+                    } else {
+                        None
+                    }.unwrap_or_else(|| #default);
                 });
             }
         }
     }
     if let Some(var_args) = &s.var_args {
         let field = &var_args.field.tmp_name;
+        let ty = &var_args.field.ty;
         let val = syn::Ident::new("val", Span::mixed_site());
-        let decode_scalar = decode_scalar(&val, ctx);
+        match ty {
+            syn::Type::Tuple(tuple) => {
+                let len = tuple.elems.len();
+                let items = (0..len).map(|idx| {
+                    let val = quote!(&#val[#idx]);
+                    quote!(::kfl::traits::DecodeScalar::decode(#val, #ctx)?)
+                });
+                decoder.push(quote! {
+                    let #val = ::kfl::decode::fixed_arguments(
+                        #node, #iter_args.as_slice(), #len, #ctx)?;
+                    let #field: #ty = (#(#items,)*);
+                });
+            }
+            syn::Type::Array(_) => {
+                decoder.push(quote! {
+                    let #field: #ty = ::kfl::decode::decode_array(
+                        #node, #iter_args.as_slice(), #ctx)?;
+                });
+            }
+            _ => {
+                let Bounds { min, max } = &var_args.bounds;
+                if min.is_some() || max.is_some() {
+                    let min = quote_option(min);
+                    let max = quote_option(max);
+                    decoder.push(quote! {
+                        ::kfl::decode::check_arguments_count(
+                            #node, #iter_args.as_slice(), #min, #max, #ctx)?;
+                    });
+                }
+                let decode_scalar = decode_item(&var_args.field, &val, ctx);
+                decoder.push(quote! {
+                    let #field = #iter_args.map(|#val| #decode_scalar)
+                                         .collect::<Result<_, _>>()?;
+                });
+            }
+        }
+        if let Some(check) = validate(&var_args.field, quote!(&#field),
+                                      quote!(#ctx.span(&#node)))
+        {
+            decoder.push(quote!(#check?;));
+        }
+    } else if let Some(other) = &s.other_arguments {
+        let field = &other.field.tmp_name;
         decoder.push(quote! {
-            let #field = #iter_args.map(|#val| #decode_scalar)
-                                 .collect::<Result<_, _>>()?;
+            let #field = #iter_args.cloned().collect();
         });
     } else {
         decoder.push(quote! {
@@ -210,50 +487,78 @@ pub(crate) fn decode_properties(s: &Struct, node: &syn::Ident, ctx: &syn::Ident)
     for property in &s.properties {
         let field = &property.field.tmp_name;
         let prop_name = &property.name;
-        let seen_name = format_ident!("seen_{}", field, span = Span::mixed_site());
-        if false /* TODO property.flatten */ {
-            declare_empty.push(quote! {
-                let mut #field = ::std::default::Default::default();
-            });
-            match_branches.push(quote! {
-                _ if ::kfl::traits::DecodePartial::
-                    insert_property(&mut #field, #name, #val, #ctx)?
-                => {}
+        let decode_scalar = decode_field(&property.field, &val, ctx);
+        declare_empty.push(quote! {
+            let mut #field = None;
+        });
+        let value = syn::Ident::new("value", Span::mixed_site());
+        let check = validate(&property.field, quote!(&#value),
+                             quote!(#ctx.span(&#val)))
+            .map(|check| quote!(#check?;));
+        match_branches.push(quote! {
+            #prop_name => {
+                let #value = #decode_scalar?;
+                #check
+                #field = Some(#value);
+            }
+        });
+        let req_msg = format!("property `{}` is required", prop_name);
+        if let Some(value) = &property.default {
+            let default = if let Some(expr) = value {
+                quote!(#expr)
+            } else {
+                quote!(::std::default::Default::default())
+            };
+            postprocess.push(quote! {
+                let #field = #field.unwrap_or_else(|| #default);
             });
         } else {
-            let decode_scalar = decode_scalar(&val, ctx);
+            postprocess.push(quote! {
+                let #field = #field.ok_or_else(|| {
+                    ::kfl::errors::DecodeError::missing(
+                        #ctx.span(&#node), #req_msg)
+                })?;
+            });
+        }
+    }
+    // Flattened fields are declared here rather than in `decode_children`
+    // because they have to receive properties before children are decoded.
+    let flattened = syn::Ident::new("flattened", Span::mixed_site());
+    let inserted = syn::Ident::new("inserted", Span::mixed_site());
+    if s.children.iter().any(|c| matches!(c.mode, ChildMode::Flatten)) {
+        declare_empty.push(quote!(let mut #flattened = Vec::new();));
+    }
+    for child_def in &s.children {
+        if let ChildMode::Flatten = child_def.mode {
+            let field = &child_def.field.tmp_name;
+            let ty = &child_def.field.ty;
             declare_empty.push(quote! {
-                let mut #field = None;
-                let mut #seen_name = false;
+                let mut #field: #ty = ::std::default::Default::default();
             });
             match_branches.push(quote! {
-                #prop_name => {
-                    #field = Some(#decode_scalar?);
+                _ if <#ty as ::kfl::traits::DecodePartial>
+                    ::insert_property(&mut #field, #name, #val, #ctx)?
+                => {
+                    #flattened.push(#name.as_ref());
                 }
             });
-            let req_msg = format!("property `{}` is required", prop_name);
-            if let Some(value) = &property.default {
-                let default = if let Some(expr) = value {
-                    quote!(#expr)
-                } else {
-                    quote!(::std::default::Default::default())
-                };
-                postprocess.push(quote! {
-                    let #field = #field.unwrap_or_else(|| #default);
-                });
-            } else {
-                postprocess.push(quote! {
-                    let #field = #field.ok_or_else(|| {
-                        ::kfl::errors::DecodeError::missing(
-                            #ctx.span(&#node), #req_msg)
-                    })?;
-                });
-            }
+            postprocess.push(quote! {
+                let #inserted = |name: &str| #flattened.iter().any(|n| *n == name);
+                if let Some(missing) = <#ty as ::kfl::traits::DecodePartial>
+                    ::missing_property(&#inserted)
+                {
+                    return Err(::kfl::errors::DecodeError::missing(
+                        #ctx.span(&#node),
+                        format!("property `{}` is required", missing)));
+                }
+                <#ty as ::kfl::traits::DecodePartial>
+                    ::apply_defaults(&mut #field, &#inserted);
+            });
         }
     }
     if let Some(var_props) = &s.var_props {
         let field = &var_props.field.tmp_name;
-        let decode_scalar = decode_scalar(&val, ctx);
+        let decode_scalar = decode_item(&var_props.field, &val, ctx);
         declare_empty.push(quote!(let mut #field = Vec::new();));
         match_branches.push(quote! {
             #name_str => {
@@ -270,6 +575,22 @@ pub(crate) fn decode_properties(s: &Struct, node: &syn::Ident, ctx: &syn::Ident)
         postprocess.push(quote! {
             let #field = #field.into_iter().collect();
         });
+        if let Some(check) = validate(&var_props.field, quote!(&#field),
+                                      quote!(#ctx.span(&#node)))
+        {
+            postprocess.push(quote!(#check?;));
+        }
+    } else if let Some(other) = &s.other_properties {
+        let field = &other.field.tmp_name;
+        declare_empty.push(quote!(let mut #field = Vec::new();));
+        match_branches.push(quote! {
+            _ => {
+                #field.push((#name.clone(), #val.clone()));
+            }
+        });
+        postprocess.push(quote! {
+            let #field = #field.into_iter().collect();
+        });
     } else {
         match_branches.push(quote! {
             #name_str => {
@@ -282,7 +603,7 @@ pub(crate) fn decode_properties(s: &Struct, node: &syn::Ident, ctx: &syn::Ident)
     };
     Ok(quote! {
         #(#declare_empty)*
-        for (#name, #val) in #node.properties.iter() {
+        for (#name, #val) in ::kfl::decode::properties(#node, #ctx)? {
             match #name.as_ref() {
                 #(#match_branches)*
             }
@@ -329,8 +650,8 @@ pub(crate) fn decode_properties(s: &Struct, node: &syn::Ident, ctx: &syn::Ident)
 //     })
 // }
 
-fn has_only_children(s: &Struct) -> bool {
-    !s.has_arguments && !s.has_properties
+fn is_partial_compatible(s: &Struct) -> bool {
+    !s.has_arguments
     // && s.children.iter().all(|child| child.default.is_some())
 }
 
@@ -345,53 +666,256 @@ fn decode_partial(s: &Struct, node: &syn::Ident, ctx: &syn::Ident)
     for child_def in &s.children {
         let field = &child_def.field.from_self();
         let ty = &child_def.field.ty;
+        if let ChildMode::Map(key) = child_def.mode {
+            let insert_child = insert_child_fn(key);
+            branches.push(quote! {
+                else if let Some(result) = ::kfl::decode::#insert_child(
+                    &mut #field, #node, ::std::slice::from_ref(#node), #ctx)
+                    .transpose()
+                {
+                    result.map(|()| true)
+                }
+            });
+            continue;
+        }
+        let check = match child_def.mode {
+            ChildMode::Normal => validate(&child_def.field, quote!(&#field),
+                                          quote!(#ctx.span(&#node)))
+                .map(|check| quote!(#check?;)),
+            _ => None,
+        };
+        let decode_partial = match (&child_def.mode,
+                                    &child_def.field.codec.decode) {
+            (ChildMode::Multi, Some(decode_with)) => {
+                let value = syn::Ident::new("value", Span::mixed_site());
+                branches.push(quote! {
+                    else if let Ok(#value) = #decode_with(#node, #ctx) {
+                        ::kfl::decode::push_item(&mut #field, #value);
+                        Ok(true)
+                    }
+                });
+                continue;
+            }
+            (_, Some(decode_with)) => quote! {
+                ::kfl::decode::decode_child_with(
+                    &mut #field, #node, #ctx, #decode_with)
+            },
+            (_, None) => quote! {
+                <#ty as ::kfl::traits::DecodePartial>
+                    ::decode_partial(&mut #field, #node, #ctx)
+            },
+        };
         branches.push(quote! {
-            else if let Ok(true) = <#ty as ::kfl::traits::DecodePartial>
-                ::decode_partial(&mut #field, #node, #ctx)
-            {
+            else if let Ok(true) = #decode_partial {
+                #check
                 Ok(true)
             }
         });
     }
-    branches.push(quote! {
-        else {
-            Ok(false)
-        }
-    });
+    if let Some(other) = &s.other_children {
+        let field = other.field.from_self();
+        branches.push(quote! {
+            else {
+                ::kfl::decode::push_item(&mut #field, #node.clone());
+                Ok(true)
+            }
+        });
+    } else {
+        branches.push(quote! {
+            else {
+                Ok(false)
+            }
+        });
+    }
     Ok(quote!(#(#branches)*))
 }
 
-// fn insert_property(s: &Common, name: &syn::Ident, value: &syn::Ident)
-//     -> syn::Result<TokenStream>
-// {
-//     let ctx = s.ctx;
-//     let mut match_branches = Vec::with_capacity(s.object.children.len());
-//     for property in &s.object.properties {
-//         let dest = &property.field.from_self();
-//         let prop_name = &property.name;
-//         if false /* TODO property.flatten */ {
-//             match_branches.push(quote! {
-//                 _ if ::kfl::traits::DecodePartial
-//                     ::insert_property(&mut #dest, #name, #value, #ctx)?
-//                 => Ok(true),
-//             });
-//         } else {
-//             let decode_scalar = decode_scalar(&value, ctx, &property.decode);
-//             match_branches.push(quote! {
-//                 #prop_name => {
-//                     #dest = Some(#decode_scalar?);
-//                     Ok(true)
-//                 }
-//             });
-//         }
-//     }
-//     Ok(quote! {
-//         match &*#name {
-//             #(#match_branches)*
-//             _ => Ok(false),
-//         }
-//     })
-// }
+/// Emits the `children(min = .., max = ..)` checks
+///
+/// Returns the code which remembers the first child exceeding `max`, it is
+/// run after each child added to the field.
+fn track_excess(child_def: &Child, child: &syn::Ident,
+                declare_empty: &mut Vec<TokenStream>,
+                postprocess: &mut Vec<TokenStream>,
+                err_span: &Option<TokenStream>, ctx: &syn::Ident)
+    -> TokenStream
+{
+    let Bounds { min, max } = &child_def.bounds;
+    if min.is_none() && max.is_none() {
+        return quote!();
+    }
+    let field = &child_def.field.tmp_name;
+    let excess = syn::Ident::new(&format!("{}_excess", field.unraw()),
+                                 Span::mixed_site());
+    declare_empty.push(if max.is_some() {
+        quote!(let mut #excess: Option<&::kfl::ast::Node> = None;)
+    } else {
+        quote!(let #excess: Option<&::kfl::ast::Node> = None;)
+    });
+    let span = match err_span {
+        Some(span) => quote!(Some(#span)),
+        None => quote!(None),
+    };
+    let (min_value, max_value) = (quote_option(min), quote_option(max));
+    postprocess.push(quote! {
+        ::kfl::decode::check_children_count(#field.len(), #min_value,
+            #max_value, #excess, #span, #ctx)?;
+    });
+    match max {
+        Some(max) => quote! {
+            if #field.len() == #max + 1 {
+                #excess = Some(#child);
+            }
+        },
+        None => quote!(),
+    }
+}
+
+fn insert_child_fn(key: MapKey) -> syn::Ident {
+    let name = match key {
+        MapKey::Name => "insert_child_by_name",
+        MapKey::Argument => "insert_child_by_argument",
+    };
+    syn::Ident::new(name, Span::call_site())
+}
+
+fn encode_child_fn(key: MapKey) -> syn::Ident {
+    let name = match key {
+        MapKey::Name => "encode_child_by_name",
+        MapKey::Argument => "encode_child_by_argument",
+    };
+    syn::Ident::new(name, Span::call_site())
+}
+
+/// Emits the body of `DecodePartial::missing_property`, checking the
+/// required properties and the flattened fields
+fn missing_property(s: &Struct, inserted: &syn::Ident) -> TokenStream {
+    let mut checks = Vec::new();
+    for property in &s.properties {
+        // partial structures start from `Default`, so options stay `None`
+        if property.default.is_none()
+            && option_inner(&property.field.ty).is_none()
+        {
+            let prop_name = &property.name;
+            checks.push(quote! {
+                if !#inserted(#prop_name) {
+                    return Some(#prop_name);
+                }
+            });
+        }
+    }
+    for child_def in &s.children {
+        if let ChildMode::Flatten = child_def.mode {
+            let ty = &child_def.field.ty;
+            checks.push(quote! {
+                if let Some(name) = <#ty as ::kfl::traits::DecodePartial>
+                    ::missing_property(#inserted)
+                {
+                    return Some(name);
+                }
+            });
+        }
+    }
+    quote! {
+        #(#checks)*
+        None
+    }
+}
+
+/// Emits the body of `DecodePartial::apply_defaults`, setting the properties
+/// with `default = expr` and the flattened fields
+fn apply_defaults(s: &Struct, inserted: &syn::Ident) -> TokenStream {
+    let mut assignments = Vec::new();
+    for property in &s.properties {
+        // `default` without value is what the structure starts from
+        if let Some(Some(expr)) = &property.default {
+            let dest = property.field.from_self();
+            let prop_name = &property.name;
+            assignments.push(quote! {
+                if !#inserted(#prop_name) {
+                    #dest = #expr;
+                }
+            });
+        }
+    }
+    for child_def in &s.children {
+        if let ChildMode::Flatten = child_def.mode {
+            let dest = child_def.field.from_self();
+            let ty = &child_def.field.ty;
+            assignments.push(quote! {
+                <#ty as ::kfl::traits::DecodePartial>
+                    ::apply_defaults(&mut #dest, #inserted);
+            });
+        }
+    }
+    quote!(#(#assignments)*)
+}
+
+fn insert_property(s: &Struct, name: &syn::Ident, scalar: &syn::Ident,
+                   ctx: &syn::Ident)
+    -> syn::Result<TokenStream>
+{
+    let mut match_branches = Vec::with_capacity(s.properties.len());
+    for property in &s.properties {
+        let dest = &property.field.from_self();
+        let prop_name = &property.name;
+        let decode_scalar = decode_field(&property.field, scalar, ctx);
+        let check = validate(&property.field, quote!(&#dest),
+                             quote!(#ctx.span(&#scalar)))
+            .map(|check| quote!(#check?;));
+        match_branches.push(quote! {
+            #prop_name => {
+                #dest = #decode_scalar?;
+                #check
+                Ok(true)
+            }
+        });
+    }
+    for child_def in &s.children {
+        if let ChildMode::Flatten = child_def.mode {
+            let dest = &child_def.field.from_self();
+            let ty = &child_def.field.ty;
+            match_branches.push(quote! {
+                _ if <#ty as ::kfl::traits::DecodePartial>
+                    ::insert_property(&mut #dest, #name, #scalar, #ctx)?
+                => Ok(true),
+            });
+        }
+    }
+    if let Some(var_props) = &s.var_props {
+        let dest = &var_props.field.from_self();
+        let decode_scalar = decode_item(&var_props.field, scalar, ctx);
+        match_branches.push(quote! {
+            name_str => {
+                let converted_name = name_str.parse()
+                    .map_err(|e| {
+                        ::kfl::errors::DecodeError::conversion(
+                            #ctx.span(&#scalar), e)
+                    })?;
+                ::kfl::decode::push_item(&mut #dest,
+                    (converted_name, #decode_scalar?));
+                Ok(true)
+            }
+        });
+    } else if let Some(other) = &s.other_properties {
+        let dest = &other.field.from_self();
+        match_branches.push(quote! {
+            name_str => {
+                ::kfl::decode::push_item(&mut #dest,
+                    (name_str.into(), #scalar.clone()));
+                Ok(true)
+            }
+        });
+    }
+    if s.var_props.is_none() && s.other_properties.is_none() {
+        match_branches.push(quote!(_ => Ok(false),));
+    }
+    Ok(quote! {
+        match #name {
+            #(#match_branches)*
+        }
+    })
+}
 
 pub(crate) fn decode_children(s: &Struct, children: &syn::Ident,
                               ctx: &syn::Ident, err_span: Option<TokenStream>)
@@ -406,14 +930,18 @@ pub(crate) fn decode_children(s: &Struct, children: &syn::Ident,
     let mut postprocess = Vec::new();
 
     let child = syn::Ident::new("child", Span::mixed_site());
+    let value = syn::Ident::new("value", Span::mixed_site());
+    let node_span = err_span.clone()
+        .unwrap_or_else(|| quote!(::kfl::span::Span(0, 0)));
     for child_def in &s.children {
         let field = &child_def.field.tmp_name;
         let ty = &child_def.field.ty;
+        let check = validate(&child_def.field, quote!(&#field),
+                             node_span.clone())
+            .map(|check| quote!(#check?;));
         match child_def.mode {
             ChildMode::Flatten => {
-                declare_empty.push(quote! {
-                    let mut #field = ::std::default::Default::default();
-                });
+                // declared in `decode_properties`
                 branches.push(quote! {
                     else if let Ok(true) = <#ty as ::kfl::traits::DecodePartial>
                         ::decode_partial(&mut #field, #child, #ctx) {
@@ -423,11 +951,65 @@ pub(crate) fn decode_children(s: &Struct, children: &syn::Ident,
             }
             ChildMode::Multi => {
                 declare_empty.push(quote!(let mut #field = Vec::new();));
+                let track_excess = track_excess(child_def, &child,
+                    &mut declare_empty, &mut postprocess, &err_span, ctx);
+                if let Some(decode_with) = &child_def.field.codec.decode {
+                    branches.push(quote! {
+                        else if let Ok(#value) = #decode_with(#child, #ctx) {
+                            #field.push(#value);
+                            #track_excess
+                            None
+                        }
+                    });
+                } else {
+                    let item = children_item(ty);
+                    branches.push(quote! {
+                        else if let Ok(true) = <Vec<#item> as ::kfl::traits::DecodePartial>
+                            ::decode_partial(&mut #field, #child, #ctx)
+                        {
+                            #track_excess
+                            None
+                        }
+                    });
+                }
+                if let Some(default_value) = &child_def.default {
+                    let default = if let Some(expr) = default_value {
+                        quote!(#expr)
+                    } else {
+                        quote!(::std::default::Default::default())
+                    };
+                    postprocess.push(quote! {
+                        let #field = if #field.is_empty() {
+                            #default
+                        } else {
+                            let #field = #field.into_iter().collect();
+                            #check
+                            #field
+                        };
+                    });
+                } else {
+                    postprocess.push(quote! {
+                        let #field = #field.into_iter().collect();
+                        #check
+                    });
+                }
+            }
+            ChildMode::Map(key) => {
+                declare_empty.push(quote! {
+                    let mut #field: #ty = ::std::default::Default::default();
+                });
+                let insert_child = insert_child_fn(key);
+                let track_excess = track_excess(child_def, &child,
+                    &mut declare_empty, &mut postprocess, &err_span, ctx);
                 branches.push(quote! {
-                    else if let Ok(true) = <Vec<<#ty as IntoIterator>::Item> as ::kfl::traits::DecodePartial>
-                        ::decode_partial(&mut #field, #child, #ctx)
+                    else if let Some(result) = ::kfl::decode::#insert_child(
+                        &mut #field, #child, #children, #ctx)
+                        .transpose()
                     {
-                        None
+                        if result.is_ok() {
+                            #track_excess
+                        }
+                        result.err().map(Err)
                     }
                 });
                 if let Some(default_value) = &child_def.default {
@@ -440,22 +1022,45 @@ pub(crate) fn decode_children(s: &Struct, children: &syn::Ident,
                         let #field = if #field.is_empty() {
                             #default
                         } else {
-                            #field.into_iter().collect()
+                            #check
+                            #field
                         };
                     });
                 } else {
-                    postprocess.push(quote! {
-                        let #field = #field.into_iter().collect();
-                    });
+                    postprocess.extend(check);
                 }
             }
             ChildMode::Normal => {
                 declare_empty.push(quote!(let mut #field = None;));
+                let check = validate(&child_def.field, quote!(#value),
+                                     quote!(#ctx.span(&#child)))
+                    .map(|check| quote! {
+                        #field.as_ref().and_then(|#value| #check.err())
+                            .map(Err)
+                    })
+                    .unwrap_or_else(|| quote!(None));
+                let decode_partial = match &child_def.field.codec.decode {
+                    Some(decode_with) => {
+                        let decode_with = if option_inner(ty).is_some() {
+                            quote! {
+                                |node, ctx| #decode_with(node, ctx).map(Some)
+                            }
+                        } else {
+                            quote!(#decode_with)
+                        };
+                        quote! {
+                            ::kfl::decode::decode_child_with(
+                                &mut #field, #child, #ctx, #decode_with)
+                        }
+                    }
+                    None => quote! {
+                        <Option<#ty> as ::kfl::traits::DecodePartial>
+                            ::decode_partial(&mut #field, #child, #ctx)
+                    },
+                };
                 branches.push(quote! {
-                    else if let Ok(true) = <Option<#ty> as ::kfl::traits::DecodePartial>
-                        ::decode_partial(&mut #field, #child, #ctx)
-                    {
-                        None
+                    else if let Ok(true) = #decode_partial {
+                        #check
                     }
                 });
                 let req_msg = format!(
@@ -493,16 +1098,30 @@ pub(crate) fn decode_children(s: &Struct, children: &syn::Ident,
             }
         }
     }
-    // TODO(rnarkk) return Err?
-    branches.push(quote! {
-        else {
-            #ctx.emit_error(::kfl::errors::DecodeError::unexpected(
-                #ctx.span(&#child), "node",
-                format!("unexpected node `{}`",
-                        #child.node_name.as_ref())));
-            None
-        }
-    });
+    if let Some(other) = &s.other_children {
+        let field = &other.field.tmp_name;
+        declare_empty.push(quote!(let mut #field = Vec::new();));
+        branches.push(quote! {
+            else {
+                #field.push(#child.clone());
+                None
+            }
+        });
+        postprocess.push(quote! {
+            let #field = #field.into_iter().collect();
+        });
+    } else {
+        // TODO(rnarkk) return Err?
+        branches.push(quote! {
+            else {
+                #ctx.emit_error(::kfl::errors::DecodeError::unexpected(
+                    #ctx.span(&#child), "node",
+                    format!("unexpected node `{}`",
+                            #child.node_name.as_ref())));
+                None
+            }
+        });
+    }
     Ok(quote! {
         #(#declare_empty)*
         #children.iter().flat_map(|#child| {
@@ -547,12 +1166,9 @@ pub fn emit_encode_struct(s: &Struct, partial: bool)
 
     let mut extra_traits = Vec::new();
     if partial {
-        if has_only_children(&s) {
+        if is_partial_compatible(&s) {
             let node = syn::Ident::new("node", Span::mixed_site());
-            // let name = syn::Ident::new("name", Span::mixed_site());
-            // let scalar = syn::Ident::new("scalar", Span::mixed_site());
             let encode_partial = encode_partial(&common, &node)?;
-            // let insert_property = insert_property(&common, &name, &scalar)?;
             extra_traits.push(quote! {
                 impl #impl_gen ::kfl::traits::EncodePartial
                     for #s_name #type_gen
@@ -566,14 +1182,6 @@ pub fn emit_encode_struct(s: &Struct, partial: bool)
                     {
                         #encode_partial
                     }
-                    // fn insert_property(&mut self,
-                    //     #name: &Box<str>,
-                    //     #scalar: &::kfl::ast::Scalar,
-                    //     #ctx: &mut ::kfl::context::Context)
-                    //     -> Result<bool, ::kfl::errors::EncodeError>
-                    // {
-                    //     #insert_property
-                    // }
                 }
             });
         } else {
@@ -606,11 +1214,9 @@ pub(crate) fn encode_arguments(s: &Common, node: &syn::Ident, variant: bool)
     -> syn::Result<TokenStream>
 {
     let ctx = s.ctx;
+    let encode_defaults = s.object.trait_props.encode_defaults;
     let mut encoder = Vec::new();
     let scalar = syn::Ident::new("scalar", Span::mixed_site());
-    // encoder.push(quote! {
-    //     let mut #iter_args = #node.arguments.iter();
-    // });
     for argument in &s.object.arguments {
         let field = if variant {
             let name = &argument.field.tmp_name;
@@ -620,79 +1226,104 @@ pub(crate) fn encode_arguments(s: &Common, node: &syn::Ident, variant: bool)
             quote!(&#name)
         };
         let ty = &argument.field.ty;
-        let encode_scalar = quote!(::kfl::traits::EncodeScalar::encode(
-                                   #field, #ctx));
-        match &argument.default {
-            None => {
-                // let error = if argument.field.is_indexed() {
-                //     "additional argument is required".into()
-                // } else {
-                //     format!("additional argument `{}` is required", field.unraw())
-                // };
-                encoder.push(quote! {
-                    #node.arguments.push(#encode_scalar?);
-                    // let #val =
-                    //     #iter_args.next().ok_or_else(|| {
-                    //         ::kfl::errors::EncodeError::missing(
-                    //             #ctx.span(&#node), #error)
-                    //     })?;
-                    // let #field = #encode_scalar?;
-                });
+        let encode_scalar = encode_field(&argument.field, field.clone(), ctx);
+        let encode = match &argument.default {
+            _ if nullable_inner(ty).is_some() => {
+                let value = syn::Ident::new("value", Span::mixed_site());
+                let encode_scalar = encode_field(&argument.field,
+                                                 quote!(#value), ctx);
+                quote! {
+                    if let Some(#value) = #field {
+                        #node.arguments.push(#encode_scalar?);
+                    }
+                }
             }
-            Some(default_value) => {
+            Some(default_value) if !encode_defaults => {
                 let default = if let Some(expr) = default_value {
                     quote!(#expr)
                 } else {
                     quote!(::std::default::Default::default())
                 };
-                encoder.push(quote! {
+                quote! {
                     let default: #ty = #default;
                     if &default != #field {
                         let #scalar = #encode_scalar?;
                         #node.arguments.push(#scalar);
                     }
-                    // let #field = #iter_args.next().map(|#val| {
-                    //     #encode_scalar
-                    // }).transpose()?.unwrap_or_else(|| {
-                    //     #default
-                    // });
-                });
+                }
             }
-        }
+            _ => quote! {
+                #node.arguments.push(#encode_scalar?);
+            },
+        };
+        encoder.push(skip_encode_if(&argument.field, &field, encode));
     }
     if let Some(var_args) = &s.object.var_args {
-        let field = &var_args.field.from_self();
+        let field = if variant {
+            let name = &var_args.field.tmp_name;
+            quote!(#name)
+        } else {
+            var_args.field.from_self()
+        };
         let scalar = syn::Ident::new("scalar", Span::mixed_site());
-        let encode_scalar = quote!(::kfl::traits::EncodeScalar::encode(
-                                   #scalar, #ctx));
+        let encode_scalar = encode_item(&var_args.field, &scalar, ctx);
+        let encode = if let syn::Type::Tuple(tuple) = &var_args.field.ty {
+            let items = (0..tuple.elems.len()).map(|idx| {
+                let idx = syn::Index::from(idx);
+                quote! {
+                    #node.arguments.push(::kfl::traits::EncodeScalar::encode(
+                        &#field.#idx, #ctx)?);
+                }
+            });
+            quote!(#(#items)*)
+        } else {
+            quote! {
+                let args = #field.iter().map(|#scalar| #encode_scalar)
+                        .collect::<Result<Vec<_>, _>>()?;
+                #node.arguments.extend(args);
+            }
+        };
+        let field = if variant { field } else { quote!(&#field) };
+        encoder.push(skip_encode_if(&var_args.field, &field, encode));
+    } else if let Some(other) = &s.object.other_arguments {
+        let field = if variant {
+            let name = &other.field.tmp_name;
+            quote!(#name)
+        } else {
+            other.field.from_self()
+        };
         encoder.push(quote! {
-            let args = #field.iter().map(|#scalar| #encode_scalar)
-                    .collect::<Result<Vec<_>, _>>()?;
-            #node.arguments.extend(args);
+            #node.arguments.extend(#field.iter().cloned());
         });
-    } else {
-        // encoder.push(quote! {
-        //     if let Some(scalar) = #field.into_iter().next() {
-        //         return Err(::kfl::errors::EncodeError::unexpected(
-        //                 #ctx.span(&scalar), "argument",
-        //                 "unexpected argument"));
-        //     }
-        // });
     }
     Ok(quote!(#(#encoder)*))
 }
 
+/// Wraps the encoding of the field into the `skip_encode_if` check
+///
+/// `value` is the reference to the field passed to the function.
+fn skip_encode_if(field: &Field, value: &TokenStream, encode: TokenStream)
+    -> TokenStream
+{
+    match &field.skip_encode_if {
+        Some(path) => quote! {
+            if !#path(#value) {
+                #encode
+            }
+        },
+        None => encode,
+    }
+}
+
 // TODO(rnarkk) named and unnamed
 pub(crate) fn encode_properties(s: &Common, node: &syn::Ident, variant: bool)
     -> syn::Result<TokenStream>
 {
-    // let mut preprocess = Vec::new();
     let mut branches = Vec::new();
 
     let ctx = s.ctx;
+    let encode_defaults = s.object.trait_props.encode_defaults;
     let scalar = syn::Ident::new("scalar", Span::mixed_site());
-    // let props = syn::Ident::new("props", Span::mixed_site());
-    // let declare_empty = quote!(let mut #props = Vec::new(););
 
     for property in &s.object.properties {
         let field = if variant {
@@ -704,103 +1335,127 @@ pub(crate) fn encode_properties(s: &Common, node: &syn::Ident, variant: bool)
         };
         let name = &property.name;
         let ty = &property.field.ty;
-        // let seen_name = format_ident!("seen_{}", field, span = Span::mixed_site());
-        if false /* TODO property.flatten */ {
-            // declare_empty.push(quote! {
-            //     let mut #field = ::std::default::Default::default();
-            // });
-            // match_branches.push(quote! {
-            //     _ if ::kfl::traits::EncodePartial::
-            //         insert_property(&mut #field, #name, #val, #ctx)?
-            //     => {}
-            // });
-        } else {
-            let encode_scalar = quote!(::kfl::traits::EncodeScalar::encode(
-                                       #field, #ctx));
-            // let req_msg = format!("property `{}` is required", prop_name);
-            if let Some(value) = &property.default {
+        let encode_scalar = encode_field(&property.field, field.clone(), ctx);
+        let encode = match &property.default {
+            _ if nullable_inner(ty).is_some() => {
+                let value = syn::Ident::new("value", Span::mixed_site());
+                let encode_scalar = encode_field(&property.field,
+                                                 quote!(#value), ctx);
+                quote! {
+                    if let Some(#value) = #field {
+                        let #scalar = #encode_scalar?;
+                        #node.properties.insert(#name.to_owned().into_boxed_str(), #scalar);
+                    }
+                }
+            }
+            Some(value) if !encode_defaults => {
                 let default = if let Some(expr) = value {
                     quote!(#expr)
                 } else {
                     quote!(::std::default::Default::default())
                 };
-                branches.push(quote! {
+                quote! {
                     let default: #ty = #default;
                     if &default != #field {
                         let #scalar = #encode_scalar?;
                         #node.properties.insert(#name.to_owned().into_boxed_str(), #scalar);
                     }
-                });
-            } else {
-                // postprocess.push(quote! {
-                //     let #field = #field.ok_or_else(|| {
-                //         ::kfl::errors::EncodeError::missing(
-                //             #ctx.span(&#node), #req_msg)
-                //     })?;
-                // });
-                
-                branches.push(quote! {
-                    let #scalar = #encode_scalar?;
-                    // let mut #seen_name = false;
-                    #node.properties.insert(#name.to_owned().into_boxed_str(), #scalar);
-                });
+                }
             }
-        }
+            _ => quote! {
+                let #scalar = #encode_scalar?;
+                #node.properties.insert(#name.to_owned().into_boxed_str(), #scalar);
+            },
+        };
+        branches.push(skip_encode_if(&property.field, &field, encode));
     }
     if let Some(var_props) = &s.object.var_props {
-        let field = &var_props.field.from_self();
+        let field = if variant {
+            let name = &var_props.field.tmp_name;
+            quote!(#name)
+        } else {
+            var_props.field.from_self()
+        };
         let scalar = syn::Ident::new("scalar", Span::mixed_site());
-        let encode_scalar = quote!(::kfl::traits::EncodeScalar::encode(
-                                   #scalar, #ctx));
-        // declare_full.push(quote! {
-        //     let mut #field = Vec::new();
-        // });
-        branches.push(quote! {
-            // #name_str => {
-            //     let converted_name = #name_str.parse()
-            //         .map_err(|e| {
-            //             ::kfl::errors::EncodeError::conversion(
-            //                 #ctx.span(&#name), e)
-            //         })?;
-            //     #field.push((
-            //         converted_name,
-            //         #encode_scalar?,
-            //     ));
-            // }
+        let encode_scalar = encode_item(&var_props.field, &scalar, ctx);
+        let encode = quote! {
             for (name, #scalar) in #field.iter() {
-                #node.properties.insert(name.clone().into_boxed_str(),
+                let name = ::std::string::ToString::to_string(name);
+                #node.properties.insert(name.into_boxed_str(),
                                         #encode_scalar?);
             }
+        };
+        let field = if variant { field } else { quote!(&#field) };
+        branches.push(skip_encode_if(&var_props.field, &field, encode));
+    } else if let Some(other) = &s.object.other_properties {
+        let field = if variant {
+            let name = &other.field.tmp_name;
+            quote!(#name)
+        } else {
+            other.field.from_self()
+        };
+        branches.push(quote! {
+            for (name, #scalar) in #field.iter() {
+                #node.properties.insert(name.clone(), #scalar.clone());
+            }
         });
-        // postprocess.push(quote! {
-        //     let #field = #field.into_iter().collect();
-        // });
-    } else {
-    //     match_branches.push(quote! {
-    //         #name_str => {
-    //             return Err(::kfl::errors::EncodeError::unexpected(
-    //                 #ctx.span(&#name), "property",
-    //                 format!("unexpected property `{}`",
-    //                         #name_str.escape_default())));
-    //         }
-    //     });
     }
-    Ok(quote! {
-        // #(#preprocess)*
-        #(#branches)*
-        // #(#postprocess)*
-    })
+    Ok(quote!(#(#branches)*))
 }
 
 fn encode_partial(s: &Common, node: &syn::Ident) -> syn::Result<TokenStream> {
     let ctx = s.ctx;
-    let mut branches = Vec::new();
+    let encode_defaults = s.object.trait_props.encode_defaults;
+    let mut branches = vec![encode_properties(s, node, false)?];
     for child_def in &s.object.children {
         let field = &child_def.field.from_self();
         let ty = &child_def.field.ty;
+        let encode = if let ChildMode::Map(key) = child_def.mode {
+            let encode_child = encode_child_fn(key);
+            quote! {
+                for (key, value) in #field.iter() {
+                    let child = ::kfl::decode::#encode_child(key, value, #ctx)?;
+                    #node.children.get_or_insert_with(Vec::new).push(child);
+                }
+            }
+        } else if let Some(encode_with) = &child_def.field.codec.encode {
+            quote! {
+                for child in #field.iter() {
+                    let child = #encode_with(child, #ctx)?;
+                    #node.children.get_or_insert_with(Vec::new).push(child);
+                }
+            }
+        } else {
+            quote! {
+                let _ = <#ty as ::kfl::traits::EncodePartial>
+                    ::encode_partial(&#field, #node, #ctx)?;
+            }
+        };
+        let encode = match &child_def.default {
+            Some(default_value) if !encode_defaults => {
+                let default = if let Some(expr) = default_value {
+                    quote!(#expr)
+                } else {
+                    quote!(::std::default::Default::default())
+                };
+                quote! {
+                    let default: #ty = #default;
+                    if default != #field {
+                        #encode
+                    }
+                }
+            }
+            _ => encode,
+        };
+        branches.push(skip_encode_if(&child_def.field, &quote!(&#field), encode));
+    }
+    if let Some(other) = &s.object.other_children {
+        let field = other.field.from_self();
         branches.push(quote! {
-            let _ = <#ty as ::kfl::traits::EncodePartial>
-                ::encode_partial(&#field, #node, #ctx)?;
+            for child in #field.iter() {
+                #node.children.get_or_insert_with(Vec::new)
+                    .push(child.clone());
+            }
         });
     }
     branches.push(quote!(Ok(())));
@@ -810,147 +1465,113 @@ fn encode_partial(s: &Common, node: &syn::Ident) -> syn::Result<TokenStream> {
 pub(crate) fn encode_children(s: &Common, node: &syn::Ident, _err_span: Option<TokenStream>)
     -> syn::Result<TokenStream>
 {
-    if s.object.children.is_empty() {
+    if s.object.children.is_empty() && s.object.other_children.is_none() {
         return Ok(quote!());
     }
-    // let mut declare_empty = Vec::new();
     let mut encodes = Vec::new();
-    // let mut postprocess = Vec::new();
 
     let ctx = s.ctx;
+    let encode_defaults = s.object.trait_props.encode_defaults;
     let children = syn::Ident::new("children", Span::mixed_site());
     let child = syn::Ident::new("child", Span::mixed_site());
     for child_def in &s.object.children {
         let field = &child_def.field.from_self();
         let ty = &child_def.field.ty;
-        match child_def.mode {
+        let encode = match child_def.mode {
             ChildMode::Flatten => {
-                // declare_empty.push(quote! {
-                //     let mut #field = ::std::default::Default::default();
-                // });
-                encodes.push(quote! {
-                    let _ = <#ty as ::kfl::traits::EncodePartial>
-                        ::encode_partial(&#field, &mut #node, #ctx);
-                });
+                // properties go straight into the node, children are
+                // collected in order with the rest of the children
+                quote! {
+                    <#ty as ::kfl::traits::EncodePartial>
+                        ::encode_partial(&#field, &mut #node, #ctx)?;
+                    if let Some(flattened) = #node.children.take() {
+                        #children.extend(flattened);
+                    }
+                }
             }
             ChildMode::Multi => {
-                // declare_empty.push(quote! {
-                //     let mut #field = Vec::new();
-                // });
-                let ctx = &s.ctx;
-                if let Some(default_value) = &child_def.default {
-                    let default = if let Some(expr) = default_value {
-                        quote!(#expr)
-                    } else {
-                        quote!(::std::default::Default::default())
-                    };
-                    encodes.push(quote! {
-                        let default: #ty = #default;
-                        if default != #field {
-                            for #child in #field.iter() {
-                                let #child = <<#ty as IntoIterator>::Item as ::kfl::traits::Encode>
-                                ::encode(&#child, #ctx)?;
-                                #children.push(#child);
-                            }
-                        }
-                        // else if let Ok(true) = <Vec<<#ty as IntoIterator>::Item> as ::kfl::traits::EncodePartial>
-                        //     ::encode_partial(&#field, &mut #output, #ctx)
-                        // {
-                        //     None
-                        // }
-                    });
-                    // postprocess.push(quote! {
-                    //     let #field = if #field.is_empty() {
-                    //         #default
-                    //     } else {
-                    //         #field.into_iter().collect()
-                    //     };
-                    // });
-                } else {
-                    // postprocess.push(quote! {
-                    //     let #field = #field.into_iter().collect();
-                    // });
-                    encodes.push(quote! {
-                        for #child in #field.iter() {
-                            let #child = <<#ty as IntoIterator>::Item as ::kfl::traits::Encode>
-                            ::encode(&#child, #ctx)?;
-                            #children.push(#child);
-                        }
-                    });
+                let item = children_item(ty);
+                let encode_item = match &child_def.field.codec.encode {
+                    Some(encode_with) => quote!(#encode_with(#child, #ctx)?),
+                    None => quote! {
+                        <#item as ::kfl::traits::Encode>::encode(&#child, #ctx)?
+                    },
+                };
+                quote! {
+                    for #child in #field.iter() {
+                        let #child = #encode_item;
+                        #children.push(#child);
+                    }
+                }
+            }
+            ChildMode::Map(key) => {
+                let encode_child = encode_child_fn(key);
+                quote! {
+                    for (key, value) in #field.iter() {
+                        let #child = ::kfl::decode::#encode_child(
+                            key, value, #ctx)?;
+                        #children.push(#child);
+                    }
                 }
             }
             ChildMode::Normal => {
-                // declare_empty.push(quote! {
-                //     let mut #field = None;
-                // });
-                // let req_msg = format!(
-                //     "child node for struct field `{}` is required",
-                //     &field.to_string());
-                if let Some(default_value) = &child_def.default {
-                    let default = if let Some(expr) = default_value {
-                        quote!(#expr)
-                    } else {
-                        quote!(::std::default::Default::default())
-                    };
-                    encodes.push(quote! {
-                        let default: #ty = #default;
-                        if default != #field {
-                            let #child = <#ty as ::kfl::traits::Encode>
-                            ::encode(&#field, #ctx)?;
-                            #children.push(#child);
+                // `None` has no representation as a node, so it's always
+                // omitted
+                match (option_inner(ty), &child_def.field.codec.encode) {
+                    (Some(_), Some(encode_with)) => quote! {
+                        if let Some(#child) = &#field {
+                            #children.push(#encode_with(#child, #ctx)?);
                         }
-                    });
-                    // postprocess.push(quote! {
-                    //     let #field = #field.unwrap_or_else(|| #default);
-                    // });
-                } else {
-                    // if let Some(span) = &err_span {
-                    //     postprocess.push(quote! {
-                    //         let #field = #field.ok_or_else(|| {
-                    //             ::kfl::errors::EncodeError::Missing {
-                    //                 span: #span.clone(),
-                    //                 message: #req_msg.into(),
-                    //             }
-                    //         })?;
-                    //     });
-                    // } else {
-                    //     postprocess.push(quote! {
-                    //         let #field = #field.ok_or_else(|| {
-                    //             ::kfl::errors::EncodeError::MissingNode {
-                    //                 message: #req_msg.into(),
-                    //             }
-                    //         })?;
-                    //     });
-                    // }
-                    encodes.push(quote! {
+                    },
+                    (Some(inner), None) => quote! {
+                        if let Some(#child) = &#field {
+                            #children.push(<#inner as ::kfl::traits::Encode>
+                                ::encode(#child, #ctx)?);
+                        }
+                    },
+                    (None, Some(encode_with)) => quote! {
+                        #children.push(#encode_with(&#field, #ctx)?);
+                    },
+                    (None, None) => quote! {
                         let #child = <#ty as ::kfl::traits::Encode>
                         ::encode(&#field, #ctx)?;
                         #children.push(#child);
-                    });
+                    },
                 }
             }
-        }
+        };
+        let encode = match &child_def.default {
+            Some(default_value) if !encode_defaults => {
+                let default = if let Some(expr) = default_value {
+                    quote!(#expr)
+                } else {
+                    quote!(::std::default::Default::default())
+                };
+                quote! {
+                    let default: #ty = #default;
+                    if default != #field {
+                        #encode
+                    }
+                }
+            }
+            _ => encode,
+        };
+        encodes.push(skip_encode_if(&child_def.field, &quote!(&#field),
+                                    encode));
+    }
+    if let Some(other) = &s.object.other_children {
+        let field = other.field.from_self();
+        encodes.push(quote! {
+            for #child in #field.iter() {
+                #children.push(#child.clone());
+            }
+        });
     }
-    // TODO(rnarkk) return Err?
-    // encodes.push(quote! {
-    //     else {
-    //         #ctx.emit_error(::kfl::errors::EncodeError::unexpected(
-    //             #ctx.span(&#child), "node",
-    //             format!("unexpected node `{}`",
-    //                     #child.node_name.as_ref())));
-    //         None
-    //     }
-    // });
     Ok(quote! {
-        // #(#declare_empty)*
         let mut #children = Vec::new();
         #(#encodes)*
         if !#children.is_empty() {
             #node.children = Some(#children);
         }
-        // #children.iter().flat_map(|#child| {
-        //     #(#encodes)*
-        // }).collect::<Result<(), ::kfl::errors::EncodeError>>()?;
-        // #(#postprocess)*
     })
 }
@@ -12,9 +12,12 @@ use syn::{
     spanned::Spanned
 };
 
+use crate::kw;
+
 pub enum Scalar {
     // Struct(Struct),
     Enum(Enum),
+    Units(Units),
 }
 
 // pub struct Struct {
@@ -38,6 +41,12 @@ pub struct Variant {
     pub name: String,
 }
 
+/// Numeric new-type with `#[kfl(units = path)]`
+pub struct Units {
+    pub ident: syn::Ident,
+    pub units: syn::Path,
+}
+
 impl Enum {
     fn new(ident: syn::Ident, _attrs: Vec<syn::Attribute>,
            src_variants: impl Iterator<Item = syn::Variant>)
@@ -60,6 +69,35 @@ impl Enum {
     }
 }
 
+impl Units {
+    fn new(item: syn::ItemStruct, attrs: Vec<syn::Attribute>)
+        -> syn::Result<Self>
+    {
+        if !matches!(&item.fields,
+                     syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1)
+        {
+            return Err(syn::Error::new(item.span(),
+                "only new-types with a single unnamed field are allowed for \
+                 DecodeScalar"));
+        }
+        let mut units = None;
+        for attr in &attrs {
+            if matches!(attr.style, syn::AttrStyle::Outer) &&
+                attr.path.is_ident("kfl")
+            {
+                units = Some(attr.parse_args_with(|input: ParseStream| {
+                    let _kw: kw::units = input.parse()?;
+                    let _eq: syn::Token![=] = input.parse()?;
+                    input.parse::<syn::Path>()
+                })?);
+            }
+        }
+        let units = units.ok_or_else(|| syn::Error::new(item.span(),
+            "new-types need `#[kfl(units = path)]` for DecodeScalar"))?;
+        Ok(Units { ident: item.ident, units })
+    }
+}
+
 impl Parse for Scalar {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut attrs = input.call(syn::Attribute::parse_outer)?;
@@ -72,6 +110,10 @@ impl Parse for Scalar {
             attrs.extend(item.attrs);
             Enum::new(item.ident, attrs, item.variants.into_iter())
                 .map(Scalar::Enum)
+        } else if lookahead.peek(syn::Token![struct]) {
+            let mut item: syn::ItemStruct = input.parse()?;
+            attrs.extend(core::mem::take(&mut item.attrs));
+            Units::new(item, attrs).map(Scalar::Units)
         } else {
             Err(lookahead.error())
         }
@@ -80,10 +122,27 @@ impl Parse for Scalar {
 
 pub fn emit_decode_scalar(s: &Scalar) -> syn::Result<TokenStream> {
     match s {
-        Scalar::Enum(e) => emit_decode_enum(e)
+        Scalar::Enum(e) => emit_decode_enum(e),
+        Scalar::Units(u) => emit_decode_units(u),
     }
 }
 
+pub fn emit_decode_units(u: &Units) -> syn::Result<TokenStream> {
+    let s_name = &u.ident;
+    let units = &u.units;
+    Ok(quote! {
+        impl ::kfl::traits::DecodeScalar for #s_name {
+            fn decode(scalar: &::kfl::ast::Scalar,
+                      ctx: &mut ::kfl::context::Context)
+                -> Result<Self, ::kfl::errors::DecodeError>
+            {
+                ::kfl::units::decode::<#units, _>(
+                    scalar, stringify!(#s_name), ctx).map(#s_name)
+            }
+        }
+    })
+}
+
 pub fn emit_decode_enum(e: &Enum) -> syn::Result<TokenStream> {
     let e_name = &e.ident;
     let value_err = if e.variants.len() <= 3 {
@@ -130,10 +189,25 @@ pub fn emit_decode_enum(e: &Enum) -> syn::Result<TokenStream> {
 
 pub fn emit_encode_scalar(s: &Scalar) -> syn::Result<TokenStream> {
     match s {
-        Scalar::Enum(e) => emit_encode_enum(e)
+        Scalar::Enum(e) => emit_encode_enum(e),
+        Scalar::Units(u) => emit_encode_units(u),
     }
 }
 
+pub fn emit_encode_units(u: &Units) -> syn::Result<TokenStream> {
+    let s_name = &u.ident;
+    let units = &u.units;
+    Ok(quote! {
+        impl ::kfl::traits::EncodeScalar for #s_name {
+            fn encode(&self, ctx: &mut ::kfl::context::Context)
+                -> Result<::kfl::ast::Scalar, ::kfl::errors::EncodeError>
+            {
+                ::kfl::units::encode::<#units, _>(&self.0, ctx)
+            }
+        }
+    })
+}
+
 pub fn emit_encode_enum(e: &Enum) -> syn::Result<TokenStream> {
     let e_name = &e.ident;
     // let value_err = if e.variants.len() <= 3 {
@@ -155,7 +229,8 @@ pub fn emit_encode_enum(e: &Enum) -> syn::Result<TokenStream> {
             quote! {
                 #e_name::#ident => Ok(::kfl::ast::Scalar {
                     type_name: None,
-                    literal: #name.to_owned().into_boxed_str()
+                    literal: #name.to_owned().into_boxed_str(),
+                    quoted: false,
                 })
             }
         });
@@ -29,14 +29,31 @@ pub fn emit_decode_enum(e: &Enum) -> syn::Result<TokenStream> {
     };
     let check_type = check_type(&common, &node)?;
     let decode = decode(&common, &node)?;
+    let body = if e.trait_props.validate.is_empty() {
+        quote! {
+            #check_type
+            #decode
+        }
+    } else {
+        let value = syn::Ident::new("value", Span::mixed_site());
+        let validate = node::validate_container(&e.trait_props, &value,
+                                                &node, &ctx);
+        quote! {
+            let #value = (|| -> Result<Self, ::kfl::errors::DecodeError> {
+                #check_type
+                #decode
+            })()?;
+            #validate
+            Ok(#value)
+        }
+    };
     Ok(quote! {
         impl #impl_gen ::kfl::traits::Decode for #name #type_gen #bounds {
             fn decode(#node: &::kfl::ast::Node,
                       #ctx: &mut ::kfl::context::Context)
                 -> Result<Self, ::kfl::errors::DecodeError>
             {
-                #check_type
-                #decode
+                #body
             }
         }
     })
@@ -45,20 +62,43 @@ pub fn emit_decode_enum(e: &Enum) -> syn::Result<TokenStream> {
 fn check_type(s: &Common, node: &syn::Ident) -> syn::Result<TokenStream> {
     let ctx = s.ctx;
     let name = crate::to_kebab_case(&s.object.ident.unraw());
+    let mismatch = if let Some(decode_other) = decode_other(s, node) {
+        decode_other
+    } else {
+        quote! {
+            Err(::kfl::errors::DecodeError::unexpected(
+                #ctx.span(&#node), "node", format!("unexpected node `({}){}`",
+                type_name,
+                #node.node_name.as_ref())
+            ))
+        }
+    };
     Ok(quote! {
         if let Some(type_name) = #node.type_name.as_ref() {
             let type_name = type_name.as_ref();
             if type_name != #name {
-                return Err(::kfl::errors::DecodeError::unexpected(
-                    #ctx.span(&#node), "node", format!("unexpected node `({}){}`",
-                    type_name,
-                    #node.node_name.as_ref())
-                ))
+                return #mismatch;
             }
         }
     })
 }
 
+/// Decodes the node into the `other` variant if the enum has one
+fn decode_other(e: &Common, node: &syn::Ident) -> Option<TokenStream> {
+    let ctx = e.ctx;
+    let enum_name = &e.object.ident;
+    e.object.variants.iter().find_map(|variant| match &variant.kind {
+        VariantKind::Other { ty } => {
+            let variant_name = &variant.ident;
+            Some(quote! {
+                <#ty as ::kfl::traits::Decode>::decode(#node, #ctx)
+                    .map(#enum_name::#variant_name)
+            })
+        }
+        _ => None
+    })
+}
+
 fn decode(e: &Common, node: &syn::Ident) -> syn::Result<TokenStream> {
     let ctx = e.ctx;
     let mut branches = Vec::with_capacity(e.object.variants.len());
@@ -122,8 +162,17 @@ fn decode(e: &Common, node: &syn::Ident) -> syn::Result<TokenStream> {
                 )?;
                 branches.push(quote!(#name => { #decode_variant }));
             },
+            VariantKind::Other { .. } => {}
         }
     }
+    if let Some(decode_other) = decode_other(e, node) {
+        return Ok(quote! {
+            match &*#node.node_name {
+                #(#branches)*
+                _ => #decode_other,
+            }
+        });
+    }
     // TODO(tailhook) use strsim to find similar names
     let err = if e.object.variants.len() <= 3 {
         format!("expected one of {}",
@@ -269,6 +318,13 @@ fn encode(e: &Enum, node: &syn::Ident, ctx: &syn::Ident)
                     #enum_name::#variant_pattern => { #encode_variant }
                 });
             },
+            VariantKind::Other { ty } => {
+                branches.push(quote! {
+                    #enum_name::#ident(other) => {
+                        <#ty as ::kfl::traits::Encode>::encode(other, #ctx)
+                    }
+                });
+            }
         }
     }
     // TODO(tailhook) use strsim to find similar names
@@ -285,6 +341,7 @@ fn encode(e: &Enum, node: &syn::Ident, ctx: &syn::Ident)
     //             e.object.variants.len() - 2)
     // };
     branches.push(quote! {
+        #[allow(unreachable_patterns)]
         variant => Err(::kfl::errors::EncodeError::extra_variant(
                        format!("{:?}", &variant)))
     });
@@ -15,14 +15,34 @@
 use alloc::{
     borrow::ToOwned,
     boxed::Box,
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet, btree_map::Entry},
+    format,
+    string::String,
     vec::Vec
 };
-use core::fmt::{self, Debug, Pointer};
+use core::{
+    fmt::{self, Debug, Pointer},
+    hash::{Hash, Hasher},
+    mem
+};
 
-use crate::own;
+use crate::{
+    errors::EncodeError,
+    own,
+    span::Span,
+    value::{Literal, Value}
+};
 
 /// Single node of the KDL document
+///
+/// Nodes compare and hash by meaning rather than spelling: scalars are
+/// compared as [`Value`]s, so `0x10` equals `16` and `"a"` equals `a`, and
+/// properties are compared by their last value regardless of order.
+/// Arguments and children are still compared in order.
+///
+/// This differs from [`Scalar`], whose `==` compares spelling: two equal
+/// nodes may hold arguments that are not `==` to each other. Use
+/// [`Scalar::same_value`] to compare single scalars the way nodes do.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
 pub struct Node {
@@ -35,15 +55,48 @@ pub struct Node {
     /// Positional arguments
     #[cfg_attr(feature = "minicbor", n(2))]
     pub arguments: Vec<Scalar>,
-    /// Named properties
+    /// Named properties in the order they are written
     #[cfg_attr(feature = "minicbor", n(3))]
-    pub properties: BTreeMap<Box<str>, Scalar>,
+    pub properties: Properties,
     /// Node's children. This field is not none if there are braces `{..}`
     #[cfg_attr(feature = "minicbor", n(4))]
     pub children: Option<Vec<Node>>,
 }
 
+/// Named properties of a node in the order they are written
+///
+/// Repeated names are kept along with their spans, see
+/// [`duplicates`](Properties::duplicates). Lookups and iteration follow the
+/// KDL specification where the last value wins, but the name stays at the
+/// position it first appears.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
+pub struct Properties {
+    #[cfg_attr(feature = "minicbor", n(0))]
+    entries: Vec<Property>,
+}
+
+/// Single property as it is written in the source
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
+pub struct Property {
+    /// A property name
+    #[cfg_attr(feature = "minicbor", n(0))]
+    pub name: Box<str>,
+    /// A property value
+    #[cfg_attr(feature = "minicbor", n(1))]
+    pub value: Scalar,
+    /// Position in the source, `Span(0, 0)` if the property is not parsed
+    #[cfg_attr(feature = "minicbor", n(2))]
+    pub span: Span,
+}
+
 /// Possibly typed KDL scalar value
+///
+/// Scalars compare by spelling, so `0x10 != 16` and `"a" != a` even though
+/// [`Node`]s holding them compare equal. Use
+/// [`same_value`](Scalar::same_value) or convert them into [`Value`] to
+/// compare by meaning.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
 pub struct Scalar {
@@ -51,8 +104,18 @@ pub struct Scalar {
     #[cfg_attr(feature = "minicbor", n(0))]
     pub type_name: Option<Box<str>>,
     /// The actual value literal
+    ///
+    /// Numbers are kept as written in the source, so no precision is lost
+    /// before they are converted into the target type.
     #[cfg_attr(feature = "minicbor", n(1))]
     pub literal: Box<str>,
+    /// Whether the literal was written as a quoted string
+    ///
+    /// Keywords such as `null` are only recognised when this is `false`,
+    /// so `"null"` stays a string. The `literal` of a quoted string is kept
+    /// unescaped and without the quotes, both when parsed and when encoded.
+    #[cfg_attr(feature = "minicbor", n(2))]
+    pub quoted: bool,
 }
 
 impl Node {
@@ -62,10 +125,42 @@ impl Node {
             type_name: None,
             node_name: own!(name),
             arguments: Vec::new(),
-            properties: BTreeMap::new(),
+            properties: Properties::new(),
             children: None,
         }
     }
+    /// Starts building a node, e.g.
+    /// `Node::build("route").arg("/api").prop("port", 80)`
+    pub fn build(name: &str) -> Self {
+        Self::new(name)
+    }
+    /// Sets the type name of the node
+    pub fn typed(mut self, type_name: &str) -> Self {
+        self.type_name = Some(own!(type_name));
+        self
+    }
+    /// Appends an argument
+    pub fn arg(mut self, value: impl Into<Scalar>) -> Self {
+        self.arguments.push(value.into());
+        self
+    }
+    /// Sets a property
+    pub fn prop(mut self, name: &str, value: impl Into<Scalar>) -> Self {
+        self.properties.insert(own!(name), value.into());
+        self
+    }
+    /// Appends a child
+    pub fn child(mut self, child: Node) -> Self {
+        self.children.get_or_insert_with(Vec::new).push(child);
+        self
+    }
+    /// Appends the children, adding braces even if there are none
+    pub fn with_children(mut self, children: impl IntoIterator<Item = Node>)
+        -> Self
+    {
+        self.children.get_or_insert_with(Vec::new).extend(children);
+        self
+    }
     /// Returns node children
     pub fn children(&self)
         -> impl Iterator<Item = &Node> +
@@ -73,21 +168,428 @@ impl Node {
     {
         self.children.as_ref().map(|c| c.iter()).unwrap_or_else(|| [].iter())
     }
+    /// Returns the argument at `index`
+    pub fn argument(&self, index: usize) -> Option<&Scalar> {
+        self.arguments.get(index)
+    }
+    /// Appends an argument
+    pub fn push_arg(&mut self, value: impl Into<Scalar>) {
+        self.arguments.push(value.into());
+    }
+    /// Inserts an argument at `index`, panics if `index` is out of bounds
+    pub fn insert_arg(&mut self, index: usize, value: impl Into<Scalar>) {
+        self.arguments.insert(index, value.into());
+    }
+    /// Replaces the argument at `index` and returns the old one, panics if
+    /// `index` is out of bounds
+    pub fn replace_arg(&mut self, index: usize, value: impl Into<Scalar>)
+        -> Scalar
+    {
+        core::mem::replace(&mut self.arguments[index], value.into())
+    }
+    /// Removes the argument at `index`, panics if `index` is out of bounds
+    pub fn remove_arg(&mut self, index: usize) -> Scalar {
+        self.arguments.remove(index)
+    }
+    /// Returns the property `name`
+    pub fn property(&self, name: &str) -> Option<&Scalar> {
+        self.properties.get(name)
+    }
+    /// Sets the property and returns the old value if there was one
+    pub fn insert_prop(&mut self, name: &str, value: impl Into<Scalar>)
+        -> Option<Scalar>
+    {
+        self.properties.insert(own!(name), value.into())
+    }
+    /// Replaces the value of an existing property and returns the old one,
+    /// does nothing if there is no such property
+    pub fn replace_prop(&mut self, name: &str, value: impl Into<Scalar>)
+        -> Option<Scalar>
+    {
+        self.properties.get_mut(name)
+            .map(|old| core::mem::replace(old, value.into()))
+    }
+    /// Removes the property and returns its value
+    pub fn remove_prop(&mut self, name: &str) -> Option<Scalar> {
+        self.properties.remove(name)
+    }
+    /// Appends a child, adding braces if there were none
+    pub fn push_child(&mut self, child: Node) {
+        self.children.get_or_insert_with(Vec::new).push(child);
+    }
+    /// Inserts a child at `index`, panics if `index` is out of bounds
+    pub fn insert_child(&mut self, index: usize, child: Node) {
+        self.children.get_or_insert_with(Vec::new).insert(index, child);
+    }
+    /// Replaces the child at `index` and returns the old one, panics if
+    /// `index` is out of bounds
+    pub fn replace_child(&mut self, index: usize, child: Node) -> Node {
+        let children = self.children.as_mut().expect("node has no children");
+        core::mem::replace(&mut children[index], child)
+    }
+    /// Removes the child at `index`, panics if `index` is out of bounds
+    pub fn remove_child(&mut self, index: usize) -> Node {
+        self.children.as_mut().expect("node has no children").remove(index)
+    }
+    /// Rewrites the node and its children in canonical form
+    ///
+    /// Scalars are respelled from their value, repeated properties are
+    /// reduced to the last one and properties are sorted by name. The result
+    /// is equal to the original node.
+    pub fn canonicalize(&mut self) {
+        for argument in self.arguments.iter_mut() {
+            argument.canonicalize();
+        }
+        self.properties.canonicalize();
+        for child in self.children.iter_mut().flatten() {
+            child.canonicalize();
+        }
+    }
+}
+
+impl Properties {
+    /// Creates an empty set of properties
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+    /// Number of distinct property names
+    pub fn len(&self) -> usize {
+        self.distinct().len()
+    }
+    /// Returns `true` if there are no properties
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Returns the last value of the property `name`
+    pub fn get(&self, name: &str) -> Option<&Scalar> {
+        self.entries.iter().rev()
+            .find(|p| &*p.name == name)
+            .map(|p| &p.value)
+    }
+    /// Returns the last value of the property `name` for modification
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Scalar> {
+        self.entries.iter_mut().rev()
+            .find(|p| &*p.name == name)
+            .map(|p| &mut p.value)
+    }
+    /// Returns the first value of the property `name`
+    pub fn first(&self, name: &str) -> Option<&Scalar> {
+        self.entries.iter()
+            .find(|p| &*p.name == name)
+            .map(|p| &p.value)
+    }
+    /// Sets the property and returns the old value if there was one
+    ///
+    /// An existing property keeps its position and loses its duplicates, a
+    /// new one is appended.
+    pub fn insert(&mut self, name: Box<str>, value: Scalar) -> Option<Scalar> {
+        let old = self.remove_duplicates(&name);
+        match self.entries.iter_mut().find(|p| p.name == name) {
+            Some(property) => {
+                let first = core::mem::replace(&mut property.value, value);
+                Some(old.unwrap_or(first))
+            }
+            None => {
+                self.entries.push(Property { name, value, span: Span(0, 0) });
+                None
+            }
+        }
+    }
+    /// Appends the property, keeping earlier values of the same name as
+    /// duplicates
+    pub fn push(&mut self, property: Property) {
+        self.entries.push(property);
+    }
+    /// Removes all values of the property and returns the last one
+    pub fn remove(&mut self, name: &str) -> Option<Scalar> {
+        let mut removed = None;
+        self.entries.retain(|p| if &*p.name == name {
+            removed = Some(p.value.clone());
+            false
+        } else {
+            true
+        });
+        removed
+    }
+    /// Iterates over distinct names with their last value, in the order
+    /// names first appear
+    pub fn iter(&self) -> impl Iterator<Item = (&Box<str>, &Scalar)> + '_ {
+        self.distinct().into_iter().map(move |(first, last)| {
+            (&self.entries[first].name, &self.entries[last].value)
+        })
+    }
+    /// All properties as written, including duplicates
+    pub fn entries(&self) -> &[Property] {
+        &self.entries
+    }
+    /// All properties as written, for modification in place
+    pub fn entries_mut(&mut self) -> &mut [Property] {
+        &mut self.entries
+    }
+    /// Iterates over properties whose name has already appeared before
+    pub fn duplicates(&self) -> impl Iterator<Item = &Property> + '_ {
+        let mut seen = BTreeSet::new();
+        self.entries.iter().filter(move |p| !seen.insert(&*p.name))
+    }
+    /// Keeps the last value of each name, sorts them by name and writes the
+    /// values in canonical form
+    pub fn canonicalize(&mut self) {
+        let distinct = self.distinct();
+        let mut all = mem::take(&mut self.entries).into_iter().map(Some)
+            .collect::<Vec<_>>();
+        let mut entries = distinct.into_iter()
+            .filter_map(|(_, last)| all[last].take())
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        for property in entries.iter_mut() {
+            property.value.canonicalize();
+        }
+        self.entries = entries;
+    }
+    /// Distinct names with their last value, sorted by name
+    fn sorted(&self) -> Vec<(&Box<str>, &Scalar)> {
+        let mut properties = self.iter().collect::<Vec<_>>();
+        properties.sort_by(|a, b| a.0.cmp(b.0));
+        properties
+    }
+    /// Indices of the first and the last entry of each name, in the order
+    /// names first appear
+    fn distinct(&self) -> Vec<(usize, usize)> {
+        let mut positions = BTreeMap::<&str, usize>::new();
+        let mut distinct = Vec::<(usize, usize)>::new();
+        for (index, property) in self.entries.iter().enumerate() {
+            match positions.entry(&property.name) {
+                Entry::Occupied(entry) => distinct[*entry.get()].1 = index,
+                Entry::Vacant(entry) => {
+                    entry.insert(distinct.len());
+                    distinct.push((index, index));
+                }
+            }
+        }
+        distinct
+    }
+    /// Removes all but the first entry of `name`, returns the last removed
+    /// value
+    fn remove_duplicates(&mut self, name: &str) -> Option<Scalar> {
+        let mut seen = false;
+        let mut removed = None;
+        self.entries.retain(|p| {
+            if &*p.name != name {
+                true
+            } else if !seen {
+                seen = true;
+                true
+            } else {
+                removed = Some(p.value.clone());
+                false
+            }
+        });
+        removed
+    }
+}
+
+impl<'a> IntoIterator for &'a Properties {
+    type Item = (&'a Box<str>, &'a Scalar);
+    type IntoIter = Box<dyn Iterator<Item = Self::Item> + 'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl IntoIterator for Properties {
+    type Item = Property;
+    type IntoIter = alloc::vec::IntoIter<Property>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl FromIterator<(Box<str>, Scalar)> for Properties {
+    fn from_iter<I>(iter: I) -> Self
+        where I: IntoIterator<Item = (Box<str>, Scalar)>
+    {
+        let all = iter.into_iter()
+            .map(|(name, value)| Property { name, value, span: Span(0, 0) })
+            .collect::<Properties>();
+        // Same as inserting one by one: first position, last value
+        let entries = all.distinct().into_iter()
+            .map(|(first, last)| Property {
+                value: all.entries[last].value.clone(),
+                ..all.entries[first].clone()
+            })
+            .collect();
+        Properties { entries }
+    }
+}
+
+impl FromIterator<Property> for Properties {
+    fn from_iter<I: IntoIterator<Item = Property>>(iter: I) -> Self {
+        Properties { entries: iter.into_iter().collect() }
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_name == other.type_name
+            && self.node_name == other.node_name
+            && self.arguments.len() == other.arguments.len()
+            && self.arguments.iter().zip(other.arguments.iter())
+                .all(|(a, b)| a.same_value(b))
+            && self.properties == other.properties
+            && self.children == other.children
+    }
+}
+
+impl Eq for Node {}
+
+impl Hash for Node {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.type_name.hash(state);
+        self.node_name.hash(state);
+        self.arguments.len().hash(state);
+        for argument in self.arguments.iter() {
+            Value::from(argument).hash(state);
+        }
+        self.properties.hash(state);
+        self.children.hash(state);
+    }
+}
+
+/// Compares the last value of each name, ignoring order and duplicates
+impl PartialEq for Properties {
+    fn eq(&self, other: &Self) -> bool {
+        let this = self.sorted();
+        let other = other.sorted();
+        this.len() == other.len()
+            && this.iter().zip(other.iter()).all(|(a, b)| {
+                a.0 == b.0 && a.1.same_value(b.1)
+            })
+    }
+}
+
+impl Eq for Properties {}
+
+impl Hash for Properties {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let properties = self.sorted();
+        properties.len().hash(state);
+        for (name, value) in properties {
+            name.hash(state);
+            Value::from(value).hash(state);
+        }
+    }
 }
 
 impl Scalar {
     ///
     pub fn new(type_name: Box<str>, literal: Box<str>) -> Self {
-        Self { type_name: Some(type_name), literal }
+        Self { type_name: Some(type_name), literal, quoted: false }
+    }
+    /// Creates a quoted string
+    pub fn string(value: &str) -> Self {
+        Self { type_name: None, literal: own!(value), quoted: true }
+    }
+    /// Creates a bare literal such as a number or a keyword
+    pub fn bare(literal: &str) -> Self {
+        Self { type_name: None, literal: own!(literal), quoted: false }
+    }
+    /// Sets the type name of the scalar
+    pub fn with_type(mut self, type_name: &str) -> Self {
+        self.type_name = Some(own!(type_name));
+        self
+    }
+    /// Returns `true` if the scalars mean the same value, regardless of the
+    /// radix of numbers or the quoting of strings
+    ///
+    /// This is how [`Node`] compares its arguments and properties.
+    pub fn same_value(&self, other: &Scalar) -> bool {
+        Value::from(self) == Value::from(other)
+    }
+    /// Returns `true` if the scalar is the `null` keyword
+    pub fn is_null(&self) -> bool {
+        !self.quoted && self.literal.as_ref() == "null"
+    }
+    /// Respells the scalar from its value: strings are quoted, numbers are
+    /// written in decimal and the type name is kept
+    pub fn canonicalize(&mut self) {
+        let value = Value::from(&*self);
+        let (literal, quoted) = match &value.literal {
+            Literal::String(string) => (string.clone(), true),
+            literal => match literal.to_literal() {
+                Ok(literal) => (literal, false),
+                // keep the spelling of numbers too large for a float
+                Err(_) => return,
+            },
+        };
+        self.literal = literal;
+        self.quoted = quoted;
     }
 }
 
 impl From<Box<str>> for Scalar {
     fn from(value: Box<str>) -> Self {
-        Scalar { type_name: None, literal: value }
+        Scalar { type_name: None, literal: value, quoted: true }
+    }
+}
+
+impl From<&str> for Scalar {
+    fn from(value: &str) -> Self {
+        Scalar::string(value)
+    }
+}
+
+impl From<String> for Scalar {
+    fn from(value: String) -> Self {
+        Scalar { type_name: None, literal: value.into(), quoted: true }
     }
 }
 
+impl From<bool> for Scalar {
+    fn from(value: bool) -> Self {
+        Scalar::bare(if value { "true" } else { "false" })
+    }
+}
+
+impl<T: Into<Scalar>> From<Option<T>> for Scalar {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or_else(|| Scalar::bare("null"))
+    }
+}
+
+macro_rules! impl_from_number {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Scalar {
+                fn from(value: $ty) -> Self {
+                    Scalar::bare(&format!("{:?}", value))
+                }
+            }
+        )*
+    }
+}
+
+impl_from_number!(i8, i16, i32, i64, i128, isize,
+                  u8, u16, u32, u64, u128, usize);
+
+/// Floats only convert if they are finite, as KDL has no NaN or infinity
+macro_rules! impl_try_from_float {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<$ty> for Scalar {
+                type Error = EncodeError;
+                fn try_from(value: $ty) -> Result<Self, EncodeError> {
+                    if !value.is_finite() {
+                        return Err(EncodeError::non_finite(f64::from(value)));
+                    }
+                    Ok(Scalar::bare(&format!("{:?}", value)))
+                }
+            }
+        )*
+    }
+}
+
+impl_try_from_float!(f32, f64);
+
 macro_rules! impl_pointer {
     ($ty:ty) => {
         impl Pointer for $ty {
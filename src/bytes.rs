@@ -0,0 +1,167 @@
+//! Codecs for byte buffers
+//!
+//! `Vec<u8>`, `Box<[u8]>` and `[u8; N]` decode `(base64)` and `(hex)`
+//! strings, or take the bytes of the string as is if there is no type
+//! annotation. They are encoded as `(base64)` if the `base64` feature is
+//! enabled and as `(hex)` otherwise. To choose the encoding of a single field
+//! use one of the modules here with `with`:
+//!
+//! ```rust
+//! #[derive(kfl::Decode, kfl::Encode, Debug)]
+//! struct Key {
+//!     #[kfl(argument, with = kfl::bytes::hex)]
+//!     fingerprint: [u8; 4],
+//! }
+//! ```
+
+use alloc::{
+    format,
+    string::String,
+    vec::Vec
+};
+use core::fmt::Write;
+
+use crate::{
+    ast::Scalar,
+    context::Context,
+    decode::{check_scalar_kind, check_scalar_type},
+    errors::{DecodeError, EncodeError},
+};
+
+/// Decodes the bytes of the scalar according to its type annotation
+pub(crate) fn decode_bytes(scalar: &Scalar, ctx: &mut Context)
+    -> Result<Vec<u8>, DecodeError>
+{
+    check_scalar_type(scalar, &["base64", "hex"], "bytes", ctx)?;
+    check_scalar_kind(scalar, "string", true, ctx)?;
+    match scalar.type_name.as_deref() {
+        Some("base64") => decode_base64(scalar, ctx),
+        Some(_) => decode_hex(scalar, ctx),
+        None => Ok(scalar.literal.as_bytes().to_vec()),
+    }
+}
+
+/// Encodes the bytes with the default encoding
+pub(crate) fn encode_bytes(bytes: &[u8]) -> Scalar {
+    #[cfg(feature = "base64")] {
+        encode_base64(bytes)
+    }
+    #[cfg(not(feature = "base64"))] {
+        encode_hex(bytes)
+    }
+}
+
+/// Converts the decoded bytes into the target type, e.g. an array
+pub(crate) fn convert_bytes<T>(bytes: Vec<u8>, scalar: &Scalar, ctx: &Context)
+    -> Result<T, DecodeError>
+    where T: TryFrom<Vec<u8>>,
+{
+    let len = bytes.len();
+    T::try_from(bytes).map_err(|_| {
+        DecodeError::conversion(ctx.span(&scalar),
+            format!("unexpected number of bytes: {}", len))
+    })
+}
+
+fn decode_base64(scalar: &Scalar, ctx: &mut Context)
+    -> Result<Vec<u8>, DecodeError>
+{
+    #[cfg(feature = "base64")] {
+        use ::base64::{Engine as _, engine::general_purpose::STANDARD};
+        STANDARD.decode(scalar.literal.as_bytes())
+            .map_err(|e| DecodeError::conversion(ctx.span(&scalar), e))
+    }
+    #[cfg(not(feature = "base64"))] {
+        Err(DecodeError::unsupported(ctx.span(&scalar),
+            "base64 support is not compiled in"))
+    }
+}
+
+#[cfg(feature = "base64")]
+fn encode_base64(bytes: &[u8]) -> Scalar {
+    use ::base64::{Engine as _, engine::general_purpose::STANDARD};
+    Scalar {
+        type_name: Some("base64".into()),
+        literal: STANDARD.encode(bytes).into(),
+        quoted: true,
+    }
+}
+
+fn decode_hex(scalar: &Scalar, ctx: &mut Context)
+    -> Result<Vec<u8>, DecodeError>
+{
+    let digits = scalar.literal.as_bytes();
+    if digits.len() % 2 != 0 {
+        return Err(DecodeError::conversion(ctx.span(&scalar),
+                   "odd number of hex digits"));
+    }
+    digits.chunks(2).map(|pair| {
+        let pair = core::str::from_utf8(pair).ok()
+            .filter(|pair| pair.chars().all(|c| c.is_ascii_hexdigit()));
+        match pair {
+            Some(pair) => u8::from_str_radix(pair, 16)
+                .map_err(|e| DecodeError::conversion(ctx.span(&scalar), e)),
+            None => Err(DecodeError::conversion(ctx.span(&scalar),
+                        "invalid hex digit")),
+        }
+    }).collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> Scalar {
+    let mut literal = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(literal, "{:02x}", byte).unwrap();
+    }
+    Scalar {
+        type_name: Some("hex".into()),
+        literal: literal.into(),
+        quoted: true,
+    }
+}
+
+/// Decodes and encodes bytes as `(hex)` strings
+pub mod hex {
+    use super::*;
+
+    /// Decodes hex digits, with or without the `(hex)` annotation
+    pub fn decode<T>(scalar: &Scalar, ctx: &mut Context)
+        -> Result<T, DecodeError>
+        where T: TryFrom<Vec<u8>>,
+    {
+        check_scalar_type(scalar, &["hex"], "bytes", ctx)?;
+        check_scalar_kind(scalar, "string", true, ctx)?;
+        let bytes = decode_hex(scalar, ctx)?;
+        convert_bytes(bytes, scalar, ctx)
+    }
+
+    /// Encodes the bytes as a `(hex)` string
+    pub fn encode<T>(value: &T, _: &mut Context) -> Result<Scalar, EncodeError>
+        where T: AsRef<[u8]>,
+    {
+        Ok(encode_hex(value.as_ref()))
+    }
+}
+
+/// Decodes and encodes bytes as `(base64)` strings
+#[cfg(feature = "base64")]
+pub mod base64 {
+    use super::*;
+
+    /// Decodes base64, with or without the `(base64)` annotation
+    pub fn decode<T>(scalar: &Scalar, ctx: &mut Context)
+        -> Result<T, DecodeError>
+        where T: TryFrom<Vec<u8>>,
+    {
+        check_scalar_type(scalar, &["base64"], "bytes", ctx)?;
+        check_scalar_kind(scalar, "string", true, ctx)?;
+        let bytes = decode_base64(scalar, ctx)?;
+        convert_bytes(bytes, scalar, ctx)
+    }
+
+    /// Encodes the bytes as a `(base64)` string
+    pub fn encode<T>(value: &T, _: &mut Context) -> Result<Scalar, EncodeError>
+        where T: AsRef<[u8]>,
+    {
+        Ok(encode_base64(value.as_ref()))
+    }
+}
@@ -0,0 +1,135 @@
+//! Canonical form and content hash of documents
+//!
+//! Documents that mean the same compare equal even if they are written
+//! differently, see [`Node`]. [`canonicalize`] rewrites them into a single
+//! spelling and [`content_hash`] gives a hash that can be stored to detect
+//! whether a configuration actually changed on reload:
+//!
+//! ```rust
+//! use kfl::{canonical, context::Context};
+//!
+//! let old = kfl::parse(&mut Context::default(), r#"
+//!     // listens on http
+//!     server port=0x50 host=localhost
+//! "#)?;
+//! let new = kfl::parse(&mut Context::default(),
+//!                      r#"server host="localhost" port=80"#)?;
+//! assert_eq!(old, new);
+//! assert_eq!(canonical::content_hash(&old), canonical::content_hash(&new));
+//!
+//! let mut nodes = old.clone();
+//! canonical::canonicalize(&mut nodes);
+//! assert_eq!(nodes[0].to_string(), r#"server host="localhost" port=80"#);
+//! # Ok::<(), kfl::Error>(())
+//! ```
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    ast::{Node, Scalar},
+    value::{Literal, Value}
+};
+
+/// Rewrites the nodes in canonical form, see [`Node::canonicalize`]
+///
+/// Comments are never part of the parsed nodes, so printing the result
+/// gives the canonical text of the document.
+pub fn canonicalize(nodes: &mut [Node]) {
+    for node in nodes.iter_mut() {
+        node.canonicalize();
+    }
+}
+
+/// Hash of the document content
+///
+/// Equal documents have the same hash. Unlike [`Hash`](core::hash::Hash)
+/// the value doesn't depend on the platform or the compiler version, so it
+/// can be stored and compared later.
+pub fn content_hash(nodes: &[Node]) -> u64 {
+    let mut hasher = Fnv::new();
+    hasher.nodes(nodes);
+    hasher.0
+}
+
+/// 64-bit FNV-1a over a fixed encoding of the canonical form
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    fn len(&mut self, len: usize) {
+        self.bytes(&(len as u64).to_le_bytes());
+    }
+    fn str(&mut self, value: &str) {
+        self.len(value.len());
+        self.bytes(value.as_bytes());
+    }
+    fn type_name(&mut self, type_name: &Option<Box<str>>) {
+        match type_name {
+            Some(type_name) => {
+                self.bytes(&[1]);
+                self.str(type_name);
+            }
+            None => self.bytes(&[0]),
+        }
+    }
+    fn scalar(&mut self, scalar: &Scalar) {
+        let value = Value::from(scalar);
+        self.type_name(&value.type_name);
+        match value.literal {
+            Literal::String(value) => {
+                self.bytes(&[0]);
+                self.str(&value);
+            }
+            Literal::Integer(value) => {
+                self.bytes(&[1]);
+                self.bytes(&value.to_le_bytes());
+            }
+            Literal::Float(value) => {
+                self.bytes(&[2]);
+                self.bytes(&value.to_bits().to_le_bytes());
+            }
+            Literal::Bool(value) => self.bytes(&[3, u8::from(value)]),
+            Literal::Null => self.bytes(&[4]),
+            Literal::Unsigned(value) => {
+                self.bytes(&[5]);
+                self.bytes(&value.to_le_bytes());
+            }
+        }
+    }
+    fn nodes(&mut self, nodes: &[Node]) {
+        self.len(nodes.len());
+        for node in nodes {
+            self.node(node);
+        }
+    }
+    fn node(&mut self, node: &Node) {
+        self.type_name(&node.type_name);
+        self.str(&node.node_name);
+        self.len(node.arguments.len());
+        for argument in node.arguments.iter() {
+            self.scalar(argument);
+        }
+        let mut properties = node.properties.iter().collect::<Vec<_>>();
+        properties.sort_by(|a, b| a.0.cmp(b.0));
+        self.len(properties.len());
+        for (name, value) in properties {
+            self.str(name);
+            self.scalar(value);
+        }
+        match &node.children {
+            Some(children) => {
+                self.bytes(&[1]);
+                self.nodes(children);
+            }
+            None => self.bytes(&[0]),
+        }
+    }
+}
@@ -18,6 +18,65 @@ use crate::{
     span::Span,
 };
 
+/// Encoder option that makes built-in scalars write their reserved type
+/// annotation, e.g. `(u8)1` or `(date-time)"..."`
+///
+/// Set it with [`Context::set`] in
+/// [`encode_with_context`](crate::encode_with_context).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TypeAnnotations;
+
+/// Encoder option selecting the radix integers are written in
+///
+/// Set it with [`Context::set`] in
+/// [`encode_with_context`](crate::encode_with_context), decimal is used if
+/// it is not set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Radix {
+    /// `0b` prefixed binary
+    Binary,
+    /// `0o` prefixed octal
+    Octal,
+    /// Plain decimal
+    #[default]
+    Decimal,
+    /// `0x` prefixed lowercase hexadecimal
+    Hexadecimal,
+}
+
+/// Decoder option selecting whether built-in scalars check that the value is
+/// quoted
+///
+/// Set it with [`Context::set`] in
+/// [`decode_with_context`](crate::decode_with_context), lenient mode is used
+/// if it is not set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScalarMode {
+    /// Strings accept bare values like `8080`, numbers and booleans accept
+    /// quoted ones like `"8080"`
+    #[default]
+    Lenient,
+    /// Strings must be quoted, numbers and booleans must not be
+    Strict,
+}
+
+/// Decoder option selecting which value is used when a property is repeated,
+/// like `port` in `server port=80 port=8080`
+///
+/// Set it with [`Context::set`] in
+/// [`decode_with_context`](crate::decode_with_context), the last value is
+/// used if it is not set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateProperties {
+    /// The last value is used, as the KDL specification says
+    #[default]
+    LastWins,
+    /// The first value is used
+    FirstWins,
+    /// Repeating a property is an error
+    Error,
+}
+
 /// Context is passed through all the decode operations and can be used for:
 ///
 /// 1. To emit error and proceed (so multiple errors presented to user)
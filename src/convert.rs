@@ -2,9 +2,10 @@
 
 mod containers;
 mod scalars;
+pub(crate) mod time;
 
 use crate::{
-    ast::{Node, Scalar},
+    ast::{Node, Property, Scalar},
     context::Context,
     errors::{DecodeError, EncodeError},
     traits::{Decode, DecodeScalar, Encode, EncodeScalar}
@@ -18,9 +19,13 @@ impl Decode for Node {
             arguments: node.arguments.iter()
                 .map(|v| DecodeScalar::decode(v, ctx))
                 .collect::<Result<_, _>>()?,
-            properties: node.properties.iter()
-                .map(|(k, v)| {
-                    Ok((k.clone(), DecodeScalar::decode(v, ctx)?))
+            properties: node.properties.entries().iter()
+                .map(|p| {
+                    Ok(Property {
+                        name: p.name.clone(),
+                        value: DecodeScalar::decode(&p.value, ctx)?,
+                        span: p.span.clone(),
+                    })
                 })
                 .collect::<Result<_, _>>()?,
             children: node.children.as_ref().map(|sc| {
@@ -43,6 +48,7 @@ impl DecodeScalar for Scalar {
         Ok(Scalar {
             type_name: scalar.type_name.as_ref().map(|n| n.clone()),
             literal: scalar.literal.clone(),
+            quoted: scalar.quoted,
         })
     }
 }
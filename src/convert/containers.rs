@@ -1,20 +1,28 @@
 //! Convert container types.
 
+#[cfg(feature = "std")]
+extern crate std;
+
 use alloc::{
     boxed::Box,
+    collections::{BTreeMap, BTreeSet, VecDeque},
     format,
     rc::Rc,
     sync::Arc,
     vec,
     vec::Vec
 };
-use core::mem;
+use core::{mem, num::Wrapping};
+#[cfg(any(feature = "std", feature = "indexmap"))]
+use core::hash::{BuildHasher, Hash};
 
 use crate::{
     ast::{Node, Scalar},
+    bytes,
     context::Context,
-    errors::{DecodeError, ExpectedType, EncodeError},
-    traits::{Decode, DecodePartial, DecodeScalar},
+    decode,
+    errors::{DecodeError, EncodeError},
+    traits::{Decode, DecodeMap, DecodePartial, DecodeScalar},
     traits::{Encode, EncodePartial, EncodeScalar},
 };
 
@@ -30,13 +38,20 @@ impl<T: DecodePartial> DecodePartial for Box<T> {
     {
         (**self).decode_partial(node, ctx)
     }
-    // fn insert_property(&mut self,
-    //                    name: &Box<str>, scalar: &Scalar,
-    //                    ctx: &mut Context)
-    //     -> Result<bool, DecodeError>
-    // {
-    //     (**self).insert_property(name, value, ctx)
-    // }
+    fn insert_property(&mut self, name: &str, scalar: &Scalar,
+                       ctx: &mut Context)
+        -> Result<bool, DecodeError>
+    {
+        (**self).insert_property(name, scalar, ctx)
+    }
+    fn missing_property(inserted: &dyn Fn(&str) -> bool)
+        -> Option<&'static str>
+    {
+        <T as DecodePartial>::missing_property(inserted)
+    }
+    fn apply_defaults(&mut self, inserted: &dyn Fn(&str) -> bool) {
+        (**self).apply_defaults(inserted)
+    }
 }
 
 impl<T: DecodeScalar> DecodeScalar for Box<T> {
@@ -58,14 +73,22 @@ impl<T: DecodePartial> DecodePartial for Arc<T> {
         Arc::get_mut(self).expect("no Arc clone yet")
             .decode_partial(node, ctx)
     }
-    // fn insert_property(&mut self,
-    //                    name: &Box<str>, scalar: &Scalar,
-    //                    ctx: &mut Context)
-    //     -> Result<bool, DecodeError>
-    // {
-    //     Arc::get_mut(self).expect("no Arc clone yet")
-    //         .insert_property(name, value, ctx)
-    // }
+    fn insert_property(&mut self, name: &str, scalar: &Scalar,
+                       ctx: &mut Context)
+        -> Result<bool, DecodeError>
+    {
+        Arc::get_mut(self).expect("no Arc clone yet")
+            .insert_property(name, scalar, ctx)
+    }
+    fn missing_property(inserted: &dyn Fn(&str) -> bool)
+        -> Option<&'static str>
+    {
+        <T as DecodePartial>::missing_property(inserted)
+    }
+    fn apply_defaults(&mut self, inserted: &dyn Fn(&str) -> bool) {
+        Arc::get_mut(self).expect("no Arc clone yet")
+            .apply_defaults(inserted)
+    }
 }
 
 impl<T: DecodeScalar> DecodeScalar for Arc<T> {
@@ -87,14 +110,22 @@ impl<T: DecodePartial> DecodePartial for Rc<T> {
         Rc::get_mut(self).expect("no Rc clone yet")
             .decode_partial(node, ctx)
     }
-    // fn insert_property(&mut self,
-    //                    name: &Box<str>, scalar: &Scalar,
-    //                    ctx: &mut Context)
-    //     -> Result<bool, DecodeError>
-    // {
-    //     Rc::get_mut(self).expect("no Rc clone yet")
-    //         .insert_property(name, value, ctx)
-    // }
+    fn insert_property(&mut self, name: &str, scalar: &Scalar,
+                       ctx: &mut Context)
+        -> Result<bool, DecodeError>
+    {
+        Rc::get_mut(self).expect("no Rc clone yet")
+            .insert_property(name, scalar, ctx)
+    }
+    fn missing_property(inserted: &dyn Fn(&str) -> bool)
+        -> Option<&'static str>
+    {
+        <T as DecodePartial>::missing_property(inserted)
+    }
+    fn apply_defaults(&mut self, inserted: &dyn Fn(&str) -> bool) {
+        Rc::get_mut(self).expect("no Rc clone yet")
+            .apply_defaults(inserted)
+    }
 }
 
 impl<T: DecodeScalar> DecodeScalar for Rc<T> {
@@ -136,9 +167,10 @@ impl<T: Decode> DecodePartial for Option<T> {
 
 impl<T: DecodeScalar> DecodeScalar for Option<T> {
     fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
-        match scalar.literal.as_ref() {
-            "null" => Ok(None),
-            _ => T::decode(scalar, ctx).map(Some),
+        if scalar.is_null() {
+            Ok(None)
+        } else {
+            T::decode(scalar, ctx).map(Some)
         }
     }
 }
@@ -156,18 +188,10 @@ impl<T: Encode> EncodePartial for Option<T> {
     fn encode_partial(&self, node: &mut Node, ctx: &mut Context)
         -> Result<(), EncodeError>
     {
-        let mut children = match mem::take(&mut node.children) {
-            None => Vec::new(),
-            Some(children) => children
-        };
-        match self {
-            None => panic!(),
-            Some(t) => {
-                let child = <T as Encode>::encode(t, ctx)?;
-                children.push(child);
-            }
-        };
-        let _ = mem::replace(&mut node.children, Some(children));
+        if let Some(t) = self {
+            let child = <T as Encode>::encode(t, ctx)?;
+            node.children.get_or_insert_with(Vec::new).push(child);
+        }
         Ok(())
     }
 }
@@ -175,12 +199,28 @@ impl<T: Encode> EncodePartial for Option<T> {
 impl<T: EncodeScalar> EncodeScalar for Option<T> {
     fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
         match &self {
-            None => Ok(Scalar { type_name: None, literal: "null".into() }),
+            None => Ok(Scalar {
+                type_name: None,
+                literal: "null".into(),
+                quoted: false,
+            }),
             Some(scalar) => <T as EncodeScalar>::encode(&scalar, ctx),
         }
     }
 }
 
+impl<T: DecodeScalar> DecodeScalar for Wrapping<T> {
+    fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
+        <T as DecodeScalar>::decode(scalar, ctx).map(Wrapping)
+    }
+}
+
+impl<T: EncodeScalar> EncodeScalar for Wrapping<T> {
+    fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+        <T as EncodeScalar>::encode(&self.0, ctx)
+    }
+}
+
 impl<T: Decode> Decode for Vec<T> {
     fn decode(node: &Node, ctx: &mut Context) -> Result<Self, DecodeError> {
         <T as Decode>::decode(node, ctx).map(|node| vec![node])
@@ -220,51 +260,255 @@ impl<T: Encode> EncodePartial for Vec<T> {
     fn encode_partial(&self, node: &mut Node, ctx: &mut Context)
         -> Result<(), EncodeError>
     {
-        let mut children = match mem::take(&mut node.children) {
-            None => Vec::new(),
-            Some(children) => children
-        };
+        encode_items(self.iter(), node, ctx)
+    }
+}
+
+impl<T: Decode> Decode for VecDeque<T> {
+    fn decode(node: &Node, ctx: &mut Context) -> Result<Self, DecodeError> {
+        <T as Decode>::decode(node, ctx).map(|node| VecDeque::from([node]))
+    }
+}
+
+impl<T: Decode> DecodePartial for VecDeque<T> {
+    fn decode_partial(&mut self, node: &Node, ctx: &mut Context)
+        -> Result<bool, DecodeError>
+    {
+        let value = <T as Decode>::decode(node, ctx)?;
+        self.push_back(value);
+        Ok(true)
+    }
+}
+
+impl<T: Encode> EncodePartial for VecDeque<T> {
+    fn encode_partial(&self, node: &mut Node, ctx: &mut Context)
+        -> Result<(), EncodeError>
+    {
+        encode_items(self.iter(), node, ctx)
+    }
+}
+
+impl<T: Decode + Ord> Decode for BTreeSet<T> {
+    fn decode(node: &Node, ctx: &mut Context) -> Result<Self, DecodeError> {
+        <T as Decode>::decode(node, ctx).map(|node| BTreeSet::from([node]))
+    }
+}
+
+impl<T: Decode + Ord> DecodePartial for BTreeSet<T> {
+    fn decode_partial(&mut self, node: &Node, ctx: &mut Context)
+        -> Result<bool, DecodeError>
+    {
+        let value = <T as Decode>::decode(node, ctx)?;
+        self.insert(value);
+        Ok(true)
+    }
+}
+
+impl<T: Encode + Ord> EncodePartial for BTreeSet<T> {
+    fn encode_partial(&self, node: &mut Node, ctx: &mut Context)
+        -> Result<(), EncodeError>
+    {
+        encode_items(self.iter(), node, ctx)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S> Decode for std::collections::HashSet<T, S>
+    where T: Decode + Eq + Hash,
+          S: BuildHasher + Default,
+{
+    fn decode(node: &Node, ctx: &mut Context) -> Result<Self, DecodeError> {
+        let mut set = Self::default();
+        set.insert(<T as Decode>::decode(node, ctx)?);
+        Ok(set)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S> DecodePartial for std::collections::HashSet<T, S>
+    where T: Decode + Eq + Hash,
+          S: BuildHasher + Default,
+{
+    fn decode_partial(&mut self, node: &Node, ctx: &mut Context)
+        -> Result<bool, DecodeError>
+    {
+        let value = <T as Decode>::decode(node, ctx)?;
+        self.insert(value);
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S> EncodePartial for std::collections::HashSet<T, S>
+    where T: Encode + Eq + Hash,
+          S: BuildHasher + Default,
+{
+    fn encode_partial(&self, node: &mut Node, ctx: &mut Context)
+        -> Result<(), EncodeError>
+    {
+        encode_items(self.iter(), node, ctx)
+    }
+}
+
+impl<T: Decode> Decode for Box<[T]> {
+    fn decode(node: &Node, ctx: &mut Context) -> Result<Self, DecodeError> {
+        <T as Decode>::decode(node, ctx).map(|node| Box::from([node]))
+    }
+}
+
+// No `DecodePartial` for `Box<[T]>`: a boxed slice can't grow, so the
+// derive collects `#[kfl(children)]` into a `Vec` and boxes it once.
+
+impl<T: Encode> EncodePartial for Box<[T]> {
+    fn encode_partial(&self, node: &mut Node, ctx: &mut Context)
+        -> Result<(), EncodeError>
+    {
+        encode_items(self.iter(), node, ctx)
+    }
+}
+
+impl<T: DecodeScalar, const N: usize> Decode for [T; N] {
+    fn decode(node: &Node, ctx: &mut Context) -> Result<Self, DecodeError> {
+        decode::check_type("-", node, ctx)?;
+        decode::check_arguments_only(node, ctx)?;
+        decode::decode_array(node, &node.arguments, ctx)
+    }
+}
+
+impl<T: EncodeScalar, const N: usize> Encode for [T; N] {
+    fn encode(&self, ctx: &mut Context) -> Result<Node, EncodeError> {
+        let mut node = Node::new("-");
         for item in self.iter() {
-            let child = <T as Encode>::encode(item, ctx)?;
-            children.push(child);
+            node.arguments.push(<T as EncodeScalar>::encode(item, ctx)?);
         }
-        let _ = mem::replace(&mut node.children, Some(children));
-        Ok(())
+        Ok(node)
     }
 }
 
-impl DecodeScalar for Vec<u8> {
-    fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
-        let is_base64 = if let Some(ty) = scalar.type_name.as_ref() {
-            match ty.as_ref() {
-                "base64" => true,
-                _ => {
-                    return Err(DecodeError::TypeName {
-                        span: ctx.span(&ty),
-                        found: Some(ty.clone()),
-                        expected: ExpectedType::optional(ty.clone()),
-                        rust_type: "bytes",
-                    });
-                }
-            }
-        } else { false };
-        if is_base64 {
-            #[cfg(feature = "base64")] {
-                use base64::{Engine as _,
-                             engine::general_purpose::STANDARD};
-                match STANDARD.decode(scalar.literal.as_bytes()) {
-                    Ok(vec) => Ok(vec),
-                    Err(e) => {
-                        Err(DecodeError::conversion(ctx.span(&scalar), e))
-                    }
-                }
+macro_rules! impl_tuple {
+    ($len:literal; $($name:ident $idx:tt),+) => {
+        impl<$($name: DecodeScalar),+> Decode for ($($name,)+) {
+            fn decode(node: &Node, ctx: &mut Context)
+                -> Result<Self, DecodeError>
+            {
+                decode::check_type("-", node, ctx)?;
+                decode::check_arguments_only(node, ctx)?;
+                let arguments = decode::fixed_arguments(
+                    node, &node.arguments, $len, ctx)?;
+                Ok(($(<$name as DecodeScalar>::decode(
+                    &arguments[$idx], ctx)?,)+))
             }
-            #[cfg(not(feature = "base64"))] {
-                Err(DecodeError::unsupported(ctx.span(&value),
-                    "base64 support is not compiled in"))
+        }
+
+        impl<$($name: EncodeScalar),+> Encode for ($($name,)+) {
+            fn encode(&self, ctx: &mut Context) -> Result<Node, EncodeError> {
+                let mut node = Node::new("-");
+                $(
+                    node.arguments.push(
+                        <$name as EncodeScalar>::encode(&self.$idx, ctx)?);
+                )+
+                Ok(node)
             }
-        } else {
-            Ok(scalar.literal.as_bytes().to_vec())
         }
     }
 }
+
+impl_tuple!(1; A 0);
+impl_tuple!(2; A 0, B 1);
+impl_tuple!(3; A 0, B 1, C 2);
+impl_tuple!(4; A 0, B 1, C 2, D 3);
+impl_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+impl_tuple!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_tuple!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Appends encoded items to the children of the node
+fn encode_items<'a, T, I>(items: I, node: &mut Node, ctx: &mut Context)
+    -> Result<(), EncodeError>
+    where T: Encode + 'a,
+          I: Iterator<Item = &'a T>,
+{
+    for item in items {
+        let child = <T as Encode>::encode(item, ctx)?;
+        node.children.get_or_insert_with(Vec::new).push(child);
+    }
+    Ok(())
+}
+
+impl<K: Ord, V> DecodeMap for BTreeMap<K, V> {
+    type Key = K;
+    type Value = V;
+    fn contains_key(&self, key: &K) -> bool {
+        BTreeMap::contains_key(self, key)
+    }
+    fn insert_entry(&mut self, key: K, value: V) {
+        self.insert(key, value);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> DecodeMap for std::collections::HashMap<K, V, S>
+    where K: Eq + Hash,
+          S: BuildHasher + Default,
+{
+    type Key = K;
+    type Value = V;
+    fn contains_key(&self, key: &K) -> bool {
+        std::collections::HashMap::contains_key(self, key)
+    }
+    fn insert_entry(&mut self, key: K, value: V) {
+        self.insert(key, value);
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V, S> DecodeMap for indexmap::IndexMap<K, V, S>
+    where K: Eq + Hash,
+          S: BuildHasher + Default,
+{
+    type Key = K;
+    type Value = V;
+    fn contains_key(&self, key: &K) -> bool {
+        indexmap::IndexMap::contains_key(self, key)
+    }
+    fn insert_entry(&mut self, key: K, value: V) {
+        self.insert(key, value);
+    }
+}
+
+impl DecodeScalar for Vec<u8> {
+    fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
+        bytes::decode_bytes(scalar, ctx)
+    }
+}
+
+impl EncodeScalar for Vec<u8> {
+    fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
+        Ok(bytes::encode_bytes(self))
+    }
+}
+
+impl DecodeScalar for Box<[u8]> {
+    fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
+        bytes::decode_bytes(scalar, ctx).map(Vec::into_boxed_slice)
+    }
+}
+
+impl EncodeScalar for Box<[u8]> {
+    fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
+        Ok(bytes::encode_bytes(self))
+    }
+}
+
+impl<const N: usize> DecodeScalar for [u8; N] {
+    fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
+        let value = bytes::decode_bytes(scalar, ctx)?;
+        bytes::convert_bytes(value, scalar, ctx)
+    }
+}
+
+impl<const N: usize> EncodeScalar for [u8; N] {
+    fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
+        Ok(bytes::encode_bytes(self))
+    }
+}
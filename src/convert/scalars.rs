@@ -5,7 +5,15 @@ use alloc::{
     format,
     string::String
 };
-use core::str::FromStr;
+use core::{
+    fmt,
+    num::{
+        NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128,
+        NonZeroIsize, NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64,
+        NonZeroU128, NonZeroUsize,
+    },
+    str::FromStr,
+};
 
 use chumsky::{
     extra::Full,
@@ -17,7 +25,8 @@ type Extra = Full<ParseError, Context, ()>;
 
 use crate::{
     ast::Scalar,
-    context::Context,
+    context::{Context, Radix},
+    decode::{check_scalar_kind, check_scalar_type, type_annotation},
     errors::{DecodeError, ExpectedType, EncodeError, ParseError},
     traits::{DecodeScalar, EncodeScalar}
 };
@@ -36,7 +45,7 @@ fn decimal_number<'a>() -> impl Parser<'a, I<'a>, (u32, Box<str>), Extra> {
     .then(just('.').then(digit(10)).then(digits(10)).or_not())
     .then(just('e').or(just('E'))
            .then(just('-').or(just('+')).or_not())
-           .then(digits(10)).or_not())
+           .then(digit(10)).then(digits(10)).or_not())
     .map_slice(|v|
         (10, v.chars().filter(|c| c != &'_').collect::<String>().into()))
 }
@@ -49,7 +58,7 @@ fn radix_number<'a>() -> impl Parser<'a, I<'a>, (u32, Box<str>), Extra> {
         just('b').ignore_then(
             digit(2).then(digits(2)).map_slice(|s| (2, s))),
         just('o').ignore_then(
-            digit(8).then(digits(8)).map_slice(|s| (10, s))),
+            digit(8).then(digits(8)).map_slice(|s| (8, s))),
         just('x').ignore_then(
             digit(16).then(digits(16)).map_slice(|s| (16, s))),
     )))
@@ -65,25 +74,83 @@ fn number<'a>() -> impl Parser<'a, I<'a>, (u32, Box<str>), Extra> {
     radix_number().or(decimal_number())
 }
 
+/// Converts a decimal number with fraction or exponent, like `1.5e3`, into
+/// the digits of the integer it denotes
+///
+/// Returns `None` if the number is not integral. At most `max_zeros + 1`
+/// zeros are appended by the exponent, so a longer number is still too
+/// large for the caller while the allocation stays bounded.
+fn integral_digits(value: &str, max_zeros: usize) -> Option<String> {
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (mantissa, exponent) = match rest.find(|c: char| c == 'e' || c == 'E') {
+        Some(idx) => (&rest[..idx], rest[idx + 1..].parse::<i32>().ok()?),
+        None => (rest, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int, frac);
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Some("0".into());
+    }
+    let shift = exponent.checked_sub(i32::try_from(frac.len()).ok()?)?;
+    let mut result = String::from(sign);
+    if shift >= 0 {
+        result.push_str(digits);
+        let zeros = (shift as usize).min(max_zeros.saturating_add(1));
+        result.extend(core::iter::repeat('0').take(zeros));
+    } else {
+        let cut = digits.len().checked_sub(shift.unsigned_abs() as usize)?;
+        let (head, tail) = digits.split_at(cut);
+        if head.is_empty() || tail.chars().any(|c| c != '0') {
+            return None;
+        }
+        result.push_str(head);
+    }
+    Some(result)
+}
+
+/// Writes the integer in the [`Radix`] set in the context
+fn format_integer<T>(negative: bool, magnitude: T, ctx: &Context) -> String
+    where T: fmt::Display + fmt::Binary + fmt::Octal + fmt::LowerHex,
+{
+    let sign = if negative { "-" } else { "" };
+    match ctx.get::<Radix>().copied().unwrap_or_default() {
+        Radix::Binary => format!("{}0b{:b}", sign, magnitude),
+        Radix::Octal => format!("{}0o{:o}", sign, magnitude),
+        Radix::Decimal => format!("{}{}", sign, magnitude),
+        Radix::Hexadecimal => format!("{}0x{:x}", sign, magnitude),
+    }
+}
+
+/// Splits integers into sign and absolute value for [`format_integer`]
+trait Magnitude {
+    type Unsigned: fmt::Display + fmt::Binary + fmt::Octal + fmt::LowerHex;
+    fn magnitude(&self) -> (bool, Self::Unsigned);
+}
+
 macro_rules! impl_integer {
-    ($ty:ident) => {
+    (@impl $ty:ident) => {
         impl DecodeScalar for $ty {
             fn decode(scalar: &Scalar, ctx: &mut Context)
                 -> Result<Self, DecodeError>
             {
-                if let Some(typ) = scalar.type_name.as_ref() {
-                    if typ.as_ref() != stringify!($ty) {
-                        return Err(DecodeError::TypeName {
-                            span: ctx.span(&typ),
-                            found: Some(typ.clone()),
-                            expected: ExpectedType::optional(stringify!($ty)),
-                            rust_type: stringify!($ty),
-                        });
-                    }
-                }
+                check_scalar_type(scalar, &[stringify!($ty)], stringify!($ty),
+                                  ctx)?;
+                check_scalar_kind(scalar, "integer", false, ctx)?;
                 match number().parse_with_state(scalar.literal.as_ref(), ctx)
                     .into_result()
                 {
+                    Ok((10, value)) if value.contains(&['.', 'e', 'E'][..]) => {
+                        // Anything longer overflows every integer type
+                        let digits = integral_digits(&value, 40).ok_or_else(|| {
+                            DecodeError::scalar_kind(ctx.span(&scalar),
+                                "integer", scalar.literal.clone())
+                        })?;
+                        <$ty>::from_str(&digits).map_err(|err| DecodeError::conversion(ctx.span(&scalar), err))
+                    }
                     Ok((radix, value)) => <$ty>::from_str_radix(&value, radix).map_err(|err| DecodeError::conversion(ctx.span(&scalar), err)),
                     Err(_) => Err(DecodeError::scalar_kind(ctx.span(&scalar), "integer", scalar.literal.clone()))  // TODO(rnarkk)
                 }
@@ -91,54 +158,130 @@ macro_rules! impl_integer {
         }
 
         impl EncodeScalar for $ty {
-            fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
-                let literal = format!("{}", self);
-                Ok(Scalar { type_name: None, literal: literal.into() })
+            fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+                let (negative, magnitude) = self.magnitude();
+                let literal = format_integer(negative, magnitude, ctx);
+                Ok(Scalar {
+                    type_name: type_annotation(stringify!($ty), ctx),
+                    literal: literal.into(),
+                    quoted: false,
+                })
             }
         }
-    }
+    };
+    ($ty:ident => $unsigned:ident) => {
+        impl Magnitude for $ty {
+            type Unsigned = $unsigned;
+            fn magnitude(&self) -> (bool, $unsigned) {
+                (*self < 0, self.unsigned_abs())
+            }
+        }
+        impl_integer!(@impl $ty);
+    };
+    ($ty:ident) => {
+        impl Magnitude for $ty {
+            type Unsigned = $ty;
+            fn magnitude(&self) -> (bool, $ty) {
+                (false, *self)
+            }
+        }
+        impl_integer!(@impl $ty);
+    };
 }
 
-impl_integer!(i8);
+impl_integer!(i8 => u8);
 impl_integer!(u8);
-impl_integer!(i16);
+impl_integer!(i16 => u16);
 impl_integer!(u16);
-impl_integer!(i32);
+impl_integer!(i32 => u32);
 impl_integer!(u32);
-impl_integer!(i64);
+impl_integer!(i64 => u64);
 impl_integer!(u64);
-impl_integer!(isize);
+impl_integer!(i128 => u128);
+impl_integer!(u128);
+impl_integer!(isize => usize);
 impl_integer!(usize);
 
+macro_rules! impl_non_zero {
+    ($ty:ident, $inner:ident) => {
+        impl DecodeScalar for $ty {
+            fn decode(scalar: &Scalar, ctx: &mut Context)
+                -> Result<Self, DecodeError>
+            {
+                let value = <$inner as DecodeScalar>::decode(scalar, ctx)?;
+                <$ty>::new(value).ok_or_else(|| {
+                    DecodeError::conversion(ctx.span(&scalar),
+                                            "value must not be zero")
+                })
+            }
+        }
+
+        impl EncodeScalar for $ty {
+            fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+                <$inner as EncodeScalar>::encode(&self.get(), ctx)
+            }
+        }
+    }
+}
+
+impl_non_zero!(NonZeroI8, i8);
+impl_non_zero!(NonZeroU8, u8);
+impl_non_zero!(NonZeroI16, i16);
+impl_non_zero!(NonZeroU16, u16);
+impl_non_zero!(NonZeroI32, i32);
+impl_non_zero!(NonZeroU32, u32);
+impl_non_zero!(NonZeroI64, i64);
+impl_non_zero!(NonZeroU64, u64);
+impl_non_zero!(NonZeroI128, i128);
+impl_non_zero!(NonZeroU128, u128);
+impl_non_zero!(NonZeroIsize, isize);
+impl_non_zero!(NonZeroUsize, usize);
+
 macro_rules! impl_decimal {
     ($ty:ident) => {
         impl DecodeScalar for $ty {
             fn decode(scalar: &Scalar, ctx: &mut Context)
                 -> Result<Self, DecodeError>
             {
-                if let Some(typ) = scalar.type_name.as_ref() {
-                    if typ.as_ref() != stringify!($ty) {
-                        return Err(DecodeError::TypeName {
-                            span: ctx.span(&typ),
-                            found: Some(typ.clone()),
-                            expected: ExpectedType::optional(stringify!($ty)),
-                            rust_type: stringify!($ty),
-                        });
-                    }
-                }
+                check_scalar_type(scalar, &[stringify!($ty), "decimal"],
+                                  stringify!($ty), ctx)?;
+                check_scalar_kind(scalar, "decimal", false, ctx)?;
                 match number().parse_with_state(scalar.literal.as_ref(), ctx).into_result() {
                     Ok((10, value)) => <$ty>::from_str(value.as_ref()).map_err(|err| DecodeError::conversion(ctx.span(&scalar), err)),
-                    Ok(_) => Err(DecodeError::unexpected(ctx.span(&scalar), "radix", "radix other than 10 (decimal) is not implemented")),
+                    Ok((radix, value)) => {
+                        let value = i128::from_str_radix(&value, radix)
+                            .map_err(|err| DecodeError::conversion(
+                                     ctx.span(&scalar), err))?;
+                        let converted = value as $ty;
+                        // large integers are rounded to the nearest float,
+                        // and `as i128` saturates at 2^127 so it has to be
+                        // excluded before converting back
+                        let limit = -(i128::MIN as $ty);
+                        if converted >= limit || converted as i128 != value {
+                            return Err(DecodeError::conversion(
+                                ctx.span(&scalar),
+                                format!("value `{}` can't be represented \
+                                         exactly as {}",
+                                        scalar.literal, stringify!($ty))));
+                        }
+                        Ok(converted)
+                    }
                     Err(_) => Err(DecodeError::scalar_kind(ctx.span(&scalar), "decimal", scalar.literal.clone()))
                 }
-                // <$ty>::from_str(scalar.literal.as_ref())
             }
         }
 
         impl EncodeScalar for $ty {
-            fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
+            fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+                if !self.is_finite() {
+                    return Err(EncodeError::non_finite(f64::from(*self)));
+                }
                 let literal = format!("{}", self);
-                Ok(Scalar { type_name: None, literal: literal.into() })
+                Ok(Scalar {
+                    type_name: type_annotation(stringify!($ty), ctx),
+                    literal: literal.into(),
+                    quoted: false,
+                })
             }
         }
     }
@@ -157,30 +300,30 @@ impl DecodeScalar for String {
                 rust_type: "String",
             });
         }
+        check_scalar_kind(scalar, "string", true, ctx)?;
         Ok(scalar.literal.clone().into())
     }
 }
 impl EncodeScalar for String {
     fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
-        let literal = format!("{:?}", self);
-        Ok(Scalar { type_name: None, literal: literal.into() })
+        Ok(Scalar::string(self))
     }
 }
 
+/// Implements `DecodeScalar` using `FromStr`, accepting the listed reserved
+/// type annotations
 macro_rules! impl_from_str {
     ($ty:ty) => {
+        impl_from_str!($ty, []);
+    };
+    ($ty:ty, [$($name:literal),*]) => {
         impl DecodeScalar for $ty {
             fn decode(scalar: &crate::ast::Scalar, ctx: &mut Context)
                 -> Result<Self, DecodeError>
             {
-                if let Some(typ) = scalar.type_name.as_ref() {
-                    return Err(DecodeError::TypeName {
-                        span: ctx.span(&typ),
-                        found: Some(typ.clone()),
-                        expected: ExpectedType::no_type(),
-                        rust_type: stringify!($ty),
-                    });
-                }
+                check_scalar_type(scalar, &[$($name),*], stringify!($ty),
+                                  ctx)?;
+                check_scalar_kind(scalar, "string", true, ctx)?;
                 <$ty>::from_str(scalar.literal.as_ref())
                         .map_err(|err| DecodeError::conversion(
                                  ctx.span(&scalar), err))
@@ -189,20 +332,120 @@ macro_rules! impl_from_str {
     }
 }
 
+impl DecodeScalar for char {
+    fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
+        check_scalar_type(scalar, &[], "char", ctx)?;
+        check_scalar_kind(scalar, "string", true, ctx)?;
+        let mut chars = scalar.literal.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(DecodeError::conversion(ctx.span(&scalar),
+                     format!("expected a single character, found `{}`",
+                             scalar.literal.escape_default()))),
+        }
+    }
+}
+impl EncodeScalar for char {
+    fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
+        let mut string = String::new();
+        string.push(*self);
+        Ok(Scalar::from(string))
+    }
+}
+
 #[cfg(feature = "std")]
 mod _std {
     extern crate std;
+    use std::ffi::OsString;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
     use std::path::PathBuf;
-    use std::net::SocketAddr;
     use super::*;
 
+    impl_from_str!(Ipv4Addr, ["ipv4"]);
+    impl EncodeScalar for Ipv4Addr {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            Ok(Scalar {
+                type_name: type_annotation("ipv4", ctx),
+                literal: format!("{}", self).into_boxed_str(),
+                quoted: true,
+            })
+        }
+    }
+
+    impl_from_str!(Ipv6Addr, ["ipv6"]);
+    impl EncodeScalar for Ipv6Addr {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            Ok(Scalar {
+                type_name: type_annotation("ipv6", ctx),
+                literal: format!("{}", self).into_boxed_str(),
+                quoted: true,
+            })
+        }
+    }
+
+    impl DecodeScalar for IpAddr {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            // The annotation restricts the address to a single family
+            match scalar.type_name.as_deref() {
+                Some("ipv4") => <Ipv4Addr as DecodeScalar>::decode(scalar, ctx)
+                    .map(IpAddr::V4),
+                Some("ipv6") => <Ipv6Addr as DecodeScalar>::decode(scalar, ctx)
+                    .map(IpAddr::V6),
+                _ => {
+                    check_scalar_type(scalar, &["ipv4", "ipv6"], "IpAddr",
+                                      ctx)?;
+                    check_scalar_kind(scalar, "string", true, ctx)?;
+                    IpAddr::from_str(scalar.literal.as_ref())
+                        .map_err(|err| DecodeError::conversion(
+                                 ctx.span(&scalar), err))
+                }
+            }
+        }
+    }
+    impl EncodeScalar for IpAddr {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            match self {
+                IpAddr::V4(addr) => EncodeScalar::encode(addr, ctx),
+                IpAddr::V6(addr) => EncodeScalar::encode(addr, ctx),
+            }
+        }
+    }
+
+    impl DecodeScalar for OsString {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            check_scalar_type(scalar, &[], "OsString", ctx)?;
+            check_scalar_kind(scalar, "string", true, ctx)?;
+            Ok(OsString::from(scalar.literal.as_ref()))
+        }
+    }
+    impl EncodeScalar for OsString {
+        fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
+            let string = self.to_str().ok_or_else(|| {
+                EncodeError::Unexpected {
+                    kind: "string",
+                    message: format!("{:?} is not valid unicode", self),
+                }
+            })?;
+            Ok(Scalar {
+                type_name: None,
+                literal: string.into(),
+                quoted: true,
+            })
+        }
+    }
+
     impl_from_str!(PathBuf);
     impl EncodeScalar for PathBuf {
         fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
             let string = format!("{}", self.display());
             Ok(Scalar {
                 type_name: None,
-                literal: string.into_boxed_str()
+                literal: string.into_boxed_str(),
+                quoted: true,
             })
         }
     }
@@ -213,23 +456,128 @@ mod _std {
             let string = format!("{}", self);
             Ok(Scalar {
                 type_name: None,
-                literal: string.into_boxed_str()
+                literal: string.into_boxed_str(),
+                quoted: true,
             })
         }
     }
 }
 
-#[cfg(feature = "chrono")]
-mod _chrono {
-    use chrono::NaiveDateTime;
+#[cfg(feature = "bigdecimal")]
+mod _bigdecimal {
+    use bigdecimal::{BigDecimal, num_bigint::BigInt};
     use super::*;
-    impl_from_str!(NaiveDateTime);
-    impl EncodeScalar for NaiveDateTime {
-        fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
-            let string = format!("{}", self);
+
+    impl DecodeScalar for BigDecimal {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            check_scalar_type(scalar, &["decimal"], "BigDecimal", ctx)?;
+            check_scalar_kind(scalar, "decimal", false, ctx)?;
+            match number().parse_with_state(scalar.literal.as_ref(), ctx)
+                .into_result()
+            {
+                Ok((10, value)) => BigDecimal::from_str(&value)
+                    .map_err(|err| DecodeError::conversion(
+                             ctx.span(&scalar), err)),
+                Ok((radix, value)) => {
+                    BigInt::parse_bytes(value.as_bytes(), radix)
+                        .map(BigDecimal::from)
+                        .ok_or_else(|| DecodeError::scalar_kind(
+                            ctx.span(&scalar), "decimal",
+                            scalar.literal.clone()))
+                }
+                Err(_) => Err(DecodeError::scalar_kind(ctx.span(&scalar),
+                              "decimal", scalar.literal.clone()))
+            }
+        }
+    }
+
+    impl EncodeScalar for BigDecimal {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            Ok(Scalar {
+                type_name: type_annotation("decimal", ctx),
+                literal: format!("{}", self).into_boxed_str(),
+                quoted: false,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+mod _num_bigint {
+    use num_bigint::{BigInt, BigUint, Sign};
+    use super::*;
+
+    /// Limits the number of digits an exponent can expand into
+    const MAX_DIGITS: usize = 10_000;
+
+    fn decode_big_int(scalar: &Scalar, rust_type: &'static str,
+                      ctx: &mut Context)
+        -> Result<BigInt, DecodeError>
+    {
+        check_scalar_type(scalar, &[], rust_type, ctx)?;
+        check_scalar_kind(scalar, "integer", false, ctx)?;
+        let value = match number().parse_with_state(scalar.literal.as_ref(),
+                                                    ctx).into_result()
+        {
+            Ok((10, value)) if value.contains(&['.', 'e', 'E'][..]) => {
+                let digits = integral_digits(&value, MAX_DIGITS);
+                if let Some(digits) = &digits {
+                    if digits.trim_start_matches('-').len() > MAX_DIGITS {
+                        return Err(DecodeError::conversion(ctx.span(&scalar),
+                            format!("number is longer than {} digits",
+                                    MAX_DIGITS)));
+                    }
+                }
+                digits.and_then(|digits| {
+                    BigInt::parse_bytes(digits.as_bytes(), 10)
+                })
+            }
+            Ok((radix, value)) => BigInt::parse_bytes(value.as_bytes(), radix),
+            Err(_) => None,
+        };
+        value.ok_or_else(|| DecodeError::scalar_kind(ctx.span(&scalar),
+                         "integer", scalar.literal.clone()))
+    }
+
+    impl DecodeScalar for BigInt {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            decode_big_int(scalar, "BigInt", ctx)
+        }
+    }
+
+    impl EncodeScalar for BigInt {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            let negative = self.sign() == Sign::Minus;
+            let literal = format_integer(negative, self.magnitude(), ctx);
             Ok(Scalar {
                 type_name: None,
-                literal: string.into_boxed_str()
+                literal: literal.into_boxed_str(),
+                quoted: false,
+            })
+        }
+    }
+
+    impl DecodeScalar for BigUint {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            decode_big_int(scalar, "BigUint", ctx)?.to_biguint()
+                .ok_or_else(|| DecodeError::conversion(ctx.span(&scalar),
+                            "value must not be negative"))
+        }
+    }
+
+    impl EncodeScalar for BigUint {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            let literal = format_integer(false, self, ctx);
+            Ok(Scalar {
+                type_name: None,
+                literal: literal.into_boxed_str(),
+                quoted: false,
             })
         }
     }
@@ -239,18 +587,115 @@ mod _chrono {
 mod _http {
     use http::Uri;
     use super::*;
-    impl_from_str!(Uri);
+    impl_from_str!(Uri, ["url", "url-reference"]);
     impl EncodeScalar for Uri {
-        fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
             let string = format!("{}", self);
             Ok(Scalar {
-                type_name: None,
-                literal: string.into_boxed_str()
+                type_name: type_annotation("url", ctx),
+                literal: string.into_boxed_str(),
+                quoted: true,
             })
         }
     }
 }
 
+/// Implements `EncodeScalar` as a quoted string of the `Display` output,
+/// annotated with the reserved type `$name` if there is one
+#[cfg(any(feature = "url", feature = "uuid", feature = "semver",
+          feature = "ipnet"))]
+macro_rules! impl_to_string {
+    ($ty:ty) => {
+        impl EncodeScalar for $ty {
+            fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
+                Ok(Scalar {
+                    type_name: None,
+                    literal: self.to_string().into(),
+                    quoted: true,
+                })
+            }
+        }
+    };
+    ($ty:ty, $name:literal) => {
+        impl EncodeScalar for $ty {
+            fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+                Ok(Scalar {
+                    type_name: type_annotation($name, ctx),
+                    literal: self.to_string().into(),
+                    quoted: true,
+                })
+            }
+        }
+    };
+}
+
+#[cfg(feature = "url")]
+mod _url {
+    use alloc::string::ToString;
+    use url::Url;
+    use super::*;
+    impl_from_str!(Url, ["url"]);
+    impl_to_string!(Url, "url");
+}
+
+#[cfg(feature = "uuid")]
+mod _uuid {
+    use alloc::string::ToString;
+    use uuid::Uuid;
+    use super::*;
+    impl_from_str!(Uuid, ["uuid"]);
+    impl_to_string!(Uuid, "uuid");
+}
+
+#[cfg(feature = "regex")]
+mod _regex {
+    use regex::Regex;
+    use super::*;
+    impl DecodeScalar for Regex {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            check_scalar_type(scalar, &["regex"], "Regex", ctx)?;
+            check_scalar_kind(scalar, "string", true, ctx)?;
+            Regex::new(scalar.literal.as_ref())
+                .map_err(|err| DecodeError::conversion(ctx.span(&scalar), err))
+        }
+    }
+    impl EncodeScalar for Regex {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            Ok(Scalar {
+                type_name: type_annotation("regex", ctx),
+                literal: self.as_str().into(),
+                quoted: true,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "semver")]
+mod _semver {
+    use alloc::string::ToString;
+    use semver::{Version, VersionReq};
+    use super::*;
+    impl_from_str!(Version);
+    impl_to_string!(Version);
+    impl_from_str!(VersionReq);
+    impl_to_string!(VersionReq);
+}
+
+#[cfg(feature = "ipnet")]
+mod _ipnet {
+    use alloc::string::ToString;
+    use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+    use super::*;
+    impl_from_str!(IpNet);
+    impl_to_string!(IpNet);
+    impl_from_str!(Ipv4Net);
+    impl_to_string!(Ipv4Net);
+    impl_from_str!(Ipv6Net);
+    impl_to_string!(Ipv6Net);
+}
+
 impl DecodeScalar for bool {
     fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
         if let Some(typ) = scalar.type_name.as_ref() {
@@ -261,6 +706,7 @@ impl DecodeScalar for bool {
                 rust_type: "bool",
             });
         }
+        check_scalar_kind(scalar, "boolean", false, ctx)?;
         match scalar.literal.as_ref() {
             "true" => Ok(true),
             "false" => Ok(false),
@@ -275,6 +721,6 @@ impl EncodeScalar for bool {
             true => "true",
             false => "false"
         };
-        Ok(Scalar { type_name: None, literal: literal.into() })
+        Ok(Scalar { type_name: None, literal: literal.into(), quoted: false })
     }
 }
@@ -0,0 +1,419 @@
+//! Convert time types.
+
+use alloc::{
+    format,
+    string::String
+};
+use core::{fmt::Write, time::Duration};
+
+use crate::{
+    ast::Scalar,
+    context::Context,
+    decode::{check_scalar_kind, check_scalar_type, type_annotation},
+    errors::{DecodeError, EncodeError},
+    traits::{DecodeScalar, EncodeScalar}
+};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Units of human-readable durations, largest first
+const UNITS: &[(&str, u128)] = &[
+    ("d", 86_400 * NANOS_PER_SEC),
+    ("h", 3_600 * NANOS_PER_SEC),
+    ("m", 60 * NANOS_PER_SEC),
+    ("s", NANOS_PER_SEC),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+/// Type annotations accepted by durations: `(duration)` for strings and
+/// units for numbers
+const DURATION_TYPES: &[&str] = &["duration", "d", "h", "m", "s", "ms", "us",
+                                  "ns"];
+
+fn unit_nanos(unit: &str) -> Option<u128> {
+    let unit = if unit == "µs" { "us" } else { unit };
+    UNITS.iter().find(|(name, _)| *name == unit).map(|&(_, nanos)| nanos)
+}
+
+/// Converts an amount like `90` or `1.5` of `unit` nanoseconds into
+/// nanoseconds, digits beyond nanosecond precision are dropped
+fn parse_amount(amount: &str, unit: u128) -> Option<u128> {
+    let (int, frac) = amount.split_once('.').unwrap_or((amount, ""));
+    if int.is_empty() || !int.bytes().all(|b| b.is_ascii_digit())
+        || !frac.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let mut nanos = int.parse::<u128>().ok()?.checked_mul(unit)?;
+    let mut scale = unit;
+    for digit in frac.bytes() {
+        scale /= 10;
+        nanos = nanos.checked_add(u128::from(digit - b'0') * scale)?;
+    }
+    Some(nanos)
+}
+
+fn nanos_to_duration(nanos: u128) -> Option<Duration> {
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).ok()?;
+    Some(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+}
+
+/// Parses human-readable durations like `1h30m`, `1.5s` or `2m 10s`
+pub(crate) fn parse_duration(text: &str) -> Option<Duration> {
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut nanos: u128 = 0;
+    while !rest.is_empty() {
+        let split = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (amount, tail) = rest.split_at(split);
+        let split = tail.find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(split);
+        nanos = nanos.checked_add(parse_amount(amount, unit_nanos(unit)?)?)?;
+        rest = tail.trim_start();
+    }
+    nanos_to_duration(nanos)
+}
+
+/// Writes the duration in the form read by [`parse_duration`], e.g. `1h30m`
+pub(crate) fn format_duration(duration: &Duration) -> String {
+    let mut nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0s".into();
+    }
+    let mut output = String::new();
+    for &(unit, size) in UNITS {
+        if nanos >= size {
+            write!(output, "{}{}", nanos / size, unit).unwrap();
+            nanos %= size;
+        }
+    }
+    output
+}
+
+/// Decodes `(duration)"1h30m"`, `"1h30m"` or a number with a unit
+/// annotation like `(s)90`
+pub(crate) fn decode_duration(scalar: &Scalar, rust_type: &'static str,
+                              ctx: &mut Context)
+    -> Result<Duration, DecodeError>
+{
+    check_scalar_type(scalar, DURATION_TYPES, rust_type, ctx)?;
+    let value = match scalar.type_name.as_deref() {
+        Some("duration") | None => {
+            check_scalar_kind(scalar, "string", true, ctx)?;
+            parse_duration(&scalar.literal)
+        }
+        Some(unit) => {
+            check_scalar_kind(scalar, "number", false, ctx)?;
+            unit_nanos(unit)
+                .and_then(|unit| parse_amount(&scalar.literal, unit))
+                .and_then(nanos_to_duration)
+        }
+    };
+    value.ok_or_else(|| {
+        DecodeError::conversion(ctx.span(&scalar),
+            format!("invalid duration `{}`, expected e.g. `1h30m` or `(s)90`",
+                    scalar.literal.escape_default()))
+    })
+}
+
+impl DecodeScalar for Duration {
+    fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
+        decode_duration(scalar, "Duration", ctx)
+    }
+}
+
+impl EncodeScalar for Duration {
+    fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+        Ok(Scalar {
+            type_name: type_annotation("duration", ctx),
+            literal: format_duration(self).into(),
+            quoted: true,
+        })
+    }
+}
+
+/// Implements scalar traits for types whose `FromStr` and `Display` agree,
+/// written as strings and annotated with the reserved type `$name`
+#[cfg(any(feature = "chrono", feature = "jiff"))]
+macro_rules! impl_text {
+    ($ty:ty, $name:literal) => {
+        impl DecodeScalar for $ty {
+            fn decode(scalar: &Scalar, ctx: &mut Context)
+                -> Result<Self, DecodeError>
+            {
+                check_scalar_type(scalar, &[$name], stringify!($ty), ctx)?;
+                check_scalar_kind(scalar, "string", true, ctx)?;
+                <$ty>::from_str(scalar.literal.as_ref())
+                    .map_err(|err| DecodeError::conversion(
+                             ctx.span(&scalar), err))
+            }
+        }
+
+        impl EncodeScalar for $ty {
+            fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+                Ok(Scalar {
+                    type_name: type_annotation($name, ctx),
+                    literal: self.to_string().into_boxed_str(),
+                    quoted: true,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod _chrono {
+    use alloc::string::ToString;
+    use core::str::FromStr;
+    use chrono::{
+        DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc,
+    };
+    use super::*;
+
+    impl_text!(NaiveDate, "date");
+    impl_text!(NaiveTime, "time");
+
+    impl DecodeScalar for NaiveDateTime {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            check_scalar_type(scalar, &["date-time"], "NaiveDateTime", ctx)?;
+            check_scalar_kind(scalar, "string", true, ctx)?;
+            NaiveDateTime::from_str(scalar.literal.as_ref())
+                .map_err(|err| DecodeError::conversion(ctx.span(&scalar), err))
+        }
+    }
+    impl EncodeScalar for NaiveDateTime {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            // `Display` separates date and time with a space
+            let string = self.format("%Y-%m-%dT%H:%M:%S%.f").to_string();
+            Ok(Scalar {
+                type_name: type_annotation("date-time", ctx),
+                literal: string.into_boxed_str(),
+                quoted: true,
+            })
+        }
+    }
+
+    impl DecodeScalar for DateTime<FixedOffset> {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            check_scalar_type(scalar, &["date-time"], "DateTime", ctx)?;
+            check_scalar_kind(scalar, "string", true, ctx)?;
+            DateTime::parse_from_rfc3339(scalar.literal.as_ref())
+                .map_err(|err| DecodeError::conversion(ctx.span(&scalar), err))
+        }
+    }
+    impl EncodeScalar for DateTime<FixedOffset> {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            Ok(Scalar {
+                type_name: type_annotation("date-time", ctx),
+                literal: self.to_rfc3339().into_boxed_str(),
+                quoted: true,
+            })
+        }
+    }
+
+    impl DecodeScalar for DateTime<Utc> {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            <DateTime<FixedOffset> as DecodeScalar>::decode(scalar, ctx)
+                .map(|value| value.with_timezone(&Utc))
+        }
+    }
+    impl EncodeScalar for DateTime<Utc> {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            Ok(Scalar {
+                type_name: type_annotation("date-time", ctx),
+                literal: self.to_rfc3339().into_boxed_str(),
+                quoted: true,
+            })
+        }
+    }
+
+    impl DecodeScalar for chrono::Duration {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            let value = decode_duration(scalar, "chrono::Duration", ctx)?;
+            chrono::Duration::from_std(value)
+                .map_err(|err| DecodeError::conversion(ctx.span(&scalar), err))
+        }
+    }
+    impl EncodeScalar for chrono::Duration {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            let value = self.to_std().map_err(|err| EncodeError::Unexpected {
+                kind: "duration",
+                message: format!("cannot encode `{}`: {}", self, err),
+            })?;
+            EncodeScalar::encode(&value, ctx)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod _time {
+    use alloc::string::ToString;
+    use ::time::{
+        Date, OffsetDateTime, PrimitiveDateTime, Time,
+        format_description::{FormatItem, well_known::Rfc3339},
+        macros::format_description,
+    };
+    use super::*;
+
+    type Format = &'static [FormatItem<'static>];
+
+    const DATE: Format = format_description!("[year]-[month]-[day]");
+    const TIME: Format = format_description!(
+        "[hour]:[minute]:[second][optional [.[subsecond]]]");
+    const WHOLE_TIME: Format = format_description!("[hour]:[minute]:[second]");
+    const FRACTIONAL_TIME: Format = format_description!(
+        "[hour]:[minute]:[second].[subsecond]");
+    const DATE_TIME: Format = format_description!(
+        "[year]-[month]-[day]T[hour]:[minute]:[second][optional [.[subsecond]]]");
+    const WHOLE_DATE_TIME: Format = format_description!(
+        "[year]-[month]-[day]T[hour]:[minute]:[second]");
+    const FRACTIONAL_DATE_TIME: Format = format_description!(
+        "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond]");
+
+    fn decode_with<T, F>(scalar: &Scalar, ctx: &mut Context,
+                         name: &'static str, rust_type: &'static str,
+                         parse: F)
+        -> Result<T, DecodeError>
+        where F: FnOnce(&str) -> Result<T, ::time::error::Parse>,
+    {
+        check_scalar_type(scalar, &[name], rust_type, ctx)?;
+        check_scalar_kind(scalar, "string", true, ctx)?;
+        parse(scalar.literal.as_ref())
+            .map_err(|err| DecodeError::conversion(ctx.span(&scalar), err))
+    }
+
+    fn encode_with(name: &'static str, rust_type: &'static str,
+                      result: Result<String, ::time::error::Format>,
+                      ctx: &mut Context)
+        -> Result<Scalar, EncodeError>
+    {
+        let string = result.map_err(|err| EncodeError::Unexpected {
+            kind: rust_type,
+            message: err.to_string(),
+        })?;
+        Ok(Scalar {
+            type_name: type_annotation(name, ctx),
+            literal: string.into_boxed_str(),
+            quoted: true,
+        })
+    }
+
+    impl DecodeScalar for Date {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            decode_with(scalar, ctx, "date", "Date",
+                        |text| Date::parse(text, DATE))
+        }
+    }
+    impl EncodeScalar for Date {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            encode_with("date", "Date", self.format(DATE), ctx)
+        }
+    }
+
+    impl DecodeScalar for Time {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            decode_with(scalar, ctx, "time", "Time",
+                        |text| Time::parse(text, TIME))
+        }
+    }
+    impl EncodeScalar for Time {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            let format = if self.nanosecond() == 0 {
+                WHOLE_TIME
+            } else {
+                FRACTIONAL_TIME
+            };
+            encode_with("time", "Time", self.format(format), ctx)
+        }
+    }
+
+    impl DecodeScalar for PrimitiveDateTime {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            decode_with(scalar, ctx, "date-time", "PrimitiveDateTime",
+                        |text| PrimitiveDateTime::parse(text, DATE_TIME))
+        }
+    }
+    impl EncodeScalar for PrimitiveDateTime {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            let format = if self.nanosecond() == 0 {
+                WHOLE_DATE_TIME
+            } else {
+                FRACTIONAL_DATE_TIME
+            };
+            encode_with("date-time", "PrimitiveDateTime", self.format(format),
+                        ctx)
+        }
+    }
+
+    impl DecodeScalar for OffsetDateTime {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            decode_with(scalar, ctx, "date-time", "OffsetDateTime",
+                        |text| OffsetDateTime::parse(text, &Rfc3339))
+        }
+    }
+    impl EncodeScalar for OffsetDateTime {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            encode_with("date-time", "OffsetDateTime", self.format(&Rfc3339),
+                        ctx)
+        }
+    }
+
+    impl DecodeScalar for ::time::Duration {
+        fn decode(scalar: &Scalar, ctx: &mut Context)
+            -> Result<Self, DecodeError>
+        {
+            let value = decode_duration(scalar, "time::Duration", ctx)?;
+            ::time::Duration::try_from(value)
+                .map_err(|err| DecodeError::conversion(ctx.span(&scalar), err))
+        }
+    }
+    impl EncodeScalar for ::time::Duration {
+        fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+            let value = Duration::try_from(*self)
+                .map_err(|err| EncodeError::Unexpected {
+                    kind: "duration",
+                    message: format!("cannot encode `{}`: {}", self, err),
+                })?;
+            EncodeScalar::encode(&value, ctx)
+        }
+    }
+}
+
+#[cfg(feature = "jiff")]
+mod _jiff {
+    use alloc::string::ToString;
+    use core::str::FromStr;
+    use jiff::{
+        SignedDuration, Timestamp,
+        civil::{Date, DateTime, Time},
+    };
+    use super::*;
+
+    impl_text!(Timestamp, "date-time");
+    impl_text!(DateTime, "date-time");
+    impl_text!(Date, "date");
+    impl_text!(Time, "time");
+    impl_text!(SignedDuration, "duration");
+}
@@ -1,10 +1,22 @@
 //! Used by derive macro.
 
-use alloc::format;
+extern crate std;
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    vec::Vec
+};
+use core::{cmp::Ordering, fmt, ptr, str::FromStr};
+
 use crate::{
-    ast::Node,
-    context::Context,
-    errors::DecodeError
+    ast::{Node, Scalar},
+    context::{Context, DuplicateProperties, ScalarMode, TypeAnnotations},
+    errors::{DecodeError, EncodeError, ExpectedType},
+    span::Span,
+    traits::{Decode, DecodeMap, DecodeScalar, Encode, EncodeScalar}
 };
 
 ///
@@ -23,3 +35,481 @@ pub fn check_type(ident: &str, node: &Node, ctx: &Context)
     }
     Ok(())
 }
+
+/// Checks the type annotation of the scalar against the reserved KDL type
+/// names accepted by `rust_type`
+///
+/// No annotation is always accepted.
+pub fn check_scalar_type(scalar: &Scalar, accepted: &[&str],
+                         rust_type: &'static str, ctx: &Context)
+    -> Result<(), DecodeError>
+{
+    match scalar.type_name.as_ref() {
+        Some(typ) if !accepted.contains(&typ.as_ref()) => {
+            Err(DecodeError::TypeName {
+                span: ctx.span(&typ),
+                found: Some(typ.clone()),
+                expected: if accepted.is_empty() {
+                    ExpectedType::no_type()
+                } else {
+                    ExpectedType::optional_one_of(accepted)
+                },
+                rust_type,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the scalar is quoted if `quoted` is true and bare otherwise
+/// when [`ScalarMode::Strict`] is set in the context
+///
+/// `expected` names the kind of scalar in the error, e.g. `"integer"`.
+pub fn check_scalar_kind(scalar: &Scalar, expected: &'static str,
+                         quoted: bool, ctx: &Context)
+    -> Result<(), DecodeError>
+{
+    if scalar.quoted == quoted
+        || ctx.get::<ScalarMode>() != Some(&ScalarMode::Strict)
+    {
+        return Ok(());
+    }
+    let found = if scalar.quoted {
+        format!("{:?}", scalar.literal)
+    } else {
+        scalar.literal.to_string()
+    };
+    Err(DecodeError::scalar_kind(ctx.span(&scalar), expected, found.into()))
+}
+
+/// Returns the type annotation to write for the scalar of reserved KDL type
+/// `name` if [`TypeAnnotations`] is set in the context
+pub fn type_annotation(name: &str, ctx: &Context) -> Option<Box<str>> {
+    ctx.get::<TypeAnnotations>().map(|_| name.into())
+}
+
+/// Pushes a single item into the collection decoded by `properties`,
+/// `children` or `other` fields
+pub fn push_item<C>(collection: &mut C, item: C::Item)
+    where C: IntoIterator + Extend<<C as IntoIterator>::Item>,
+{
+    collection.extend(Some(item));
+}
+
+/// Returns properties of the node in the order they are written, resolving
+/// repeated names according to [`DuplicateProperties`]
+pub fn properties<'a>(node: &'a Node, ctx: &Context)
+    -> Result<Vec<(&'a Box<str>, &'a Scalar)>, DecodeError>
+{
+    let policy = ctx.get::<DuplicateProperties>().copied().unwrap_or_default();
+    match policy {
+        DuplicateProperties::LastWins => Ok(node.properties.iter().collect()),
+        DuplicateProperties::FirstWins => {
+            let mut seen = BTreeSet::new();
+            Ok(node.properties.entries().iter()
+                .filter(|property| seen.insert(&*property.name))
+                .map(|property| (&property.name, &property.value))
+                .collect())
+        }
+        DuplicateProperties::Error => {
+            if let Some(duplicate) = node.properties.duplicates().next() {
+                return Err(DecodeError::unexpected(
+                    duplicate.span.clone(), "property",
+                    format!("duplicate property `{}`",
+                            duplicate.name.escape_default())));
+            }
+            Ok(node.properties.iter().collect())
+        }
+    }
+}
+
+/// Checks that the node has nothing but arguments
+///
+/// Used by types decoded from positional arguments, such as tuples.
+pub fn check_arguments_only(node: &Node, ctx: &Context)
+    -> Result<(), DecodeError>
+{
+    if let Some((name, _)) = node.properties.iter().next() {
+        return Err(DecodeError::unexpected(
+            ctx.span(&name), "property",
+            format!("unexpected property `{}`", name.escape_default())));
+    }
+    if let Some(child) = node.children.as_ref().and_then(|c| c.first()) {
+        return Err(DecodeError::unexpected(
+            ctx.span(&child), "node",
+            format!("unexpected node `{}`", child.node_name.as_ref())));
+    }
+    Ok(())
+}
+
+/// Checks that exactly `expected` arguments are left for a tuple or an array
+pub fn fixed_arguments<'a>(node: &Node, arguments: &'a [Scalar],
+                           expected: usize, ctx: &Context)
+    -> Result<&'a [Scalar], DecodeError>
+{
+    let message = || format!("expected {}, found {}",
+                             count(expected, "argument"), arguments.len());
+    match arguments.len().cmp(&expected) {
+        Ordering::Equal => Ok(arguments),
+        Ordering::Less => Err(DecodeError::missing(ctx.span(&node), message())),
+        Ordering::Greater => {
+            let extra = &arguments[expected];
+            Err(DecodeError::unexpected(ctx.span(&extra), "argument",
+                                        message()))
+        }
+    }
+}
+
+/// Checks the number of arguments left for `arguments(min = .., max = ..)`
+pub fn check_arguments_count(node: &Node, arguments: &[Scalar],
+                             min: Option<usize>, max: Option<usize>,
+                             ctx: &Context)
+    -> Result<(), DecodeError>
+{
+    if let Some(min) = min {
+        if arguments.len() < min {
+            return Err(DecodeError::missing(ctx.span(&node),
+                format!("expected at least {}, found {}",
+                        count(min, "argument"), arguments.len())));
+        }
+    }
+    if let Some(max) = max {
+        if let Some(extra) = arguments.get(max) {
+            return Err(DecodeError::unexpected(ctx.span(&extra), "argument",
+                format!("expected at most {}, found {}",
+                        count(max, "argument"), arguments.len())));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the number of children collected by `children(min = .., max = ..)`
+///
+/// `excess` is the first child node above the maximum, `span` is the span of
+/// the parent node or `None` at the document level.
+pub fn check_children_count(found: usize, min: Option<usize>,
+                            max: Option<usize>, excess: Option<&Node>,
+                            span: Option<Span>, ctx: &Context)
+    -> Result<(), DecodeError>
+{
+    if let (Some(max), Some(child)) = (max, excess) {
+        return Err(DecodeError::unexpected(ctx.span(&child), "node",
+            format!("expected at most {}, found {}",
+                    count(max, "child node"), found)));
+    }
+    if let Some(min) = min {
+        if found < min {
+            let message = format!("expected at least {}, found {}",
+                                  count(min, "child node"), found);
+            return Err(match span {
+                Some(span) => DecodeError::missing(span, message),
+                None => DecodeError::MissingNode { message },
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Decodes the rest of the arguments into an array of the exact length
+pub fn decode_array<T, const N: usize>(node: &Node, arguments: &[Scalar],
+                                       ctx: &mut Context)
+    -> Result<[T; N], DecodeError>
+    where T: DecodeScalar,
+{
+    let arguments = fixed_arguments(node, arguments, N, ctx)?;
+    let items = arguments.iter()
+        .map(|scalar| <T as DecodeScalar>::decode(scalar, ctx))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(items.try_into().unwrap_or_else(|_| unreachable!()))
+}
+
+/// Decodes the scalar of an `Option<T>` field using `decode_with` function
+///
+/// `null` is decoded as `None`, other values are passed to the function.
+pub fn decode_option_with<T, F>(scalar: &Scalar, ctx: &mut Context,
+                                decode: F)
+    -> Result<Option<T>, DecodeError>
+    where F: FnOnce(&Scalar, &mut Context) -> Result<T, DecodeError>,
+{
+    if scalar.is_null() {
+        Ok(None)
+    } else {
+        decode(scalar, ctx).map(Some)
+    }
+}
+
+/// Encodes an `Option<T>` field using `encode_with` function
+///
+/// `None` is encoded as `null`.
+pub fn encode_option_with<T, F>(value: &Option<T>, ctx: &mut Context,
+                                encode: F)
+    -> Result<Scalar, EncodeError>
+    where F: FnOnce(&T, &mut Context) -> Result<Scalar, EncodeError>,
+{
+    match value {
+        None => Ok(Scalar {
+            type_name: None,
+            literal: "null".into(),
+            quoted: false,
+        }),
+        Some(value) => encode(value, ctx),
+    }
+}
+
+/// Decodes the `child` field using `decode_with` function
+///
+/// Works like [`DecodePartial`](crate::traits::DecodePartial) of `Option`:
+/// returns `Ok(false)` if the function fails, so the node can be tried with
+/// other fields.
+pub fn decode_child_with<T, F>(slot: &mut Option<T>, node: &Node,
+                               ctx: &mut Context, decode: F)
+    -> Result<bool, DecodeError>
+    where F: FnOnce(&Node, &mut Context) -> Result<T, DecodeError>,
+{
+    let value = match decode(node, ctx) {
+        Ok(value) => value,
+        Err(_) => return Ok(false),
+    };
+    if slot.is_some() {
+        return Err(DecodeError::unexpected(ctx.span(&node.node_name), "node",
+            format!("duplicate node `{}`, single node expected",
+                    node.node_name.as_ref())));
+    }
+    *slot = Some(value);
+    Ok(true)
+}
+
+/// Runs the `#[kfl(validate = path)]` function on the decoded value
+///
+/// An error returned by the function is reported as
+/// [`DecodeError::Conversion`] at `span`.
+pub fn validate_with<T, F, E>(value: &T, validator: F, span: &Span)
+    -> Result<(), DecodeError>
+    where T: ?Sized,
+          F: FnOnce(&T) -> Result<(), E>,
+          E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    validator(value).map_err(|e| DecodeError::conversion(span.clone(), e))
+}
+
+/// Checks the value against `#[kfl(range(min = .., max = ..))]`
+pub fn check_range<T>(value: &T, min: Option<T>, max: Option<T>, span: &Span)
+    -> Result<(), DecodeError>
+    where T: PartialOrd + fmt::Display,
+{
+    if let Some(min) = min {
+        if *value < min {
+            return Err(DecodeError::conversion(span.clone(),
+                format!("value must be at least {}, found {}", min, value)));
+        }
+    }
+    if let Some(max) = max {
+        if *value > max {
+            return Err(DecodeError::conversion(span.clone(),
+                format!("value must be at most {}, found {}", max, value)));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the value against `#[kfl(pattern = "..")]`
+///
+/// The pattern is not anchored, use `^` and `$` to match the whole value.
+/// The derive compiles each pattern once per field.
+#[cfg(feature = "regex")]
+pub fn check_pattern<T>(value: &T, regex: &regex::Regex, span: &Span)
+    -> Result<(), DecodeError>
+    where T: AsRef<str> + ?Sized,
+{
+    let value = value.as_ref();
+    if !regex.is_match(value) {
+        return Err(DecodeError::conversion(span.clone(),
+            format!("value `{}` does not match pattern `{}`",
+                    value.escape_default(), regex.as_str())));
+    }
+    Ok(())
+}
+
+/// Checks the value against a pattern compiled on first use
+///
+/// The derive validates the pattern at compile time, so compiling it here
+/// never fails.
+#[cfg(feature = "regex")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __check_pattern {
+    ($value:expr, $pattern:expr, $span:expr) => {{
+        static REGEX: $crate::__private::OnceCell<$crate::__private::Regex>
+            = $crate::__private::OnceCell::new();
+        let regex = REGEX.get_or_init(|| {
+            $crate::__private::Regex::new($pattern)
+                .expect("pattern is validated by the derive")
+        });
+        $crate::decode::check_pattern($value, regex, $span)
+    }};
+}
+
+/// Reports `#[kfl(pattern = "..")]` used without the `regex` feature
+#[cfg(not(feature = "regex"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __check_pattern {
+    ($value:expr, $pattern:expr, $span:expr) => {{
+        ::core::compile_error!(
+            "`pattern` requires the `regex` feature of kfl");
+        ::core::result::Result::<(), $crate::errors::DecodeError>::Ok(())
+    }};
+}
+
+/// Decodes the child node into a `#[kfl(children(key = name))]` map
+///
+/// The node name is parsed into the key and the only argument of the node is
+/// decoded into the value. Every node is consumed, so this never returns
+/// `None`.
+pub fn insert_child_by_name<M>(map: &mut M, child: &Node, children: &[Node],
+                               ctx: &mut Context)
+    -> Result<Option<()>, DecodeError>
+    where M: DecodeMap,
+          M::Key: FromStr + PartialEq,
+          <M::Key as FromStr>::Err:
+              Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+          M::Value: DecodeScalar,
+{
+    insert_named(map, child, children, ctx).map(Some)
+}
+
+/// Decodes the child node into a `#[kfl(children(key = argument))]` map
+///
+/// The first argument of the node is decoded into the key and the rest of
+/// the node is decoded into the value. Returns `None` if the node has no
+/// arguments, so it can be tried with other fields.
+pub fn insert_child_by_argument<M>(map: &mut M, child: &Node,
+                                   children: &[Node], ctx: &mut Context)
+    -> Result<Option<()>, DecodeError>
+    where M: DecodeMap,
+          M::Key: DecodeScalar + PartialEq,
+          M::Value: Decode,
+{
+    let (key, value_node) = match split_key_argument(child) {
+        Some(split) => split,
+        None => return Ok(None),
+    };
+    let value = <M::Value as Decode>::decode(&value_node, ctx)?;
+    insert_keyed(map, key, value, child, children, ctx).map(Some)
+}
+
+/// Encodes an entry of a `#[kfl(children(key = name))]` map
+pub fn encode_child_by_name<K, V>(key: &K, value: &V, ctx: &mut Context)
+    -> Result<Node, EncodeError>
+    where K: ToString,
+          V: EncodeScalar,
+{
+    Ok(Node {
+        type_name: None,
+        node_name: key.to_string().into_boxed_str(),
+        arguments: Vec::from([<V as EncodeScalar>::encode(value, ctx)?]),
+        properties: Default::default(),
+        children: None,
+    })
+}
+
+/// Encodes an entry of a `#[kfl(children(key = argument))]` map
+pub fn encode_child_by_argument<K, V>(key: &K, value: &V, ctx: &mut Context)
+    -> Result<Node, EncodeError>
+    where K: EncodeScalar,
+          V: Encode,
+{
+    let mut node = <V as Encode>::encode(value, ctx)?;
+    node.arguments.insert(0, <K as EncodeScalar>::encode(key, ctx)?);
+    Ok(node)
+}
+
+fn insert_named<M>(map: &mut M, child: &Node, children: &[Node],
+                   ctx: &mut Context)
+    -> Result<(), DecodeError>
+    where M: DecodeMap,
+          M::Key: FromStr + PartialEq,
+          <M::Key as FromStr>::Err:
+              Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+          M::Value: DecodeScalar,
+{
+    if child.type_name.is_some() {
+        return Err(DecodeError::unexpected(
+                   ctx.span(&child), "type name",
+                   "no type name expected for this node"));
+    }
+    let key: M::Key = child.node_name.parse()
+        .map_err(|e| DecodeError::conversion(ctx.span(&child), e))?;
+    if map.contains_key(&key) {
+        let first = preceding(child, children)
+            .find(|c| c.node_name.parse().map_or(false, |k: M::Key| k == key))
+            .unwrap_or(child);
+        return Err(duplicate(child, first, &child.node_name, ctx));
+    }
+    let value = decode_single_argument(child, ctx)?;
+    map.insert_entry(key, value);
+    Ok(())
+}
+
+fn insert_keyed<M>(map: &mut M, key: &Scalar, value: M::Value, child: &Node,
+                   children: &[Node], ctx: &mut Context)
+    -> Result<(), DecodeError>
+    where M: DecodeMap,
+          M::Key: DecodeScalar + PartialEq,
+{
+    let literal = &key.literal;
+    let key = <M::Key as DecodeScalar>::decode(key, ctx)?;
+    if map.contains_key(&key) {
+        let first = preceding(child, children)
+            .filter(|c| c.node_name == child.node_name)
+            .find(|c| {
+                c.arguments.first()
+                    .and_then(|k| DecodeScalar::decode(k, ctx).ok())
+                    .map_or(false, |k: M::Key| k == key)
+            })
+            .unwrap_or(child);
+        return Err(duplicate(child, first, literal, ctx));
+    }
+    map.insert_entry(key, value);
+    Ok(())
+}
+
+fn count(number: usize, noun: &str) -> String {
+    format!("{} {}{}", number, noun, if number == 1 { "" } else { "s" })
+}
+
+fn preceding<'a>(child: &'a Node, children: &'a [Node])
+    -> impl Iterator<Item = &'a Node>
+{
+    children.iter().take_while(move |c| !ptr::eq(*c, child))
+}
+
+fn split_key_argument(node: &Node) -> Option<(&Scalar, Node)> {
+    let (key, rest) = node.arguments.split_first()?;
+    let value = Node {
+        arguments: rest.to_vec(),
+        ..node.clone()
+    };
+    Some((key, value))
+}
+
+fn decode_single_argument<T: DecodeScalar>(node: &Node, ctx: &mut Context)
+    -> Result<T, DecodeError>
+{
+    check_arguments_only(node, ctx)?;
+    match &node.arguments[..] {
+        [value] => <T as DecodeScalar>::decode(value, ctx),
+        [] => Err(DecodeError::missing(
+            ctx.span(&node), "additional argument is required")),
+        [_, extra, ..] => Err(DecodeError::unexpected(
+            ctx.span(&extra), "argument", "unexpected argument")),
+    }
+}
+
+fn duplicate(child: &Node, first: &Node, key: &str, ctx: &Context)
+    -> DecodeError
+{
+    DecodeError::duplicate(
+        ctx.span(&child), ctx.span(&first),
+        format!("duplicate key `{}`", key.escape_default()))
+}
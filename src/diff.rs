@@ -0,0 +1,552 @@
+//! Structural diff and patch of documents
+//!
+//! [`diff`] compares two documents node by node, ignoring formatting such as
+//! the order of properties, the radix of numbers or how strings are quoted.
+//! Nodes are identified by a [`Key`]: their name and first argument, so
+//! `route "/api"` is the same node wherever it moves, while renaming it to
+//! `route "/v1"` removes one node and adds another. The resulting [`Diff`]
+//! prints as a report and can be [applied](Diff::apply) to another document:
+//!
+//! ```rust
+//! let old = kfl::kdl! {
+//!     server { route "/api" port=80; route "/static" }
+//! };
+//! let new = kfl::kdl! {
+//!     server { route "/static"; route "/api" port=8080; route "/v1" }
+//! };
+//! let diff = kfl::diff::diff(&old, &new);
+//! assert_eq!(diff.to_string(), r#"> server > route "/static" moved to the start
+//! + server > route "/v1" after route "/api"
+//!     route "/v1"
+//! ~ server > route "/api" property port: 80 -> 8080
+//! "#);
+//!
+//! let mut patched = old.clone();
+//! diff.apply(&mut patched)?;
+//! assert!(kfl::diff::diff(&patched, &new).is_empty());
+//! # Ok::<(), kfl::errors::PatchError>(())
+//! ```
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::ToString,
+    vec,
+    vec::Vec
+};
+use core::fmt::{self, Display};
+
+use crate::{
+    ast::{Node, Scalar},
+    errors::PatchError,
+    value::Value
+};
+
+/// Identity of a node among its siblings
+///
+/// Siblings with the same name and first argument are told apart by their
+/// `occurrence`, counting from zero in document order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Key {
+    /// The node name
+    pub name: Box<str>,
+    /// The first argument, if any
+    pub argument: Option<Value>,
+    /// Number of earlier siblings with the same name and argument
+    pub occurrence: usize,
+}
+
+/// Keys of a node and its ancestors, starting at the top level
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct KeyPath(pub Vec<Key>);
+
+/// Single change between two documents
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Change {
+    /// A node is added after the sibling `after`, or first if it is `None`
+    NodeAdded {
+        /// The parent node, empty for a top-level node
+        parent: KeyPath,
+        /// The preceding sibling in the new document
+        after: Option<Key>,
+        /// The added node with its children
+        node: Node,
+    },
+    /// A node is removed with its children
+    NodeRemoved {
+        /// The removed node
+        path: KeyPath,
+    },
+    /// A node is moved among its siblings
+    NodeMoved {
+        /// The moved node
+        path: KeyPath,
+        /// The preceding sibling in the new document, `None` moves the node
+        /// first
+        after: Option<Key>,
+    },
+    /// The type annotation of a node is changed, added or removed
+    TypeChanged {
+        /// The changed node
+        path: KeyPath,
+        /// The old type name
+        old: Option<Box<str>>,
+        /// The new type name
+        new: Option<Box<str>>,
+    },
+    /// An argument is changed, added at the end or removed from the end
+    ArgumentChanged {
+        /// The changed node
+        path: KeyPath,
+        /// Position of the argument
+        index: usize,
+        /// The old value
+        old: Option<Scalar>,
+        /// The new value
+        new: Option<Scalar>,
+    },
+    /// A property is changed, added or removed
+    PropertyChanged {
+        /// The changed node
+        path: KeyPath,
+        /// The property name
+        name: Box<str>,
+        /// The old value
+        old: Option<Scalar>,
+        /// The new value
+        new: Option<Scalar>,
+    },
+}
+
+/// Changes turning one document into another
+///
+/// Changes are ordered so that they can be replayed one by one: on each
+/// level removals come first, then additions and moves in the order of the
+/// new document, then changes inside the remaining nodes.
+#[derive(Debug, Clone, Default)]
+pub struct Diff {
+    /// The changes in the order they are applied
+    pub changes: Vec<Change>,
+}
+
+/// Compares two documents
+pub fn diff(old: &[Node], new: &[Node]) -> Diff {
+    let mut changes = Vec::new();
+    diff_nodes(old, new, &KeyPath::default(), &mut changes);
+    Diff { changes }
+}
+
+impl Diff {
+    /// Returns `true` if the documents are the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    /// Replays the changes onto a document
+    ///
+    /// The document doesn't have to be the one the diff is made from, but it
+    /// must contain the nodes the changes refer to. Changes applied before
+    /// an error are kept.
+    pub fn apply(&self, nodes: &mut Vec<Node>) -> Result<(), PatchError> {
+        for change in &self.changes {
+            change.apply(nodes)?;
+        }
+        Ok(())
+    }
+}
+
+fn keys(nodes: &[Node]) -> Vec<Key> {
+    let mut counts = BTreeMap::<(Box<str>, Option<Value>), usize>::new();
+    nodes.iter().map(|node| {
+        let name = node.node_name.clone();
+        let argument = node.arguments.first().map(Value::from);
+        let count = counts.entry((name.clone(), argument.clone()))
+            .or_insert(0);
+        let occurrence = *count;
+        *count += 1;
+        Key { name, argument, occurrence }
+    }).collect()
+}
+
+fn same(old: Option<&Scalar>, new: Option<&Scalar>) -> bool {
+    old.map(Value::from) == new.map(Value::from)
+}
+
+/// Indices into `sequence` of its longest increasing subsequence
+fn longest_increasing(sequence: &[usize]) -> Vec<usize> {
+    // `tails[k]` ends the increasing subsequence of length `k + 1` that has
+    // the smallest last value found so far
+    let mut tails = Vec::<usize>::new();
+    let mut previous = Vec::<Option<usize>>::with_capacity(sequence.len());
+    for (i, value) in sequence.iter().enumerate() {
+        let length = tails.partition_point(|&j| sequence[j] < *value);
+        previous.push(length.checked_sub(1).map(|k| tails[k]));
+        if length == tails.len() {
+            tails.push(i);
+        } else {
+            tails[length] = i;
+        }
+    }
+    let mut result = Vec::with_capacity(tails.len());
+    let mut last = tails.last().copied();
+    while let Some(i) = last {
+        result.push(i);
+        last = previous[i];
+    }
+    result.reverse();
+    result
+}
+
+fn diff_nodes(old: &[Node], new: &[Node], parent: &KeyPath,
+              changes: &mut Vec<Change>)
+{
+    let old_keys = keys(old);
+    let new_keys = keys(new);
+    let old_index = old_keys.iter().enumerate()
+        .map(|(i, key)| (key, i))
+        .collect::<BTreeMap<_, _>>();
+    let matched = new_keys.iter()
+        .map(|key| old_index.get(key).copied())
+        .collect::<Vec<_>>();
+
+    let mut kept = vec![false; old.len()];
+    for &i in matched.iter().flatten() {
+        kept[i] = true;
+    }
+    for (i, key) in old_keys.iter().enumerate().rev() {
+        if !kept[i] {
+            changes.push(Change::NodeRemoved { path: parent.child(key) });
+        }
+    }
+
+    let order = matched.iter().flatten().copied().collect::<Vec<_>>();
+    let mut stable = vec![false; old.len()];
+    for i in longest_increasing(&order) {
+        stable[order[i]] = true;
+    }
+    for (j, key) in new_keys.iter().enumerate() {
+        let after = j.checked_sub(1).map(|j| new_keys[j].clone());
+        match matched[j] {
+            None => changes.push(Change::NodeAdded {
+                parent: parent.clone(),
+                after,
+                node: new[j].clone(),
+            }),
+            Some(i) if !stable[i] => changes.push(Change::NodeMoved {
+                path: parent.child(key),
+                after,
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (j, key) in new_keys.iter().enumerate() {
+        if let Some(i) = matched[j] {
+            diff_node(&old[i], &new[j], &parent.child(key), changes);
+        }
+    }
+}
+
+fn diff_node(old: &Node, new: &Node, path: &KeyPath,
+             changes: &mut Vec<Change>)
+{
+    if old.type_name != new.type_name {
+        changes.push(Change::TypeChanged {
+            path: path.clone(),
+            old: old.type_name.clone(),
+            new: new.type_name.clone(),
+        });
+    }
+
+    let common = old.arguments.len().min(new.arguments.len());
+    for index in 0..new.arguments.len() {
+        let (before, after) = (old.arguments.get(index), &new.arguments[index]);
+        if !same(before, Some(after)) {
+            changes.push(Change::ArgumentChanged {
+                path: path.clone(),
+                index,
+                old: before.cloned(),
+                new: Some(after.clone()),
+            });
+        }
+    }
+    for index in (common..old.arguments.len()).rev() {
+        changes.push(Change::ArgumentChanged {
+            path: path.clone(),
+            index,
+            old: Some(old.arguments[index].clone()),
+            new: None,
+        });
+    }
+
+    let old_properties = old.properties.iter().collect::<BTreeMap<_, _>>();
+    let new_properties = new.properties.iter().collect::<BTreeMap<_, _>>();
+    for (name, value) in old.properties.iter() {
+        let after = new_properties.get(name).copied();
+        if !same(Some(value), after) {
+            changes.push(Change::PropertyChanged {
+                path: path.clone(),
+                name: name.clone(),
+                old: Some(value.clone()),
+                new: after.cloned(),
+            });
+        }
+    }
+    for (name, value) in new.properties.iter() {
+        if !old_properties.contains_key(name) {
+            changes.push(Change::PropertyChanged {
+                path: path.clone(),
+                name: name.clone(),
+                old: None,
+                new: Some(value.clone()),
+            });
+        }
+    }
+
+    diff_nodes(old.children.as_deref().unwrap_or(&[]),
+               new.children.as_deref().unwrap_or(&[]),
+               path, changes);
+}
+
+fn not_found(path: &KeyPath) -> PatchError {
+    PatchError { message: format!("node `{}` not found", path) }
+}
+
+fn position(nodes: &[Node], key: &Key) -> Option<usize> {
+    nodes.iter().enumerate()
+        .filter(|(_, node)| node.node_name == key.name)
+        .filter(|(_, node)| {
+            node.arguments.first().map(Value::from) == key.argument
+        })
+        .nth(key.occurrence)
+        .map(|(index, _)| index)
+}
+
+fn siblings_mut<'a>(mut nodes: &'a mut Vec<Node>, parent: &[Key])
+    -> Option<&'a mut Vec<Node>>
+{
+    for key in parent {
+        let index = position(nodes, key)?;
+        nodes = nodes[index].children.as_mut()?;
+    }
+    Some(nodes)
+}
+
+fn node_mut<'a>(nodes: &'a mut Vec<Node>, path: &KeyPath)
+    -> Result<&'a mut Node, PatchError>
+{
+    let (key, parent) = path.0.split_last().ok_or_else(|| not_found(path))?;
+    let siblings = siblings_mut(nodes, parent).ok_or_else(|| not_found(path))?;
+    let index = position(siblings, key).ok_or_else(|| not_found(path))?;
+    Ok(&mut siblings[index])
+}
+
+fn insert_after(siblings: &mut Vec<Node>, after: &Option<Key>, node: Node,
+                parent: &KeyPath)
+    -> Result<(), PatchError>
+{
+    let index = match after {
+        Some(key) => {
+            position(siblings, key).ok_or_else(|| not_found(&parent.child(key)))?
+                + 1
+        }
+        None => 0,
+    };
+    siblings.insert(index, node);
+    Ok(())
+}
+
+impl Change {
+    /// The node the change is made in, or the parent of an added node
+    pub fn path(&self) -> &KeyPath {
+        match self {
+            Change::NodeAdded { parent, .. } => parent,
+            Change::NodeRemoved { path }
+            | Change::NodeMoved { path, .. }
+            | Change::TypeChanged { path, .. }
+            | Change::ArgumentChanged { path, .. }
+            | Change::PropertyChanged { path, .. } => path,
+        }
+    }
+    fn apply(&self, nodes: &mut Vec<Node>) -> Result<(), PatchError> {
+        match self {
+            Change::NodeAdded { parent, after, node } => {
+                let siblings = if parent.0.is_empty() {
+                    nodes
+                } else {
+                    node_mut(nodes, parent)?.children
+                        .get_or_insert_with(Vec::new)
+                };
+                insert_after(siblings, after, node.clone(), parent)
+            }
+            Change::NodeRemoved { path } => {
+                let (key, parent) = path.0.split_last()
+                    .ok_or_else(|| not_found(path))?;
+                let siblings = siblings_mut(nodes, parent)
+                    .ok_or_else(|| not_found(path))?;
+                let index = position(siblings, key)
+                    .ok_or_else(|| not_found(path))?;
+                siblings.remove(index);
+                Ok(())
+            }
+            Change::NodeMoved { path, after } => {
+                let (key, parent) = path.0.split_last()
+                    .ok_or_else(|| not_found(path))?;
+                let siblings = siblings_mut(nodes, parent)
+                    .ok_or_else(|| not_found(path))?;
+                let index = position(siblings, key)
+                    .ok_or_else(|| not_found(path))?;
+                if let Some(after) = after {
+                    if position(siblings, after).is_none() {
+                        return Err(not_found(&KeyPath(parent.to_vec())
+                                             .child(after)));
+                    }
+                }
+                let node = siblings.remove(index);
+                insert_after(siblings, after, node,
+                             &KeyPath(parent.to_vec()))
+            }
+            Change::TypeChanged { path, new, .. } => {
+                node_mut(nodes, path)?.type_name = new.clone();
+                Ok(())
+            }
+            Change::ArgumentChanged { path, index, new, .. } => {
+                let arguments = &mut node_mut(nodes, path)?.arguments;
+                let missing = || PatchError {
+                    message: format!("node `{}` has no argument {}",
+                                     path, index),
+                };
+                match new {
+                    Some(new) if *index < arguments.len() => {
+                        arguments[*index] = new.clone();
+                    }
+                    Some(new) if *index == arguments.len() => {
+                        arguments.push(new.clone());
+                    }
+                    None if *index < arguments.len() => {
+                        arguments.remove(*index);
+                    }
+                    _ => return Err(missing()),
+                }
+                Ok(())
+            }
+            Change::PropertyChanged { path, name, new, .. } => {
+                let node = node_mut(nodes, path)?;
+                match new {
+                    Some(new) => {
+                        node.properties.insert(name.clone(), new.clone());
+                    }
+                    None => {
+                        node.properties.remove(name);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl KeyPath {
+    /// Path of the child with the `key`
+    pub fn child(&self, key: &Key) -> KeyPath {
+        let mut keys = self.0.clone();
+        keys.push(key.clone());
+        KeyPath(keys)
+    }
+}
+
+/// Name followed by the argument, e.g. `route "/api"`, with `[n]` added for
+/// repeated siblings
+impl Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(argument) = &self.argument {
+            let argument = Scalar::try_from(argument)
+                .map_err(|_| fmt::Error)?;
+            write!(f, " {}", argument)?;
+        }
+        if self.occurrence > 0 {
+            write!(f, "[{}]", self.occurrence)?;
+        }
+        Ok(())
+    }
+}
+
+/// Keys separated by ` > `
+impl Display for KeyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, key) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" > ")?;
+            }
+            write!(f, "{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+struct Maybe<'a>(&'a Option<Scalar>);
+
+impl Display for Maybe<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(scalar) => write!(f, "{}", scalar),
+            None => f.write_str("missing"),
+        }
+    }
+}
+
+/// One line per change starting with `+` for added nodes, `-` for removed
+/// ones, `>` for moved ones and `~` for changes inside a node. Added nodes
+/// are followed by their text
+impl Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::NodeAdded { parent, after, node } => {
+                let key = keys(core::slice::from_ref(node)).remove(0);
+                write!(f, "+ {}", parent.child(&key))?;
+                match after {
+                    Some(after) => write!(f, " after {}", after)?,
+                    None => f.write_str(" at the start")?,
+                }
+                for line in node.to_string().lines() {
+                    write!(f, "\n    {}", line)?;
+                }
+                Ok(())
+            }
+            Change::NodeRemoved { path } => write!(f, "- {}", path),
+            Change::NodeMoved { path, after: Some(after) } => {
+                write!(f, "> {} moved after {}", path, after)
+            }
+            Change::NodeMoved { path, after: None } => {
+                write!(f, "> {} moved to the start", path)
+            }
+            Change::TypeChanged { path, old, new } => {
+                let name = |n: &Option<Box<str>>| match n {
+                    Some(name) => format!("({})", name),
+                    None => "missing".to_string(),
+                };
+                write!(f, "~ {} type: {} -> {}", path, name(old), name(new))
+            }
+            Change::ArgumentChanged { path, index, old, new } => {
+                write!(f, "~ {} argument {}: {} -> {}",
+                       path, index, Maybe(old), Maybe(new))
+            }
+            Change::PropertyChanged { path, name, old, new } => {
+                write!(f, "~ {} property {}: {} -> {}",
+                       path, name, Maybe(old), Maybe(new))
+            }
+        }
+    }
+}
+
+/// The changes, one per line
+impl Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
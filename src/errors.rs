@@ -115,6 +115,22 @@ pub enum DecodeError {
         /// Description of the error
         message: String,
     },
+    /// Duplicate key in a map
+    ///
+    /// This is emitted when two nodes decoded into the same
+    /// `#[kfl(children(key = ..))]` map have equal keys.
+    #[diagnostic()]
+    #[error("{}", message)]
+    Duplicate {
+        /// Position of the repeated key
+        #[label("duplicate key")]
+        span: Span,
+        /// Position of the first occurrence of the key
+        #[label("first defined here")]
+        first_span: Span,
+        /// Description of the error
+        message: String,
+    },
     /// Bad scalar conversion
     ///
     /// This error is emitted when some scalar value of right kind cannot be
@@ -122,6 +138,7 @@ pub enum DecodeError {
     /// 1. Integer value out of range
     /// 2. `FromStr` returned error for the value parse by
     ///    `#[kfl(.., str)]`
+    /// 3. The value failed `#[kfl(validate = ..)]` or a built-in check
     #[error("{}", source)]
     #[diagnostic()]
     Conversion {
@@ -183,6 +200,33 @@ pub enum EncodeError {
     Custom(Box<dyn std::error::Error + Send + Sync + 'static>)
 }
 
+/// Error parsing a [KQL query](crate::query)
+///
+/// Implements [`miette::Diagnostic`] and carries the query text, so it can be
+/// printed with the offending part of the query highlighted.
+#[derive(Debug, Diagnostic, Error)]
+#[error("{}", message)]
+pub struct QueryError {
+    #[source_code]
+    pub(crate) query: String,
+    /// Position in the query
+    #[label("{}", label)]
+    pub span: Span,
+    /// Short description shown at the position
+    pub label: &'static str,
+    /// Description of the error
+    pub message: String,
+}
+
+/// Error applying a [diff](crate::diff) to a document that doesn't have
+/// the changed nodes
+#[derive(Debug, Diagnostic, Error)]
+#[error("{}", message)]
+pub struct PatchError {
+    /// Description of the error
+    pub message: String,
+}
+
 ///
 #[derive(Clone, Debug, PartialOrd, Ord, PartialEq, Eq)]
 pub(crate) enum TokenFormat {
@@ -427,8 +471,19 @@ impl DecodeError {
             message: message.into(),
         }
     }
+    /// Construct [`DecodeError::Duplicate`] error
+    pub fn duplicate(span: Span, first_span: Span,
+                     message: impl Into<String>)
+        -> Self
+    {
+        DecodeError::Duplicate {
+            span,
+            first_span,
+            message: message.into(),
+        }
+    }
     /// Construct [`DecodeError::Unsupported`] error
-    pub fn unsupported<T, M>(span: Span, message: M)-> Self
+    pub fn unsupported<M>(span: Span, message: M)-> Self
         where M: Into<Cow<'static, str>>,
     {
         DecodeError::Unsupported {
@@ -470,6 +525,15 @@ impl ExpectedType {
             no_type: true,
         }
     }
+    /// Declare the types one of which can be attached to the value
+    ///
+    /// No type is also okay in this case.
+    pub fn optional_one_of(types: &[&str]) -> Self {
+        ExpectedType {
+            types: types.iter().map(|&ty| ty.into()).collect(),
+            no_type: true,
+        }
+    }
 }
 
 impl Display for ExpectedType {
@@ -506,4 +570,12 @@ impl EncodeError {
             found: found.into(),
         }
     }
+    /// Construct [`EncodeError::Unexpected`] error for NaN or infinity,
+    /// which have no KDL representation
+    pub fn non_finite(value: f64) -> Self {
+        EncodeError::Unexpected {
+            kind: "number",
+            message: format!("{} can't be represented in KDL", value),
+        }
+    }
 }
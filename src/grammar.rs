@@ -1,7 +1,7 @@
 use alloc::{
     borrow::ToOwned,
     boxed::Box,
-    collections::{BTreeSet, BTreeMap},
+    collections::BTreeSet,
     string::String,
     vec::Vec
 };
@@ -14,7 +14,7 @@ use chumsky::{
 };
 
 use crate::{
-    ast::{Node, Scalar},
+    ast::{Node, Properties, Property, Scalar},
     context::Context,
     errors::{ParseError, TokenFormat},
     span::Span
@@ -263,9 +263,10 @@ fn ident<'a>() -> impl Parser<'a, I<'a>, Box<str>, Extra> + Clone {
     bare_ident().or(string())
 }
 
-fn literal<'a>() -> impl Parser<'a, I<'a>, Box<str>, Extra> + Clone {
-    string()
-    .or(any().filter(|c| c != &' ' && c != &'{' && c != &'}' && c != &'\n' && c != &'(' && c != &')' && c != &'\\' && c != &'=' && c != &'"').repeated().at_least(1).map_slice(|v: &str| v.chars().collect::<String>().into()))
+/// Parses the literal of a scalar, the flag is `true` for quoted strings
+fn literal<'a>() -> impl Parser<'a, I<'a>, (Box<str>, bool), Extra> + Clone {
+    string().map(|literal| (literal, true))
+    .or(any().filter(|c| c != &' ' && c != &'{' && c != &'}' && c != &'\n' && c != &'(' && c != &')' && c != &'\\' && c != &'=' && c != &'"').repeated().at_least(1).map_slice(|v: &str| (v.chars().collect::<String>().into(), false)))
 }
 
 fn type_name<'a>() -> impl Parser<'a, I<'a>, Box<str>, Extra> + Clone {
@@ -298,28 +299,36 @@ fn node_terminator<'a>() -> impl Parser<'a, I<'a>, (), Extra> + Clone {
 
 #[derive(Debug)]
 enum PropOrArg {
-    Prop(Box<str>, Scalar),
+    Prop(Box<str>, Scalar, Span),
     Arg(Scalar),
     Ignore,
 }
 
 fn type_name_value<'a>() -> impl Parser<'a, I<'a>, Scalar, Extra> + Clone {
     type_name().then(literal())
-    .map(|(type_name, literal)| Scalar { type_name: Some(type_name), literal })
+    .map(|(type_name, (literal, quoted))| {
+        Scalar { type_name: Some(type_name), literal, quoted }
+    })
 }
 
 fn scalar<'a>() -> impl Parser<'a, I<'a>, Scalar, Extra> + Clone {
     type_name_value()
-    .or(literal().map(|literal| Scalar { type_name: None, literal }))
+    .or(literal().map(|(literal, quoted)| {
+        Scalar { type_name: None, literal, quoted }
+    }))
 }
 
 fn prop_or_arg_inner<'a>() -> impl Parser<'a, I<'a>, PropOrArg, Extra> + Clone {
     use PropOrArg::*;
     choice((
         bare_ident().then(just('=').ignore_then(scalar()))
-            .map(|(name, scalar)| Prop(name, scalar)),
-        string().then(just('=').ignore_then(scalar())).map(
-            |(name, scalar)| Prop(name, scalar)),
+            .map_with_state(|(name, scalar), span, _| {
+                Prop(name, scalar, Span::from(span))
+            }),
+        string().then(just('=').ignore_then(scalar()))
+            .map_with_state(|(name, scalar), span, _| {
+                Prop(name, scalar, Span::from(span))
+            }),
         scalar().map(Arg),
     ))
 }
@@ -387,7 +396,7 @@ fn nodes<'a>() -> impl Parser<'a, I<'a>, Vec<Node>, Extra> {
                 let mut node = Node {
                     type_name,
                     node_name,
-                    properties: BTreeMap::new(),
+                    properties: Properties::new(),
                     arguments: Vec::new(),
                     children: match opt_children {
                         Some((Some(_comment), _)) => None,
@@ -397,8 +406,9 @@ fn nodes<'a>() -> impl Parser<'a, I<'a>, Vec<Node>, Extra> {
                 };
                 for item in line_items {
                     match item {
-                        Prop(name, scalar) => {
-                            node.properties.insert(name, scalar);
+                        Prop(name, value, span) => {
+                            node.properties.push(
+                                Property { name, value, span });
                         }
                         Arg(scalar) => {
                             node.arguments.push(scalar);
@@ -443,6 +453,7 @@ mod test {
     use crate::ast::Scalar;
     use crate::context::Context;
     use crate::errors::{Error, ParseError};
+    use crate::span::Span;
     use super::{ws, comment, ml_comment, string, ident, bare_ident, literal, type_name, type_name_value, prop_or_arg_inner};
     use super::{nodes};
 
@@ -959,7 +970,8 @@ mod test {
     #[test]
     fn parse_type_name_value() {
         assert_eq!(parse(type_name_value(), "(abcdef)\"hello\"").unwrap(),
-                   Scalar { type_name: Some("abcdef".into()), literal: "hello".into() });
+                   Scalar { type_name: Some("abcdef".into()), literal: "hello".into(),
+                            quoted: true });
         // assert_eq!(parse(type_name_value(), "(xx_cd$yy)\"hello\"").unwrap(),
         //            "xx_cd$yy".into());
         // parse(type_name_value(), "(1abc)\"hello\"").unwrap_err();
@@ -1014,6 +1026,12 @@ mod test {
         assert_eq!(nval.properties.len(), 0);
         assert_eq!(&nval.arguments[0].literal,
                    &"true".into());
+        assert!(nval.arguments[0].quoted);
+
+        let nval = single(parse(nodes(), "node null \"null\""));
+        assert_eq!(nval.arguments.len(), 2);
+        assert!(nval.arguments[0].is_null());
+        assert!(!nval.arguments[1].is_null());
 
         // let nval = single(parse(nodes(), "hello (string)\"arg1\""));
         // assert_eq!(nval.node_name.as_ref(), "hello");
@@ -1044,6 +1062,19 @@ mod test {
         assert_eq!(&nval.properties.get("key").unwrap().literal,
                    &"arg1".into());
 
+        let nval = single(parse(nodes(), "hello b=1 a=2 b=3"));
+        assert_eq!(nval.properties.len(), 2);
+        assert_eq!(nval.properties.iter()
+                   .map(|(name, value)| (&**name, &*value.literal))
+                   .collect::<Vec<_>>(),
+                   [("b", "3"), ("a", "2")]);
+        assert_eq!(&nval.properties.first("b").unwrap().literal,
+                   &"1".into());
+        let duplicates = nval.properties.duplicates().collect::<Vec<_>>();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(&*duplicates[0].name, "b");
+        assert_eq!(duplicates[0].span, Span(14, 17));
+
         let nval = single(parse(nodes(), "parent {\nchild\n}"));
         assert_eq!(nval.node_name.as_ref(), "parent");
         assert_eq!(nval.children().len(), 1);
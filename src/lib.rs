@@ -9,21 +9,41 @@ mod grammar;
 mod wrappers;
 
 pub mod ast;
+pub mod bytes;
+pub mod canonical;
 pub mod context;
 pub mod decode;
+pub mod diff;
 pub mod errors;
 pub mod own;
 pub mod print;
+pub mod query;
 pub mod span;
 pub mod traits;
+pub mod units;
+pub mod value;
+pub mod visit;
 
 #[cfg(feature = "derive")]
 pub use kfl_derive::{Decode, DecodePartial, DecodeScalar};
 #[cfg(feature = "derive")]
 pub use kfl_derive::{Encode, EncodePartial, EncodeScalar};
+#[cfg(feature = "derive")]
+pub use kfl_derive::kdl;
 
 pub use wrappers::{decode, decode_children, decode_with_context, parse};
-pub use wrappers::{encode, encode_children, print};
+pub use wrappers::{encode, encode_children, encode_with_context, print};
 pub use traits::{Decode, DecodePartial, DecodeScalar};
 pub use traits::{Encode, EncodePartial, EncodeScalar};
 pub use errors::Error;
+pub use value::Value;
+
+/// Items used by the code generated by the macros
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec;
+    #[cfg(feature = "regex")]
+    pub use once_cell::sync::OnceCell;
+    #[cfg(feature = "regex")]
+    pub use regex::Regex;
+}
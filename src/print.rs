@@ -6,35 +6,90 @@ use crate::ast::{Node, Scalar};
 
 impl Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // TODO(rnarkk)
-        // if let Some(typ) = &self.type_name {
-        //     write!(f, "({})", &typ)?;
-        // }
-        write!(f, "{}", &self.node_name)?;
-        for scalar in self.arguments.iter() {
-            write!(f, " {}", &scalar)?;
-        }
-        for (name, scalar) in self.properties.iter() {
-            write!(f, " {}={}", name, &scalar)?;
-        }
-        if let Some(children) = &self.children {
-            write!(f, " {{")?;
-            for child in children.iter() {
-                write!(f, "\n  {}", child)?;
-            }
-            write!(f, "\n}}")
-        } else {
-            write!(f, "")
+        write_node(self, f, 0)
+    }
+}
+
+/// Writes the node with its children indented by two spaces per level
+fn write_node(node: &Node, f: &mut fmt::Formatter<'_>, depth: usize)
+    -> fmt::Result
+{
+    if let Some(typ) = &node.type_name {
+        write!(f, "(")?;
+        write_ident(typ, f)?;
+        write!(f, ")")?;
+    }
+    write_ident(&node.node_name, f)?;
+    for scalar in node.arguments.iter() {
+        write!(f, " {}", &scalar)?;
+    }
+    for property in node.properties.entries() {
+        write!(f, " ")?;
+        write_ident(&property.name, f)?;
+        write!(f, "={}", &property.value)?;
+    }
+    if let Some(children) = &node.children {
+        write!(f, " {{")?;
+        for child in children.iter() {
+            write!(f, "\n{:indent$}", "", indent = 2 * (depth + 1))?;
+            write_node(child, f, depth + 1)?;
         }
+        write!(f, "\n{:indent$}}}", "", indent = 2 * depth)
+    } else {
+        Ok(())
     }
 }
 
+/// Writes the identifier bare if it parses back as the same identifier,
+/// otherwise as a quoted string
+fn write_ident(name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if is_bare_ident(name) {
+        write!(f, "{}", name)
+    } else {
+        write!(f, "{:?}", name)
+    }
+}
+
+fn is_bare_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    let first = match chars.next() {
+        Some(c) => c,
+        None => return false,
+    };
+    let after_sign = if matches!(first, '-' | '+') {
+        chars.next()
+    } else {
+        Some(first)
+    };
+    !matches!(after_sign, Some('0'..='9'))
+        && !matches!(name, "true" | "false" | "null")
+        && name.chars().all(is_id_char)
+}
+
+/// Mirrors `id_char` of the grammar
+fn is_id_char(c: char) -> bool {
+    !matches!(c,
+        '\u{0000}'..='\u{0021}' |
+        '\\'|'/'|'('|')'|'{'|'}'|'<'|'>'|';'|'['|']'|'='|','|'"' |
+        '\u{00a0}' | '\u{1680}' |
+        '\u{2000}'..='\u{200A}' |
+        '\u{202F}' | '\u{205F}' | '\u{3000}' |
+        '\u{0085}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
 impl Display for Scalar {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(typ) = &self.type_name {
-            write!(f, "({})", &typ)?;
+            write!(f, "(")?;
+            write_ident(typ, f)?;
+            write!(f, ")")?;
+        }
+        if self.quoted {
+            write!(f, "{:?}", &self.literal)
+        } else {
+            write!(f, "{}", &self.literal)
         }
-        write!(f, "{}", &self.literal)
     }
 }
 
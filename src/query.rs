@@ -0,0 +1,624 @@
+//! KDL Query Language (KQL)
+//!
+//! Selects nodes of a document with CSS-like selectors:
+//!
+//! ```rust
+//! let nodes = kfl::kdl! {
+//!     route path="/api" {
+//!         plugin "auth"
+//!         group { plugin "cache" }
+//!     }
+//!     route path="/static" { plugin "gzip" }
+//! };
+//! let found = kfl::query::select(r#"top() > route[path="/api"] >> plugin"#,
+//!                                &nodes)?;
+//! let paths = found.iter().map(|m| m.path.to_string()).collect::<Vec<_>>();
+//! assert_eq!(paths, ["route/plugin", "route/group/plugin"]);
+//! # Ok::<(), kfl::errors::QueryError>(())
+//! ```
+//!
+//! A selector is a list of filters joined by combinators, alternatives are
+//! separated by `||`:
+//!
+//! | Combinator | Selects                                      |
+//! |------------|----------------------------------------------|
+//! | `a > b`    | `b` that is a child of `a`                   |
+//! | `a >> b`   | `b` that is a descendant of `a`              |
+//! | `a + b`    | `b` that immediately follows its sibling `a` |
+//! | `a ~ b`    | `b` that follows its sibling `a`             |
+//!
+//! Without combinators a filter matches nodes at any depth. `top()` stands for
+//! the document itself and can only start a selector, so `top() > a` selects
+//! top-level `a` nodes.
+//!
+//! A filter is a node name, a type annotation `(type)` (`()` for any
+//! annotation) and any number of matchers in brackets, `[]` matches any
+//! node. A matcher is an accessor optionally compared with a value:
+//!
+//! * `val()` or `val(1)` -- the first or the given argument
+//! * `prop(name)` or just `name` -- a property
+//! * `name()` -- the node name
+//! * `type()` -- the type annotation of the node
+//!
+//! Without a comparison the matcher checks that the accessed value exists.
+//! Values are compared with `=` and `!=`, numbers also with `>`, `>=`, `<` and
+//! `<=`, and strings with `^=` (starts with), `$=` (ends with) and `*=`
+//! (contains).
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec
+};
+use core::{cmp::Ordering, str::FromStr};
+
+use crate::{
+    ast::{Node, Scalar},
+    errors::QueryError,
+    span::Span,
+    value::{Literal, Value},
+    visit::Path
+};
+
+/// Parsed KQL query
+///
+/// Parse it once with [`Query::parse`] or [`str::parse`] to run it against
+/// several documents.
+#[derive(Debug, Clone)]
+pub struct Query {
+    selectors: Vec<Selector>,
+}
+
+/// Node selected by a query
+#[derive(Debug, Clone)]
+pub struct Match<'a> {
+    /// The selected node
+    pub node: &'a Node,
+    /// Where the node is in the document
+    pub path: Path,
+}
+
+#[derive(Debug, Clone)]
+struct Selector {
+    first: Filter,
+    rest: Vec<(Combinator, Filter)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Child,
+    Descendant,
+    Next,
+    Following,
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Top,
+    Nodes(Vec<Matcher>),
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Name(Box<str>),
+    Type(Option<Box<str>>),
+    Accessor(Accessor, Option<(Operator, Literal)>),
+}
+
+#[derive(Debug, Clone)]
+enum Accessor {
+    Name,
+    Type,
+    Argument(usize),
+    Property(Box<str>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    StartsWith,
+    EndsWith,
+    Contains,
+}
+
+struct Entry<'a> {
+    node: &'a Node,
+    path: Path,
+    parent: Option<usize>,
+    previous: Option<usize>,
+}
+
+/// Parses the query and selects the matching nodes, see [`Query::select`]
+pub fn select<'a>(query: &str, nodes: &'a [Node])
+    -> Result<Vec<Match<'a>>, QueryError>
+{
+    Ok(Query::parse(query)?.select(nodes))
+}
+
+impl Query {
+    /// Parses a query
+    pub fn parse(query: &str) -> Result<Query, QueryError> {
+        Parser { text: query, pos: 0 }.query()
+    }
+    /// Returns the nodes matching any of the selectors, in document order
+    pub fn select<'a>(&self, nodes: &'a [Node]) -> Vec<Match<'a>> {
+        let mut entries = Vec::new();
+        collect(nodes, None, &Path::root(), &mut entries);
+        let mut found = Vec::new();
+        for id in 0..entries.len() {
+            if self.selectors.iter().any(|s| s.matches(&entries, id)) {
+                let entry = &entries[id];
+                found.push(Match { node: entry.node, path: entry.path.clone() });
+            }
+        }
+        found
+    }
+    /// Returns the first node matching the query
+    pub fn first<'a>(&self, nodes: &'a [Node]) -> Option<Match<'a>> {
+        self.select(nodes).into_iter().next()
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+    fn from_str(query: &str) -> Result<Query, QueryError> {
+        Query::parse(query)
+    }
+}
+
+fn collect<'a>(nodes: &'a [Node], parent: Option<usize>, path: &Path,
+               entries: &mut Vec<Entry<'a>>)
+{
+    let mut previous = None;
+    for (index, node) in nodes.iter().enumerate() {
+        let id = entries.len();
+        let path = path.child(index, &node.node_name);
+        entries.push(Entry { node, path: path.clone(), parent, previous });
+        if let Some(children) = &node.children {
+            collect(children, Some(id), &path, entries);
+        }
+        previous = Some(id);
+    }
+}
+
+impl Selector {
+    fn filter(&self, step: usize) -> &Filter {
+        match step {
+            0 => &self.first,
+            _ => &self.rest[step - 1].1,
+        }
+    }
+    fn matches(&self, entries: &[Entry], id: usize) -> bool {
+        self.matches_step(self.rest.len(), entries, id)
+    }
+    fn matches_step(&self, step: usize, entries: &[Entry], id: usize) -> bool {
+        let entry = &entries[id];
+        let matched = match self.filter(step) {
+            Filter::Top => entry.parent.is_none(),
+            Filter::Nodes(matchers) => {
+                matchers.iter().all(|m| m.matches(entry.node))
+            }
+        };
+        if !matched || step == 0 {
+            return matched;
+        }
+        let combinator = self.rest[step - 1].0;
+        if let Filter::Top = self.filter(step - 1) {
+            return match combinator {
+                Combinator::Child => entry.parent.is_none(),
+                _ => true,
+            };
+        }
+        let previous = |id| self.matches_step(step - 1, entries, id);
+        match combinator {
+            Combinator::Child => entry.parent.map_or(false, previous),
+            Combinator::Descendant => {
+                let mut parent = entry.parent;
+                while let Some(id) = parent {
+                    if previous(id) {
+                        return true;
+                    }
+                    parent = entries[id].parent;
+                }
+                false
+            }
+            Combinator::Next => entry.previous.map_or(false, previous),
+            Combinator::Following => {
+                let mut sibling = entry.previous;
+                while let Some(id) = sibling {
+                    if previous(id) {
+                        return true;
+                    }
+                    sibling = entries[id].previous;
+                }
+                false
+            }
+        }
+    }
+}
+
+impl Matcher {
+    fn matches(&self, node: &Node) -> bool {
+        match self {
+            Matcher::Name(name) => node.node_name == *name,
+            Matcher::Type(None) => node.type_name.is_some(),
+            Matcher::Type(Some(name)) => node.type_name.as_ref() == Some(name),
+            Matcher::Accessor(accessor, comparison) => {
+                let found = match accessor.access(node) {
+                    Some(found) => found,
+                    None => return false,
+                };
+                match comparison {
+                    Some((operator, expected)) => {
+                        operator.compare(&found, expected)
+                    }
+                    None => true,
+                }
+            }
+        }
+    }
+}
+
+impl Accessor {
+    fn access(&self, node: &Node) -> Option<Literal> {
+        let scalar = |scalar: &Scalar| Value::from(scalar).literal;
+        match self {
+            Accessor::Name => Some(Literal::String(node.node_name.clone())),
+            Accessor::Type => node.type_name.clone().map(Literal::String),
+            Accessor::Argument(index) => {
+                node.arguments.get(*index).map(scalar)
+            }
+            Accessor::Property(name) => node.properties.get(name).map(scalar),
+        }
+    }
+}
+
+fn number(literal: &Literal) -> Option<f64> {
+    match *literal {
+        Literal::Integer(value) => Some(value as f64),
+        Literal::Unsigned(value) => Some(value as f64),
+        Literal::Float(value) => Some(value),
+        _ => None,
+    }
+}
+
+impl Operator {
+    fn compare(self, found: &Literal, expected: &Literal) -> bool {
+        let ordering = match (found, expected) {
+            (Literal::Integer(a), Literal::Integer(b)) => Some(a.cmp(b)),
+            (Literal::Unsigned(a), Literal::Unsigned(b)) => Some(a.cmp(b)),
+            // unsigned literals are all above `i128::MAX`
+            (Literal::Integer(_), Literal::Unsigned(_)) => Some(Ordering::Less),
+            (Literal::Unsigned(_), Literal::Integer(_)) => {
+                Some(Ordering::Greater)
+            }
+            _ => number(found).zip(number(expected))
+                .and_then(|(a, b)| a.partial_cmp(&b)),
+        };
+        let strings = match (found, expected) {
+            (Literal::String(a), Literal::String(b)) => Some((a, b)),
+            _ => None,
+        };
+        match self {
+            Operator::Equal => {
+                ordering.map_or(found == expected, Ordering::is_eq)
+            }
+            Operator::NotEqual => {
+                ordering.map_or(found != expected, Ordering::is_ne)
+            }
+            Operator::Greater => ordering.map_or(false, Ordering::is_gt),
+            Operator::GreaterOrEqual => ordering.map_or(false, Ordering::is_ge),
+            Operator::Less => ordering.map_or(false, Ordering::is_lt),
+            Operator::LessOrEqual => ordering.map_or(false, Ordering::is_le),
+            Operator::StartsWith => {
+                strings.map_or(false, |(a, b)| a.starts_with(&**b))
+            }
+            Operator::EndsWith => {
+                strings.map_or(false, |(a, b)| a.ends_with(&**b))
+            }
+            Operator::Contains => {
+                strings.map_or(false, |(a, b)| a.contains(&**b))
+            }
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c,
+        '(' | ')' | '[' | ']' | '{' | '}' | '<' | '>' | '=' | ',' | ';' |
+        '"' | '\\' | '/' | '|' | '+' | '~' | '!' | '^' | '$' | '*')
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, span: Span, label: &'static str, message: String)
+        -> QueryError
+    {
+        QueryError {
+            query: self.text.to_string(),
+            span,
+            label,
+            message,
+        }
+    }
+    fn unexpected(&self, expected: &str) -> QueryError {
+        match self.peek() {
+            Some(c) => {
+                let span = Span(self.pos, self.pos + c.len_utf8());
+                self.error(span, "unexpected character",
+                    format!("expected {}, found `{}`", expected, c))
+            }
+            None => {
+                let span = Span(self.pos, self.pos);
+                self.error(span, "unexpected end of query",
+                    format!("expected {}, found end of query", expected))
+            }
+        }
+    }
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+    fn expect(&mut self, token: &str) -> Result<(), QueryError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", token)))
+        }
+    }
+
+    fn query(mut self) -> Result<Query, QueryError> {
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.selector()?);
+            self.skip_whitespace();
+            if self.peek().is_none() {
+                return Ok(Query { selectors });
+            }
+            self.expect("||")?;
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, QueryError> {
+        let start = self.pos;
+        let first = self.filter()?;
+        let mut rest = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let combinator = if self.eat(">>") {
+                Combinator::Descendant
+            } else if self.eat(">") {
+                Combinator::Child
+            } else if self.eat("+") {
+                Combinator::Next
+            } else if self.eat("~") {
+                Combinator::Following
+            } else if self.peek().is_none() || self.rest().starts_with("||") {
+                return Ok(Selector { first, rest });
+            } else {
+                return Err(self.unexpected(
+                    "`>`, `>>`, `+`, `~`, `||` or end of query"));
+            };
+            if let (Filter::Top, Combinator::Next | Combinator::Following)
+                = (&first, combinator)
+            {
+                if rest.is_empty() {
+                    let span = Span(start, self.pos);
+                    return Err(self.error(span, "top() has no siblings",
+                        "`top()` can only be followed by `>` or `>>`".into()));
+                }
+            }
+            self.skip_whitespace();
+            let filter_start = self.pos;
+            let filter = self.filter()?;
+            if let Filter::Top = filter {
+                let span = Span(filter_start, self.pos);
+                return Err(self.error(span, "misplaced top()",
+                    "`top()` is only allowed at the start of a selector"
+                    .into()));
+            }
+            rest.push((combinator, filter));
+        }
+    }
+
+    fn filter(&mut self) -> Result<Filter, QueryError> {
+        if self.eat("top()") {
+            return Ok(Filter::Top);
+        }
+        let mut matchers = Vec::new();
+        let mut empty = true;
+        if self.eat("(") {
+            empty = false;
+            self.skip_whitespace();
+            if self.eat(")") {
+                matchers.push(Matcher::Type(None));
+            } else {
+                let name = self.name()?;
+                self.skip_whitespace();
+                self.expect(")")?;
+                matchers.push(Matcher::Type(Some(name)));
+            }
+        }
+        if self.peek().map_or(false, |c| c == '"' || is_name_char(c)) {
+            empty = false;
+            matchers.push(Matcher::Name(self.name()?));
+        }
+        while self.eat("[") {
+            empty = false;
+            self.skip_whitespace();
+            if self.eat("]") {
+                continue;
+            }
+            let accessor = self.accessor()?;
+            self.skip_whitespace();
+            let comparison = match self.operator() {
+                Some(operator) => {
+                    self.skip_whitespace();
+                    Some((operator, self.literal()?))
+                }
+                None => None,
+            };
+            self.skip_whitespace();
+            self.expect("]")?;
+            matchers.push(Matcher::Accessor(accessor, comparison));
+        }
+        if empty {
+            return Err(self.unexpected("a node name, `(`, `[` or `top()`"));
+        }
+        Ok(Filter::Nodes(matchers))
+    }
+
+    fn accessor(&mut self) -> Result<Accessor, QueryError> {
+        if self.eat("val(") {
+            self.skip_whitespace();
+            let start = self.pos;
+            let digits = self.rest().chars()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+            let index = if digits == 0 {
+                0
+            } else {
+                self.pos += digits;
+                self.text[start..self.pos].parse().map_err(|_| {
+                    self.error(Span(start, self.pos), "invalid index",
+                               "argument index is too large".into())
+                })?
+            };
+            self.skip_whitespace();
+            self.expect(")")?;
+            return Ok(Accessor::Argument(index));
+        }
+        if self.eat("prop(") {
+            self.skip_whitespace();
+            let name = self.name()?;
+            self.skip_whitespace();
+            self.expect(")")?;
+            return Ok(Accessor::Property(name));
+        }
+        if self.eat("name()") {
+            return Ok(Accessor::Name);
+        }
+        if self.eat("type()") {
+            return Ok(Accessor::Type);
+        }
+        Ok(Accessor::Property(self.name()?))
+    }
+
+    fn operator(&mut self) -> Option<Operator> {
+        const OPERATORS: &[(&str, Operator)] = &[
+            ("!=", Operator::NotEqual),
+            (">=", Operator::GreaterOrEqual),
+            ("<=", Operator::LessOrEqual),
+            ("^=", Operator::StartsWith),
+            ("$=", Operator::EndsWith),
+            ("*=", Operator::Contains),
+            ("=", Operator::Equal),
+            (">", Operator::Greater),
+            ("<", Operator::Less),
+        ];
+        OPERATORS.iter()
+            .find(|(token, _)| self.eat(token))
+            .map(|(_, operator)| *operator)
+    }
+
+    fn name(&mut self) -> Result<Box<str>, QueryError> {
+        if self.peek() == Some('"') {
+            return self.string();
+        }
+        let start = self.pos;
+        let len = self.rest().chars()
+            .take_while(|&c| is_name_char(c))
+            .map(char::len_utf8)
+            .sum::<usize>();
+        if len == 0 || self.rest().starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.unexpected("a name"));
+        }
+        self.pos += len;
+        Ok(self.text[start..self.pos].into())
+    }
+
+    fn string(&mut self) -> Result<Box<str>, QueryError> {
+        let start = self.pos;
+        self.expect("\"")?;
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += offset + 1;
+                    return Ok(value.into());
+                }
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, '/')) => '/',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((end, c)) => {
+                            let at = self.pos + offset;
+                            let span = Span(at, self.pos + end + c.len_utf8());
+                            return Err(self.error(span, "invalid escape",
+                                format!("invalid escape `\\{}`", c)));
+                        }
+                        None => break,
+                    };
+                    value.push(escaped);
+                }
+                c => value.push(c),
+            }
+        }
+        Err(self.error(Span(start, self.text.len()), "unclosed string",
+                       "unclosed string, expected `\"`".into()))
+    }
+
+    fn literal(&mut self) -> Result<Literal, QueryError> {
+        if self.peek() == Some('"') {
+            return Ok(Literal::String(self.string()?));
+        }
+        let start = self.pos;
+        let len = self.rest().chars()
+            .take_while(|&c| c.is_ascii_alphanumeric()
+                        || matches!(c, '.' | '_' | '-' | '+'))
+            .count();
+        if len == 0 {
+            return Err(self.unexpected("a value"));
+        }
+        self.pos += len;
+        let text = &self.text[start..self.pos];
+        match Value::from(&Scalar::bare(text)).literal {
+            Literal::String(_) => {
+                Err(self.error(Span(start, self.pos), "invalid value",
+                    format!("expected a string, a number, `true`, `false` \
+                             or `null`, found `{}`", text)))
+            }
+            literal => Ok(literal),
+        }
+    }
+}
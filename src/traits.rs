@@ -32,15 +32,55 @@ pub trait DecodePartial: Sized + Default {
     /// structure).
     fn decode_partial(&mut self, node: &Node, ctx: &mut Context)
         -> Result<bool, DecodeError>;
-    // /// The method is called when unknown property is encountered by parent
-    // /// structure
-    // ///
-    // /// Returns `Ok(true)` if the property is "consumed" (i.e. stored in this
-    // /// structure).
-    // fn insert_property(&mut self,
-    //                    name: &Box<str>, scalar: &Scalar,
-    //                    ctx: &mut Context)
-    //     -> Result<bool, DecodeError>;
+    /// The method is called when unknown property is encountered by parent
+    /// structure
+    ///
+    /// Returns `Ok(true)` if the property is "consumed" (i.e. stored in this
+    /// structure). Default implementation consumes no properties.
+    fn insert_property(&mut self, name: &str, scalar: &Scalar,
+                       ctx: &mut Context)
+        -> Result<bool, DecodeError>
+    {
+        let _ = (name, scalar, ctx);
+        Ok(false)
+    }
+    /// The method is called by parent structure after all of its properties
+    /// are processed
+    ///
+    /// Returns the name of a required property for which `inserted` is
+    /// `false`, i.e. that wasn't consumed by
+    /// [`insert_property`](Self::insert_property). Default implementation
+    /// requires no properties.
+    fn missing_property(inserted: &dyn Fn(&str) -> bool)
+        -> Option<&'static str>
+    {
+        let _ = inserted;
+        None
+    }
+    /// The method is called by parent structure after all of its properties
+    /// are processed
+    ///
+    /// Sets properties for which `inserted` is `false` to their default
+    /// values, since the structure itself starts from
+    /// [`Default::default`]. Default implementation does nothing.
+    fn apply_defaults(&mut self, inserted: &dyn Fn(&str) -> bool) {
+        let _ = inserted;
+    }
+}
+
+/// Map types that `#[kfl(children(key = ..))]` fields can be decoded into
+///
+/// Implemented for [`BTreeMap`](alloc::collections::BTreeMap),
+/// `HashMap` (with `std` feature) and `IndexMap` (with `indexmap` feature).
+pub trait DecodeMap: Default {
+    /// Type of the keys of the map
+    type Key;
+    /// Type of the values of the map
+    type Value;
+    /// Returns `true` if the map already contains the key
+    fn contains_key(&self, key: &Self::Key) -> bool;
+    /// Inserts the entry into the map
+    fn insert_entry(&mut self, key: Self::Key, value: Self::Value);
 }
 
 /// The trait that decodes scalar value and checks its type
@@ -55,9 +95,11 @@ pub trait Encode: Decode {
     fn encode(&self, ctx: &mut Context) -> Result<Node, EncodeError>;
 }
 
-/// TODO(rnarkk)
+/// The counterpart of [`DecodePartial`], used to encode `#[kfl(flatten)]`
+/// fields
 pub trait EncodePartial: DecodePartial {
-    /// TODO(rnarkk)
+    /// Writes properties and children of this structure into the node of the
+    /// parent structure
     fn encode_partial(&self, node: &mut Node, ctx: &mut Context)
         -> Result<(), EncodeError>;
 }
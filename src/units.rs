@@ -0,0 +1,390 @@
+//! Scalars with human-friendly units
+//!
+//! [`ByteSize`], [`HumanDuration`] and [`Rate`] accept values like
+//! `"512MiB"`, `"30s"` and `"10/min"`. Numbers with a unit as the type
+//! annotation, like `(MiB)512`, are accepted as well. Numeric new-types can
+//! use a system of units with the `units` option of the derives:
+//!
+//! ```rust
+//! #[derive(kfl::DecodeScalar, kfl::EncodeScalar, Debug)]
+//! #[kfl(units = kfl::units::Bytes)]
+//! struct MemoryLimit(u64);
+//! ```
+//!
+//! Values are normalised to the base unit of the system (bytes, seconds,
+//! milliseconds) and encoded back in the largest unit that represents them
+//! exactly, e.g. `"512MiB"`.
+
+use alloc::{
+    format,
+    string::String,
+    vec::Vec
+};
+use core::{fmt, ops::Deref, time::Duration};
+
+use crate::{
+    ast::Scalar,
+    context::Context,
+    convert::time::{format_duration, parse_duration},
+    decode::{check_scalar_kind, check_scalar_type},
+    errors::{DecodeError, EncodeError},
+    traits::{DecodeScalar, EncodeScalar}
+};
+
+/// A system of units
+pub trait Units {
+    /// Names of the units with their size in the base unit, which has size
+    /// one. If several units have the same size the first one is used for
+    /// encoding.
+    const UNITS: &'static [(&'static str, u128)];
+}
+
+/// Bytes with decimal (`kB`, `MB`, ...) and binary (`KiB`, `MiB`, ...) units
+#[derive(Debug)]
+pub struct Bytes;
+
+impl Units for Bytes {
+    const UNITS: &'static [(&'static str, u128)] = &[
+        ("B", 1),
+        ("kB", 1_000),
+        ("KB", 1_000),
+        ("MB", 1_000_000),
+        ("GB", 1_000_000_000),
+        ("TB", 1_000_000_000_000),
+        ("PB", 1_000_000_000_000_000),
+        ("EB", 1_000_000_000_000_000_000),
+        ("KiB", 1 << 10),
+        ("MiB", 1 << 20),
+        ("GiB", 1 << 30),
+        ("TiB", 1 << 40),
+        ("PiB", 1 << 50),
+        ("EiB", 1 << 60),
+    ];
+}
+
+/// Seconds, with minutes (`m` or `min`), hours and days
+#[derive(Debug)]
+pub struct Seconds;
+
+impl Units for Seconds {
+    const UNITS: &'static [(&'static str, u128)] = &[
+        ("s", 1),
+        ("m", 60),
+        ("min", 60),
+        ("h", 3_600),
+        ("d", 86_400),
+    ];
+}
+
+/// Milliseconds, with seconds, minutes (`m` or `min`), hours and days
+#[derive(Debug)]
+pub struct Millis;
+
+impl Units for Millis {
+    const UNITS: &'static [(&'static str, u128)] = &[
+        ("ms", 1),
+        ("s", 1_000),
+        ("m", 60_000),
+        ("min", 60_000),
+        ("h", 3_600_000),
+        ("d", 86_400_000),
+    ];
+}
+
+fn unit_size<U: Units>(unit: &str) -> Option<u128> {
+    U::UNITS.iter().find(|(name, _)| *name == unit).map(|&(_, size)| size)
+}
+
+fn base_unit<U: Units>() -> &'static str {
+    U::UNITS.iter().find(|(_, size)| *size == 1)
+        .map(|&(name, _)| name).unwrap_or("")
+}
+
+/// Converts an amount like `512` or `1.5` of `size` base units into base
+/// units, `None` if the result is not a whole number
+fn parse_amount(amount: &str, size: u128) -> Option<u128> {
+    let amount = amount.replace('_', "");
+    let (int, frac) = amount.split_once('.').unwrap_or((&amount, ""));
+    if int.is_empty() || !int.bytes().all(|b| b.is_ascii_digit())
+        || !frac.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let mut value = int.parse::<u128>().ok()?.checked_mul(size)?;
+    let frac = frac.trim_end_matches('0');
+    if !frac.is_empty() {
+        let scale = 10u128.checked_pow(frac.len() as u32)?;
+        let part = frac.parse::<u128>().ok()?.checked_mul(size)?;
+        if part % scale != 0 {
+            return None;
+        }
+        value = value.checked_add(part / scale)?;
+    }
+    Some(value)
+}
+
+/// Decodes a value of the system of units `U` in its base unit
+///
+/// Accepts a string like `"512MiB"` or `"1.5 GiB"`, a number with a unit
+/// annotation like `(MiB)512` and a plain number, which is in the base unit.
+pub fn decode_units<U: Units>(scalar: &Scalar, rust_type: &'static str,
+                              ctx: &mut Context)
+    -> Result<u128, DecodeError>
+{
+    let names = U::UNITS.iter().map(|&(name, _)| name).collect::<Vec<_>>();
+    check_scalar_type(scalar, &names, rust_type, ctx)?;
+    let literal = scalar.literal.trim();
+    let value = match scalar.type_name.as_deref() {
+        Some(unit) => {
+            check_scalar_kind(scalar, "number", false, ctx)?;
+            unit_size::<U>(unit).and_then(|size| parse_amount(literal, size))
+        }
+        None => {
+            let split = literal
+                .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '_'))
+                .unwrap_or(literal.len());
+            let (amount, unit) = literal.split_at(split);
+            let unit = unit.trim_start();
+            let size = if unit.is_empty() {
+                check_scalar_kind(scalar, "number", false, ctx)?;
+                Some(1)
+            } else {
+                check_scalar_kind(scalar, "string", true, ctx)?;
+                unit_size::<U>(unit)
+            };
+            size.and_then(|size| parse_amount(amount, size))
+        }
+    };
+    value.ok_or_else(|| {
+        DecodeError::conversion(ctx.span(&scalar),
+            format!("invalid quantity `{}`, expected a whole number of `{}` \
+                     or a number with one of the units {}",
+                    scalar.literal.escape_default(), base_unit::<U>(),
+                    names.join(", ")))
+    })
+}
+
+/// Writes the value in the largest unit of `U` that represents it exactly
+pub fn format_units<U: Units>(value: u128) -> String {
+    let mut best = (base_unit::<U>(), 1);
+    if value != 0 {
+        for &(name, size) in U::UNITS {
+            if size > best.1 && value % size == 0 {
+                best = (name, size);
+            }
+        }
+    }
+    let (name, size) = best;
+    format!("{}{}", value / size, name)
+}
+
+/// Encodes the value as a string in the most readable unit of `U`
+pub fn encode_units<U: Units>(value: u128) -> Scalar {
+    Scalar {
+        type_name: None,
+        literal: format_units::<U>(value).into(),
+        quoted: true,
+    }
+}
+
+/// Decodes a numeric value with the system of units `U`, used by the
+/// `units` option of the derives
+pub fn decode<U, T>(scalar: &Scalar, rust_type: &'static str,
+                    ctx: &mut Context)
+    -> Result<T, DecodeError>
+    where U: Units,
+          T: TryFrom<u128>,
+{
+    let value = decode_units::<U>(scalar, rust_type, ctx)?;
+    T::try_from(value).map_err(|_| {
+        DecodeError::conversion(ctx.span(&scalar),
+            format!("value `{}` is out of range for {}", value, rust_type))
+    })
+}
+
+/// Encodes a numeric value with the system of units `U`, used by the
+/// `units` option of the derives
+pub fn encode<U, T>(value: &T, _: &mut Context) -> Result<Scalar, EncodeError>
+    where U: Units,
+          T: Copy + fmt::Display + TryInto<u128>,
+{
+    let magnitude = (*value).try_into().map_err(|_| {
+        EncodeError::Unexpected {
+            kind: "number",
+            message: format!("cannot encode `{}` with units", value),
+        }
+    })?;
+    Ok(encode_units::<U>(magnitude))
+}
+
+/// Number of bytes, written like `"512MiB"` or `(GB)2`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    /// Number of bytes
+    pub fn bytes(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        ByteSize(bytes)
+    }
+}
+
+impl From<ByteSize> for u64 {
+    fn from(size: ByteSize) -> Self {
+        size.0
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_units::<Bytes>(self.0.into()))
+    }
+}
+
+impl DecodeScalar for ByteSize {
+    fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
+        decode::<Bytes, u64>(scalar, "ByteSize", ctx).map(ByteSize)
+    }
+}
+
+impl EncodeScalar for ByteSize {
+    fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
+        Ok(encode_units::<Bytes>(self.0.into()))
+    }
+}
+
+/// Duration written like `"1h30m"` or `(s)90`, a plain number is a number of
+/// seconds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanDuration(pub Duration);
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        HumanDuration(duration)
+    }
+}
+
+impl From<HumanDuration> for Duration {
+    fn from(duration: HumanDuration) -> Self {
+        duration.0
+    }
+}
+
+impl Deref for HumanDuration {
+    type Target = Duration;
+    fn deref(&self) -> &Duration {
+        &self.0
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_duration(&self.0))
+    }
+}
+
+impl DecodeScalar for HumanDuration {
+    fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
+        if scalar.type_name.is_none() && !scalar.quoted {
+            if let Ok(secs) = scalar.literal.parse::<u64>() {
+                return Ok(HumanDuration(Duration::from_secs(secs)));
+            }
+        }
+        crate::convert::time::decode_duration(scalar, "HumanDuration", ctx)
+            .map(HumanDuration)
+    }
+}
+
+impl EncodeScalar for HumanDuration {
+    fn encode(&self, ctx: &mut Context) -> Result<Scalar, EncodeError> {
+        EncodeScalar::encode(&self.0, ctx)
+    }
+}
+
+/// Number of events per period, written like `"10/min"`, `"100/s"` or
+/// `"5/30s"`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rate {
+    /// Number of events
+    pub count: u64,
+    /// Period the events are counted over, never zero
+    pub period: Duration,
+}
+
+impl Rate {
+    /// Creates a rate of `count` events per `period`
+    pub fn new(count: u64, period: Duration) -> Self {
+        Rate { count, period }
+    }
+    /// Number of events per second
+    pub fn per_second(&self) -> f64 {
+        self.count as f64 / self.period.as_secs_f64()
+    }
+}
+
+fn parse_period(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let period = match text {
+        "sec" | "second" => "1s",
+        "min" | "minute" => "1m",
+        "hour" => "1h",
+        "day" => "1d",
+        _ if text.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+            return parse_duration(&format!("1{}", text));
+        }
+        _ => text,
+    };
+    parse_duration(period)
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let period = format_duration(&self.period);
+        match period.strip_prefix('1') {
+            Some("m") => write!(f, "{}/min", self.count),
+            Some(unit) if unit.chars().all(|c| c.is_ascii_alphabetic()) => {
+                write!(f, "{}/{}", self.count, unit)
+            }
+            _ => write!(f, "{}/{}", self.count, period),
+        }
+    }
+}
+
+impl DecodeScalar for Rate {
+    fn decode(scalar: &Scalar, ctx: &mut Context) -> Result<Self, DecodeError> {
+        check_scalar_type(scalar, &[], "Rate", ctx)?;
+        let rate = scalar.literal.split_once('/')
+            .and_then(|(count, period)| {
+                let count = count.trim().replace('_', "").parse().ok()?;
+                let period = parse_period(period)
+                    .filter(|period| !period.is_zero())?;
+                Some(Rate { count, period })
+            });
+        rate.ok_or_else(|| {
+            DecodeError::conversion(ctx.span(&scalar),
+                format!("invalid rate `{}`, expected e.g. `10/min` or `5/30s`",
+                        scalar.literal.escape_default()))
+        })
+    }
+}
+
+impl EncodeScalar for Rate {
+    fn encode(&self, _: &mut Context) -> Result<Scalar, EncodeError> {
+        if self.period.is_zero() {
+            return Err(EncodeError::Unexpected {
+                kind: "rate",
+                message: format!("period of rate {} is zero", self.count),
+            });
+        }
+        Ok(Scalar {
+            type_name: None,
+            literal: format!("{}", self).into(),
+            quoted: true,
+        })
+    }
+}
@@ -0,0 +1,592 @@
+//! Dynamic document model
+//!
+//! For documents whose schema is not known at compile time. [`Document`],
+//! [`Node`] and [`Value`] own their data, compare and hash structurally and
+//! implement the decode and encode traits, so they can be mixed into derived
+//! structures:
+//!
+//! ```rust
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let doc: kfl::value::Document = kfl::decode_children("config.kdl", r#"
+//!     server host="localhost" port=8080 {
+//!         tls enabled=true
+//!     }
+//! "#)?;
+//! assert_eq!(doc.at("server").unwrap().get_prop::<u16>("port")?, 8080);
+//! assert_eq!(doc.prop_at("server/tls/enabled"), Some(&true.into()));
+//! # Ok(())
+//! # }
+//! ```
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec
+};
+use core::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+    ops::Index
+};
+
+use crate::{
+    ast,
+    context::Context,
+    errors::{DecodeError, EncodeError},
+    span::Span,
+    traits::{
+        Decode, DecodePartial, DecodeScalar, Encode, EncodePartial,
+        EncodeScalar
+    }
+};
+
+/// Literal of a [`Value`]
+///
+/// Floats compare and hash by their bits, so `NaN` equals itself and `0.0`
+/// differs from `-0.0`. They are ordered by [`f64::total_cmp`], literals of
+/// different kinds in the order of the variants.
+#[derive(Debug, Clone)]
+pub enum Literal {
+    /// Quoted string
+    String(Box<str>),
+    /// Integer, written in any radix
+    Integer(i128),
+    /// Integer above `i128::MAX`, smaller ones are always
+    /// [`Integer`](Literal::Integer)
+    Unsigned(u128),
+    /// Decimal number, also used for integers that don't fit `u128`
+    Float(f64),
+    /// `true` or `false`
+    Bool(bool),
+    /// `null`
+    Null,
+}
+
+/// Possibly typed scalar value
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Value {
+    /// A type name if specified in parenthesis
+    pub type_name: Option<Box<str>>,
+    /// The value itself
+    pub literal: Literal,
+}
+
+/// Single node with its properties in insertion order
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Node {
+    /// A type name if specified in parenthesis
+    pub type_name: Option<Box<str>>,
+    /// A node name
+    pub name: Box<str>,
+    /// Positional arguments
+    pub arguments: Vec<Value>,
+    /// Named properties, each name occurs once
+    pub properties: Vec<(Box<str>, Value)>,
+    /// Node's children. This field is not none if there are braces `{..}`
+    pub children: Option<Vec<Node>>,
+}
+
+/// Nodes of a whole document
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Document {
+    /// Top-level nodes
+    pub nodes: Vec<Node>,
+}
+
+impl PartialEq for Literal {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Literal::String(a), Literal::String(b)) => a == b,
+            (Literal::Integer(a), Literal::Integer(b)) => a == b,
+            (Literal::Unsigned(a), Literal::Unsigned(b)) => a == b,
+            (Literal::Float(a), Literal::Float(b)) => a.to_bits() == b.to_bits(),
+            (Literal::Bool(a), Literal::Bool(b)) => a == b,
+            (Literal::Null, Literal::Null) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Literal {}
+
+impl PartialOrd for Literal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Literal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn rank(literal: &Literal) -> u8 {
+            match literal {
+                Literal::String(_) => 0,
+                Literal::Integer(_) => 1,
+                Literal::Unsigned(_) => 2,
+                Literal::Float(_) => 3,
+                Literal::Bool(_) => 4,
+                Literal::Null => 5,
+            }
+        }
+        match (self, other) {
+            (Literal::String(a), Literal::String(b)) => a.cmp(b),
+            (Literal::Integer(a), Literal::Integer(b)) => a.cmp(b),
+            (Literal::Unsigned(a), Literal::Unsigned(b)) => a.cmp(b),
+            (Literal::Float(a), Literal::Float(b)) => a.total_cmp(b),
+            (Literal::Bool(a), Literal::Bool(b)) => a.cmp(b),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl Hash for Literal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            Literal::String(value) => value.hash(state),
+            Literal::Integer(value) => value.hash(state),
+            Literal::Unsigned(value) => value.hash(state),
+            Literal::Float(value) => value.to_bits().hash(state),
+            Literal::Bool(value) => value.hash(state),
+            Literal::Null => {}
+        }
+    }
+}
+
+impl Literal {
+    /// Writes the literal the way the decoders expect it, strings unquoted
+    ///
+    /// Fails for NaN and infinities, which KDL can't represent.
+    pub(crate) fn to_literal(&self) -> Result<Box<str>, EncodeError> {
+        Ok(match self {
+            Literal::String(value) => value.clone(),
+            Literal::Integer(value) => value.to_string().into(),
+            Literal::Unsigned(value) => value.to_string().into(),
+            Literal::Float(value) if !value.is_finite() => {
+                return Err(EncodeError::non_finite(*value));
+            }
+            Literal::Float(value) => format!("{:?}", value).into(),
+            Literal::Bool(true) => "true".into(),
+            Literal::Bool(false) => "false".into(),
+            Literal::Null => "null".into(),
+        })
+    }
+}
+
+impl Value {
+    /// Creates an untyped value
+    pub fn new(literal: Literal) -> Self {
+        Value { type_name: None, literal }
+    }
+    /// Creates a value with the type annotation
+    pub fn typed(type_name: &str, literal: Literal) -> Self {
+        Value { type_name: Some(type_name.into()), literal }
+    }
+    /// Returns `true` if the value is `null`
+    pub fn is_null(&self) -> bool {
+        matches!(self.literal, Literal::Null)
+    }
+    /// Returns the string if the value is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.literal {
+            Literal::String(value) => Some(value),
+            _ => None,
+        }
+    }
+    /// Returns the integer if the value is an integer that fits `i128`
+    pub fn as_i128(&self) -> Option<i128> {
+        match self.literal {
+            Literal::Integer(value) => Some(value),
+            _ => None,
+        }
+    }
+    /// Returns the integer if the value is a non-negative integer
+    pub fn as_u128(&self) -> Option<u128> {
+        match self.literal {
+            Literal::Integer(value) => u128::try_from(value).ok(),
+            Literal::Unsigned(value) => Some(value),
+            _ => None,
+        }
+    }
+    /// Returns the number if the value is a float or an integer
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.literal {
+            Literal::Float(value) => Some(value),
+            Literal::Integer(value) => Some(value as f64),
+            Literal::Unsigned(value) => Some(value as f64),
+            _ => None,
+        }
+    }
+    /// Returns the boolean if the value is a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.literal {
+            Literal::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+    /// Converts the value with the [`DecodeScalar`] implementation of `T`,
+    /// including the checks of its type annotation
+    pub fn get<T: DecodeScalar>(&self) -> Result<T, DecodeError> {
+        let scalar = ast::Scalar::try_from(self)
+            .map_err(|err| DecodeError::conversion(Span(0, 0), err))?;
+        T::decode(&scalar, &mut Context::new())
+    }
+}
+
+/// Classifies a bare literal, anything that is not a keyword or a number is
+/// kept as a string
+fn bare_literal(literal: &str) -> Literal {
+    match literal {
+        "null" => return Literal::Null,
+        "true" => return Literal::Bool(true),
+        "false" => return Literal::Bool(false),
+        _ => {}
+    }
+    let scalar = ast::Scalar::bare(literal);
+    let ctx = &mut Context::new();
+    let digits = literal.trim_start_matches(['-', '+']);
+    let radix = ["0x", "0o", "0b"].iter().any(|p| digits.starts_with(p));
+    if radix || !digits.contains(['.', 'e', 'E']) {
+        if let Ok(value) = <i128 as DecodeScalar>::decode(&scalar, ctx) {
+            return Literal::Integer(value);
+        }
+        if let Ok(value) = <u128 as DecodeScalar>::decode(&scalar, ctx) {
+            return Literal::Unsigned(value);
+        }
+    }
+    match <f64 as DecodeScalar>::decode(&scalar, ctx) {
+        Ok(value) => Literal::Float(value),
+        Err(_) => Literal::String(literal.into()),
+    }
+}
+
+impl From<&ast::Scalar> for Value {
+    fn from(scalar: &ast::Scalar) -> Self {
+        let literal = if scalar.quoted {
+            Literal::String(scalar.literal.clone())
+        } else {
+            bare_literal(&scalar.literal)
+        };
+        Value { type_name: scalar.type_name.clone(), literal }
+    }
+}
+
+/// Fails for NaN and infinities, which KDL can't represent
+impl TryFrom<&Value> for ast::Scalar {
+    type Error = EncodeError;
+    fn try_from(value: &Value) -> Result<Self, EncodeError> {
+        Ok(ast::Scalar {
+            type_name: value.type_name.clone(),
+            literal: value.literal.to_literal()?,
+            quoted: matches!(value.literal, Literal::String(_)),
+        })
+    }
+}
+
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Value::new(Literal::$variant(value.into()))
+                }
+            }
+        )*
+    }
+}
+
+impl_from!(&str => String, String => String, Box<str> => String,
+           i8 => Integer, i16 => Integer, i32 => Integer, i64 => Integer,
+           i128 => Integer, u8 => Integer, u16 => Integer, u32 => Integer,
+           u64 => Integer, f32 => Float, f64 => Float, bool => Bool);
+
+impl From<u128> for Value {
+    fn from(value: u128) -> Self {
+        match i128::try_from(value) {
+            Ok(value) => Value::new(Literal::Integer(value)),
+            Err(_) => Value::new(Literal::Unsigned(value)),
+        }
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or_else(|| Value::new(Literal::Null))
+    }
+}
+
+impl DecodeScalar for Value {
+    fn decode(scalar: &ast::Scalar, _: &mut Context)
+        -> Result<Self, DecodeError>
+    {
+        Ok(Value::from(scalar))
+    }
+}
+
+impl EncodeScalar for Value {
+    fn encode(&self, _: &mut Context) -> Result<ast::Scalar, EncodeError> {
+        ast::Scalar::try_from(self)
+    }
+}
+
+impl Node {
+    /// Creates a node without arguments, properties and children
+    pub fn new(name: &str) -> Self {
+        Node {
+            type_name: None,
+            name: name.into(),
+            arguments: Vec::new(),
+            properties: Vec::new(),
+            children: None,
+        }
+    }
+    /// Returns the argument at `index`
+    pub fn arg(&self, index: usize) -> Option<&Value> {
+        self.arguments.get(index)
+    }
+    /// Returns the property `name`
+    pub fn prop(&self, name: &str) -> Option<&Value> {
+        self.properties.iter()
+            .find(|(key, _)| key.as_ref() == name)
+            .map(|(_, value)| value)
+    }
+    /// Returns the property `name` for modification
+    pub fn prop_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.properties.iter_mut()
+            .find(|(key, _)| key.as_ref() == name)
+            .map(|(_, value)| value)
+    }
+    /// Converts the argument at `index`, a missing argument is an error
+    pub fn get_arg<T: DecodeScalar>(&self, index: usize)
+        -> Result<T, DecodeError>
+    {
+        match self.arg(index) {
+            Some(value) => value.get(),
+            None => Err(DecodeError::missing(Span(0, 0),
+                format!("argument {} of node `{}` is missing",
+                        index, self.name.escape_default()))),
+        }
+    }
+    /// Converts the property `name`, a missing property is an error
+    pub fn get_prop<T: DecodeScalar>(&self, name: &str)
+        -> Result<T, DecodeError>
+    {
+        match self.prop(name) {
+            Some(value) => value.get(),
+            None => Err(DecodeError::missing(Span(0, 0),
+                format!("property `{}` of node `{}` is missing",
+                        name.escape_default(), self.name.escape_default()))),
+        }
+    }
+    /// Appends an argument
+    pub fn push_arg(&mut self, value: impl Into<Value>) {
+        self.arguments.push(value.into());
+    }
+    /// Sets the property, keeping its position if it already exists
+    pub fn set_prop(&mut self, name: &str, value: impl Into<Value>) {
+        let value = value.into();
+        match self.prop_mut(name) {
+            Some(existing) => *existing = value,
+            None => self.properties.push((name.into(), value)),
+        }
+    }
+    /// Removes the property and returns its value
+    pub fn remove_prop(&mut self, name: &str) -> Option<Value> {
+        let index = self.properties.iter()
+            .position(|(key, _)| key.as_ref() == name)?;
+        Some(self.properties.remove(index).1)
+    }
+    /// Returns node children
+    pub fn children(&self) -> impl Iterator<Item = &Node> + ExactSizeIterator {
+        self.children.as_deref().unwrap_or(&[]).iter()
+    }
+    /// Appends a child, adding braces if there were none
+    pub fn push_child(&mut self, child: Node) {
+        self.children.get_or_insert_with(Vec::new).push(child);
+    }
+    /// Returns the first child named `name`
+    pub fn child(&self, name: &str) -> Option<&Node> {
+        self.children().find(|child| child.name.as_ref() == name)
+    }
+    /// Returns the first child named `name` for modification
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut Node> {
+        self.children.as_mut()?.iter_mut()
+            .find(|child| child.name.as_ref() == name)
+    }
+    /// Follows a `/` separated path of child names, e.g. `"server/tls"`
+    pub fn at(&self, path: &str) -> Option<&Node> {
+        path.split('/').filter(|name| !name.is_empty())
+            .try_fold(self, |node, name| node.child(name))
+    }
+    /// Follows a `/` separated path of child names for modification
+    pub fn at_mut(&mut self, path: &str) -> Option<&mut Node> {
+        path.split('/').filter(|name| !name.is_empty())
+            .try_fold(self, |node, name| node.child_mut(name))
+    }
+    /// Returns the property named by the last segment of the path in the
+    /// descendant named by the rest, e.g. `"tls/enabled"`
+    pub fn prop_at(&self, path: &str) -> Option<&Value> {
+        match path.rsplit_once('/') {
+            Some((nodes, name)) => self.at(nodes)?.prop(name),
+            None => self.prop(path),
+        }
+    }
+}
+
+impl Document {
+    /// Returns the first top-level node named `name`
+    pub fn get(&self, name: &str) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.name.as_ref() == name)
+    }
+    /// Returns the first top-level node named `name` for modification
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Node> {
+        self.nodes.iter_mut().find(|node| node.name.as_ref() == name)
+    }
+    /// Follows a `/` separated path of node names, e.g. `"server/tls"`
+    pub fn at(&self, path: &str) -> Option<&Node> {
+        let path = path.trim_start_matches('/');
+        let (first, rest) = path.split_once('/').unwrap_or((path, ""));
+        self.get(first)?.at(rest)
+    }
+    /// Follows a `/` separated path of node names for modification
+    pub fn at_mut(&mut self, path: &str) -> Option<&mut Node> {
+        let path = path.trim_start_matches('/');
+        let (first, rest) = path.split_once('/').unwrap_or((path, ""));
+        self.get_mut(first)?.at_mut(rest)
+    }
+    /// Returns the property named by the last segment of the path in the
+    /// node named by the rest, e.g. `"server/tls/enabled"`
+    pub fn prop_at(&self, path: &str) -> Option<&Value> {
+        let (nodes, name) = path.rsplit_once('/')?;
+        self.at(nodes)?.prop(name)
+    }
+}
+
+impl Index<usize> for Node {
+    type Output = Value;
+    /// Returns the argument, panics if there is none
+    fn index(&self, index: usize) -> &Value {
+        self.arg(index).unwrap_or_else(|| {
+            panic!("node `{}` has no argument {}", self.name, index)
+        })
+    }
+}
+
+impl Index<&str> for Node {
+    type Output = Value;
+    /// Returns the property, panics if there is none
+    fn index(&self, name: &str) -> &Value {
+        self.prop(name).unwrap_or_else(|| {
+            panic!("node `{}` has no property `{}`", self.name, name)
+        })
+    }
+}
+
+impl Index<&str> for Document {
+    type Output = Node;
+    /// Returns the node at the path, panics if there is none
+    fn index(&self, path: &str) -> &Node {
+        self.at(path).unwrap_or_else(|| panic!("no node at `{}`", path))
+    }
+}
+
+impl From<&ast::Node> for Node {
+    fn from(node: &ast::Node) -> Self {
+        Node {
+            type_name: node.type_name.clone(),
+            name: node.node_name.clone(),
+            arguments: node.arguments.iter().map(Value::from).collect(),
+            properties: node.properties.iter()
+                .map(|(name, value)| (name.clone(), Value::from(value)))
+                .collect(),
+            children: node.children.as_ref()
+                .map(|children| children.iter().map(Node::from).collect()),
+        }
+    }
+}
+
+/// Fails if any value is NaN or infinity, which KDL can't represent
+impl TryFrom<&Node> for ast::Node {
+    type Error = EncodeError;
+    fn try_from(node: &Node) -> Result<Self, EncodeError> {
+        Ok(ast::Node {
+            type_name: node.type_name.clone(),
+            node_name: node.name.clone(),
+            arguments: node.arguments.iter().map(ast::Scalar::try_from)
+                .collect::<Result<_, _>>()?,
+            properties: node.properties.iter()
+                .map(|(name, value)| {
+                    Ok((name.clone(), ast::Scalar::try_from(value)?))
+                })
+                .collect::<Result<_, EncodeError>>()?,
+            children: node.children.as_ref()
+                .map(|children| {
+                    children.iter().map(ast::Node::try_from).collect()
+                })
+                .transpose()?,
+        })
+    }
+}
+
+impl Decode for Node {
+    fn decode(node: &ast::Node, _: &mut Context) -> Result<Self, DecodeError> {
+        Ok(Node::from(node))
+    }
+}
+
+impl Encode for Node {
+    fn encode(&self, _: &mut Context) -> Result<ast::Node, EncodeError> {
+        ast::Node::try_from(self)
+    }
+}
+
+impl DecodePartial for Document {
+    fn decode_partial(&mut self, node: &ast::Node, _: &mut Context)
+        -> Result<bool, DecodeError>
+    {
+        self.nodes.push(Node::from(node));
+        Ok(true)
+    }
+}
+
+impl EncodePartial for Document {
+    fn encode_partial(&self, node: &mut ast::Node, _: &mut Context)
+        -> Result<(), EncodeError>
+    {
+        let nodes = self.nodes.iter().map(ast::Node::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        node.children.get_or_insert_with(Vec::new).extend(nodes);
+        Ok(())
+    }
+}
+
+/// Prints the node as KDL text, failing if any value is NaN or infinity
+impl Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let node = ast::Node::try_from(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", node)
+    }
+}
+
+/// Prints the top-level nodes as KDL text, one per line
+impl Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for node in self.nodes.iter() {
+            writeln!(f, "{}", node)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<Node>> for Document {
+    fn from(nodes: Vec<Node>) -> Self {
+        Document { nodes }
+    }
+}
+
+impl From<Document> for Vec<Node> {
+    fn from(document: Document) -> Self {
+        document.nodes
+    }
+}
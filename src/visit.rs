@@ -0,0 +1,295 @@
+//! Traversal of the AST
+//!
+//! [`Visit`] walks nodes by reference, [`VisitMut`] modifies them in place
+//! and can remove or replace nodes, and [`Fold`] rebuilds the tree from
+//! owned nodes. Every method has a default implementation which recurses into
+//! arguments, properties and children, so an implementation only overrides
+//! the steps it is interested in. Each step receives the [`Path`] of the
+//! node being visited:
+//!
+//! ```rust
+//! use kfl::ast::Node;
+//! use kfl::visit::{Path, Visit};
+//!
+//! #[derive(Default)]
+//! struct Names(Vec<String>);
+//!
+//! impl Visit for Names {
+//!     fn visit_node(&mut self, node: &Node, path: &Path) {
+//!         self.0.push(path.to_string());
+//!         kfl::visit::visit_node(self, node, path);
+//!     }
+//! }
+//!
+//! let nodes = kfl::kdl! {
+//!     server { listen 80; listen 443 }
+//! };
+//! let mut names = Names::default();
+//! names.visit_nodes(&nodes, &Path::root());
+//! assert_eq!(names.0, ["server", "server/listen", "server/listen"]);
+//! ```
+//!
+//! The free functions of this module are the default implementations, call
+//! them from an overridden method to keep recursing.
+
+use alloc::{
+    boxed::Box,
+    vec::Vec
+};
+use core::fmt::{self, Display};
+
+use crate::ast::{Node, Property, Scalar};
+
+/// Position of a node in the document
+///
+/// Made of the names of the node and its ancestors along with their index
+/// among siblings. Scalars are reported with the path of their node.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path {
+    segments: Vec<(usize, Box<str>)>,
+}
+
+/// What [`VisitMut::visit_node_mut`] does with the visited node
+#[derive(Debug, Clone, Default)]
+pub enum Change {
+    /// Keeps the node, including any modifications made to it
+    #[default]
+    Keep,
+    /// Removes the node
+    Remove,
+    /// Puts the nodes in place of the visited one, they are not visited
+    Replace(Vec<Node>),
+}
+
+/// Read-only traversal of nodes and scalars
+pub trait Visit {
+    /// Visits a list of sibling nodes, `path` is the path of their parent
+    fn visit_nodes(&mut self, nodes: &[Node], path: &Path) {
+        visit_nodes(self, nodes, path)
+    }
+    /// Visits a node and, by default, its arguments, properties and children
+    fn visit_node(&mut self, node: &Node, path: &Path) {
+        visit_node(self, node, path)
+    }
+    /// Visits the argument at `index`
+    fn visit_argument(&mut self, index: usize, scalar: &Scalar, path: &Path) {
+        let _ = index;
+        self.visit_scalar(scalar, path)
+    }
+    /// Visits a property, duplicates are visited as written
+    fn visit_property(&mut self, name: &str, scalar: &Scalar, path: &Path) {
+        let _ = name;
+        self.visit_scalar(scalar, path)
+    }
+    /// Visits an argument or a property value
+    fn visit_scalar(&mut self, scalar: &Scalar, path: &Path) {
+        let _ = (scalar, path);
+    }
+}
+
+/// Traversal that modifies nodes and scalars in place
+pub trait VisitMut {
+    /// Visits a list of sibling nodes, removing or replacing them as
+    /// [`visit_node_mut`](VisitMut::visit_node_mut) says
+    fn visit_nodes_mut(&mut self, nodes: &mut Vec<Node>, path: &Path) {
+        visit_nodes_mut(self, nodes, path)
+    }
+    /// Visits a node and, by default, its arguments, properties and children
+    fn visit_node_mut(&mut self, node: &mut Node, path: &Path) -> Change {
+        visit_node_mut(self, node, path);
+        Change::Keep
+    }
+    /// Visits the argument at `index`
+    fn visit_argument_mut(&mut self, index: usize, scalar: &mut Scalar,
+                          path: &Path)
+    {
+        let _ = index;
+        self.visit_scalar_mut(scalar, path)
+    }
+    /// Visits a property, duplicates are visited as written
+    fn visit_property_mut(&mut self, name: &str, scalar: &mut Scalar,
+                          path: &Path)
+    {
+        let _ = name;
+        self.visit_scalar_mut(scalar, path)
+    }
+    /// Visits an argument or a property value
+    fn visit_scalar_mut(&mut self, scalar: &mut Scalar, path: &Path) {
+        let _ = (scalar, path);
+    }
+}
+
+/// Traversal that rebuilds the tree from owned nodes and scalars
+pub trait Fold {
+    /// Folds a list of sibling nodes, `path` is the path of their parent
+    fn fold_nodes(&mut self, nodes: Vec<Node>, path: &Path) -> Vec<Node> {
+        fold_nodes(self, nodes, path)
+    }
+    /// Folds a node, returning `None` removes it
+    fn fold_node(&mut self, node: Node, path: &Path) -> Option<Node> {
+        Some(fold_node(self, node, path))
+    }
+    /// Folds the argument at `index`
+    fn fold_argument(&mut self, index: usize, scalar: Scalar, path: &Path)
+        -> Scalar
+    {
+        let _ = index;
+        self.fold_scalar(scalar, path)
+    }
+    /// Folds a property, duplicates are folded as written
+    fn fold_property(&mut self, name: &str, scalar: Scalar, path: &Path)
+        -> Scalar
+    {
+        let _ = name;
+        self.fold_scalar(scalar, path)
+    }
+    /// Folds an argument or a property value
+    fn fold_scalar(&mut self, scalar: Scalar, path: &Path) -> Scalar {
+        let _ = path;
+        scalar
+    }
+}
+
+impl Path {
+    /// Path of the document itself, the parent of top-level nodes
+    pub fn root() -> Self {
+        Self::default()
+    }
+    /// Path of the child at `index` named `name`
+    pub fn child(&self, index: usize, name: &str) -> Path {
+        let mut segments = self.segments.clone();
+        segments.push((index, name.into()));
+        Path { segments }
+    }
+    /// Number of ancestors of the node, `0` for top-level nodes
+    pub fn depth(&self) -> usize {
+        self.segments.len().saturating_sub(1)
+    }
+    /// Returns `true` for the path of the document itself
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+    /// Name of the node, `None` for the root
+    pub fn name(&self) -> Option<&str> {
+        self.segments.last().map(|(_, name)| &**name)
+    }
+    /// Index of the node among its siblings, `None` for the root
+    pub fn index(&self) -> Option<usize> {
+        self.segments.last().map(|(index, _)| *index)
+    }
+    /// Path of the parent node, `None` for the root
+    pub fn parent(&self) -> Option<Path> {
+        let (_, ancestors) = self.segments.split_last()?;
+        Some(Path { segments: ancestors.to_vec() })
+    }
+    /// Names from the top-level node down to this one
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.segments.iter().map(|(_, name)| &**name)
+    }
+    /// Indices from the top-level node down to this one
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.segments.iter().map(|(index, _)| *index)
+    }
+}
+
+/// Names separated by `/`, as accepted by
+/// [`value::Document::at`](crate::value::Document::at)
+impl Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, name) in self.names().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            f.write_str(name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Visits each of the nodes with its own path
+pub fn visit_nodes<V>(visitor: &mut V, nodes: &[Node], path: &Path)
+    where V: Visit + ?Sized,
+{
+    for (index, node) in nodes.iter().enumerate() {
+        visitor.visit_node(node, &path.child(index, &node.node_name));
+    }
+}
+
+/// Visits arguments, properties and children of the node
+pub fn visit_node<V>(visitor: &mut V, node: &Node, path: &Path)
+    where V: Visit + ?Sized,
+{
+    for (index, scalar) in node.arguments.iter().enumerate() {
+        visitor.visit_argument(index, scalar, path);
+    }
+    for property in node.properties.entries() {
+        visitor.visit_property(&property.name, &property.value, path);
+    }
+    if let Some(children) = &node.children {
+        visitor.visit_nodes(children, path);
+    }
+}
+
+/// Visits each of the nodes with its own path and applies the [`Change`]
+/// returned for it
+pub fn visit_nodes_mut<V>(visitor: &mut V, nodes: &mut Vec<Node>, path: &Path)
+    where V: VisitMut + ?Sized,
+{
+    let visited = core::mem::take(nodes);
+    nodes.reserve(visited.len());
+    for (index, mut node) in visited.into_iter().enumerate() {
+        let path = path.child(index, &node.node_name);
+        match visitor.visit_node_mut(&mut node, &path) {
+            Change::Keep => nodes.push(node),
+            Change::Remove => {}
+            Change::Replace(replacement) => nodes.extend(replacement),
+        }
+    }
+}
+
+/// Visits arguments, properties and children of the node
+pub fn visit_node_mut<V>(visitor: &mut V, node: &mut Node, path: &Path)
+    where V: VisitMut + ?Sized,
+{
+    for (index, scalar) in node.arguments.iter_mut().enumerate() {
+        visitor.visit_argument_mut(index, scalar, path);
+    }
+    for property in node.properties.entries_mut() {
+        visitor.visit_property_mut(&property.name, &mut property.value, path);
+    }
+    if let Some(children) = &mut node.children {
+        visitor.visit_nodes_mut(children, path);
+    }
+}
+
+/// Folds each of the nodes with its own path, dropping removed ones
+pub fn fold_nodes<F>(folder: &mut F, nodes: Vec<Node>, path: &Path)
+    -> Vec<Node>
+    where F: Fold + ?Sized,
+{
+    nodes.into_iter().enumerate()
+        .filter_map(|(index, node)| {
+            let path = path.child(index, &node.node_name);
+            folder.fold_node(node, &path)
+        })
+        .collect()
+}
+
+/// Folds arguments, properties and children of the node
+pub fn fold_node<F>(folder: &mut F, mut node: Node, path: &Path) -> Node
+    where F: Fold + ?Sized,
+{
+    node.arguments = core::mem::take(&mut node.arguments).into_iter()
+        .enumerate()
+        .map(|(index, scalar)| folder.fold_argument(index, scalar, path))
+        .collect();
+    node.properties = core::mem::take(&mut node.properties).into_iter()
+        .map(|property| Property {
+            value: folder.fold_property(&property.name, property.value, path),
+            ..property
+        })
+        .collect();
+    node.children = node.children.take()
+        .map(|children| folder.fold_nodes(children, path));
+    node
+}
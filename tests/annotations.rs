@@ -0,0 +1,66 @@
+mod common;
+
+use kfl::Decode;
+
+#[test]
+fn decode_integer_annotation() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        small: u8,
+        #[kfl(property)]
+        large: i64,
+    }
+    assert_decode!(r#"node (u8)1 large=(i64)-2"#, Node { small: 1, large: -2 });
+    assert_decode!(r#"node 1 large=-2"#, Node { small: 1, large: -2 });
+    assert_decode_error!(Node,
+        r#"node (i64)1 large=2"#,
+        "u8 or no type for u8, found i64");
+    assert_decode_error!(Node,
+        r#"node 1 large=(f32)2"#,
+        "i64 or no type for i64, found f32");
+}
+
+#[test]
+fn decode_float_annotation() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        ratio: f32,
+        #[kfl(argument)]
+        total: f64,
+    }
+    assert_decode!(r#"node (f32)0.5 (decimal)1.5"#,
+                   Node { ratio: 0.5, total: 1.5 });
+    assert_decode_error!(Node,
+        r#"node 0.5 (u8)1"#,
+        "f64, decimal or no type for f64, found u8");
+}
+
+#[test]
+fn decode_bytes_annotation() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        data: Vec<u8>,
+    }
+    assert_decode!(r#"node (base64)"aGVsbG8=""#,
+                   Node { data: b"hello".to_vec() });
+    assert_decode!(r#"node (hex)"68656c6c6f""#,
+                   Node { data: b"hello".to_vec() });
+    assert_decode_error!(Node,
+        r#"node (u8)"68656c6c6f""#,
+        "base64, hex or no type for bytes, found u8");
+}
+
+#[test]
+fn decode_string_annotation() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        name: String,
+    }
+    assert_decode_error!(Node,
+        r#"node (u8)"name""#,
+        "no type for String, found u8");
+}
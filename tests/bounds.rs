@@ -0,0 +1,63 @@
+mod common;
+
+use kfl::Decode;
+
+#[test]
+fn decode_arguments_bounds() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Hosts {
+        #[kfl(arguments(min = 1, max = 3))]
+        names: Vec<String>,
+    }
+    assert_decode!(r#"hosts "a""#, Hosts { names: vec!["a".into()] });
+    assert_decode!(r#"hosts "a" "b" "c""#,
+                   Hosts { names: vec!["a".into(), "b".into(), "c".into()] });
+    assert_decode_error!(Hosts,
+        r#"hosts"#,
+        "expected at least 1 argument, found 0");
+    assert_decode_error!(Hosts,
+        r#"hosts "a" "b" "c" "d""#,
+        "expected at most 3 arguments, found 4");
+}
+
+#[test]
+fn decode_children_bounds() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Server(#[kfl(argument)] String);
+    #[derive(Decode, Debug, PartialEq)]
+    struct Cluster {
+        #[kfl(children(min = 2, max = 3))]
+        servers: Vec<Server>,
+    }
+    assert_decode!(r#"cluster { server "a"; server "b"; }"#,
+                   Cluster { servers: vec![Server("a".into()),
+                                           Server("b".into())] });
+    assert_decode_error!(Cluster,
+        r#"cluster { server "a"; }"#,
+        "expected at least 2 child nodes, found 1");
+    assert_decode_error!(Cluster,
+        r#"cluster { server "a"; server "b"; server "c"; server "d"; }"#,
+        "expected at most 3 child nodes, found 4");
+}
+
+#[test]
+fn decode_optional_argument_before_required() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Copy {
+        #[kfl(argument)]
+        source: String,
+        #[kfl(argument, default)]
+        mode: Option<u32>,
+        #[kfl(argument)]
+        target: String,
+    }
+    assert_decode!(r#"copy "a" "b""#,
+                   Copy { source: "a".into(), mode: None,
+                          target: "b".into() });
+    assert_decode!(r#"copy "a" 644 "b""#,
+                   Copy { source: "a".into(), mode: Some(644),
+                          target: "b".into() });
+    assert_decode_error!(Copy,
+        r#"copy "a""#,
+        "additional argument `target` is required");
+}
@@ -0,0 +1,80 @@
+use kfl::ast::{Node, Scalar};
+use kfl::context::Context;
+use kfl::{kdl, Decode};
+
+#[derive(Decode, Debug, PartialEq)]
+struct Route {
+    #[kfl(argument)]
+    path: String,
+    #[kfl(property)]
+    port: u16,
+    #[kfl(children)]
+    backends: Vec<Backend>,
+}
+
+#[derive(Decode, Debug, PartialEq)]
+struct Backend {
+    #[kfl(argument)]
+    host: String,
+    #[kfl(property, default)]
+    weight: Option<u8>,
+}
+
+#[test]
+fn build_node() {
+    let node = Node::build("route").arg("/api").prop("port", 80)
+        .child(Node::build("backend").arg("a.local").prop("weight", 2u8))
+        .child(Node::build("backend").arg("b.local"));
+    assert_eq!(node.to_string(),
+               "route \"/api\" port=80 {\n  backend \"a.local\" weight=2\n  \
+                backend \"b.local\"\n}");
+    let route = Route::decode(&node, &mut Context::default()).unwrap();
+    assert_eq!(route, Route {
+        path: "/api".into(),
+        port: 80,
+        backends: vec![
+            Backend { host: "a.local".into(), weight: Some(2) },
+            Backend { host: "b.local".into(), weight: None },
+        ],
+    });
+}
+
+#[test]
+fn mutate_node() {
+    let mut node = Node::build("node").arg(1).arg(3).prop("a", "x");
+    node.insert_arg(1, 2);
+    assert_eq!(node.replace_arg(2, Scalar::bare("4").with_type("u8")),
+               Scalar::bare("3"));
+    assert_eq!(node.remove_arg(0), Scalar::bare("1"));
+    assert_eq!(node.insert_prop("b", true), None);
+    assert_eq!(node.replace_prop("a", "y"), Some(Scalar::string("x")));
+    assert_eq!(node.replace_prop("c", "z"), None);
+    assert_eq!(node.remove_prop("b"), Some(Scalar::bare("true")));
+    assert_eq!(node.property("a"), Some(&Scalar::string("y")));
+    node.push_child(Node::new("b"));
+    node.insert_child(0, Node::new("a"));
+    assert_eq!(node.replace_child(1, Node::new("c")).node_name.as_ref(), "b");
+    assert_eq!(node.remove_child(0).node_name.as_ref(), "a");
+    assert_eq!(node.to_string(), "node 2 (u8)4 a=\"y\" {\n  c\n}");
+}
+
+#[test]
+fn kdl_macro() {
+    let nodes = kdl! {
+        route "/api" port=80 {
+            backend "a.local" weight=2
+            /- backend "skipped"
+            backend "b.local"
+        }
+        (server)max-size (u32)1_024 -5 0x1F 1.5 true null enabled=false;
+        empty {}
+    };
+    assert_eq!(nodes.len(), 3);
+    let route = Route::decode(&nodes[0], &mut Context::default()).unwrap();
+    assert_eq!(route.backends.len(), 2);
+    assert_eq!(route.backends[1].host, "b.local");
+    assert_eq!(nodes[1].to_string(),
+               "(server)max-size (u32)1_024 -5 0x1F 1.5 true null enabled=false");
+    assert_eq!(nodes[1].type_name.as_deref(), Some("server"));
+    assert_eq!(nodes[2].to_string(), "empty {\n}");
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use kfl::Decode;
+
+#[test]
+fn decode_bytes() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        data: Vec<u8>,
+        #[kfl(property)]
+        boxed: Box<[u8]>,
+        #[kfl(property)]
+        array: [u8; 2],
+    }
+    assert_decode!(r#"node (base64)"aGk=" boxed=(hex)"6869" array="hi""#,
+                   Node { data: b"hi".to_vec(), boxed: b"hi"[..].into(),
+                          array: *b"hi" });
+    assert_decode_error!(Node,
+        r#"node "" boxed=(hex)"686" array="hi""#,
+        "odd number of hex digits");
+    assert_decode_error!(Node,
+        r#"node "" boxed=(hex)"zz" array="hi""#,
+        "invalid hex digit");
+    assert_decode_error!(Node,
+        r#"node "" boxed="" array=(hex)"686970""#,
+        "unexpected number of bytes: 3");
+}
+
+#[test]
+fn decode_bytes_with() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Key {
+        #[kfl(argument, with = kfl::bytes::hex)]
+        fingerprint: [u8; 4],
+    }
+    assert_decode!(r#"key "deadbeef""#,
+                   Key { fingerprint: [0xde, 0xad, 0xbe, 0xef] });
+    assert_decode!(r#"key (hex)"DEADBEEF""#,
+                   Key { fingerprint: [0xde, 0xad, 0xbe, 0xef] });
+    assert_decode_error!(Key,
+        r#"key (base64)"3q2+7w==""#,
+        "hex or no type for bytes, found base64");
+}
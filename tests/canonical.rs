@@ -0,0 +1,93 @@
+mod common;
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use kfl::canonical;
+use kfl::{Decode, Encode, Value};
+
+use common::parse;
+
+#[test]
+fn equality() {
+    assert_eq!(parse("node 0x10 1.50 \"a\""), parse("node 16 1.5 a"));
+    assert_eq!(parse("node b=1 a=2 b=3"), parse("node a=2 b=3"));
+    assert_eq!(parse("(t)node (u8)1 { child; }"),
+               parse("/* comment */ (t)node (u8)0b1 {\n    child\n}"));
+
+    assert_ne!(parse("node 16"), parse("node 16.0"));
+    assert_ne!(parse("node 1 2"), parse("node 2 1"));
+    assert_ne!(parse("node true"), parse("node \"true\""));
+    assert_ne!(parse("node (u8)1"), parse("node 1"));
+    assert_ne!(parse("node a=1"), parse("node a=1 b=2"));
+    assert_ne!(parse("node { a; b; }"), parse("node { b; a; }"));
+    assert_ne!(parse("node {}"), parse("node"));
+
+    let (hex, decimal) = (parse("node 0x10"), parse("node 16"));
+    let string = parse(r#"node "16""#);
+    assert_eq!(hex, decimal);
+    assert_ne!(hex[0].arguments, decimal[0].arguments);
+    assert!(hex[0].arguments[0].same_value(&decimal[0].arguments[0]));
+    assert!(!hex[0].arguments[0].same_value(&string[0].arguments[0]));
+}
+
+#[test]
+fn hashing() {
+    let nodes = [
+        parse("node 0x10 b=\"x\" a=2"),
+        parse("node 16 a=2 b=x"),
+        parse("node 16 a=2 b=x { child; }"),
+    ];
+    let set = nodes.iter().flatten().collect::<HashSet<_>>();
+    assert_eq!(set.len(), 2);
+
+    let hashes = nodes.iter().map(|n| canonical::content_hash(n))
+        .collect::<Vec<_>>();
+    assert_eq!(hashes[0], hashes[1]);
+    assert_ne!(hashes[1], hashes[2]);
+    assert_ne!(canonical::content_hash(&parse("a\nb")),
+               canonical::content_hash(&parse("b\na")));
+}
+
+#[test]
+fn canonical_form() {
+    let mut nodes = parse(r#"
+        server 0x1F "main" port=0o17 debug=true port=1_000 name=main {
+            route ratio=2.50 path="/api"
+        }
+    "#);
+    let original = nodes.clone();
+    canonical::canonicalize(&mut nodes);
+    assert_eq!(nodes, original);
+    assert_eq!(nodes[0].to_string(), "server 31 \"main\" debug=true \
+        name=\"main\" port=1000 {\n  route path=\"/api\" ratio=2.5\n}");
+    assert_eq!(nodes[0].properties.entries().len(), 3);
+    assert!(nodes[0].properties.duplicates().next().is_none());
+}
+
+#[derive(Decode, Encode, Debug, PartialEq)]
+struct Server {
+    #[kfl(argument)]
+    name: String,
+    #[kfl(property)]
+    timeout: Duration,
+    #[kfl(property)]
+    separator: char,
+}
+
+#[test]
+fn encoded_nodes() {
+    let server = Server {
+        name: "main".into(),
+        timeout: Duration::from_secs(90),
+        separator: ',',
+    };
+    let node = Encode::encode(&server, &mut Context::default()).unwrap();
+    let text = node.to_string();
+    assert_eq!(text, r#"server "main" timeout="1m30s" separator=",""#);
+    assert_eq!(Value::from(&node.arguments[0]), Value::from("main"));
+    assert_eq!(canonical::content_hash(&[node.clone()]),
+               canonical::content_hash(&parse(&text)));
+    assert_eq!(vec![node], parse(&text));
+    assert_eq!(kfl::decode::<Server>("<test>", &text).unwrap(), server);
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use kfl::Decode;
+use kfl::ast::{Node, Scalar};
+use kfl::context::Context;
+use kfl::errors::DecodeError;
+use kfl::traits::DecodeScalar;
+
+/// Stands for a type from another crate
+#[derive(Debug, PartialEq)]
+pub struct Version {
+    major: u32,
+    minor: u32,
+}
+
+mod version {
+    use super::*;
+
+    pub fn decode(scalar: &Scalar, ctx: &mut Context)
+        -> Result<Version, DecodeError>
+    {
+        let text = <String as DecodeScalar>::decode(scalar, ctx)?;
+        let (major, minor) = text.split_once('.')
+            .ok_or_else(|| DecodeError::conversion(ctx.span(&scalar),
+                                                   "expected `major.minor`"))?;
+        Ok(Version {
+            major: major.parse()
+                .map_err(|e| DecodeError::conversion(ctx.span(&scalar), e))?,
+            minor: minor.parse()
+                .map_err(|e| DecodeError::conversion(ctx.span(&scalar), e))?,
+        })
+    }
+}
+
+fn decode_upper(scalar: &Scalar, ctx: &mut Context)
+    -> Result<String, DecodeError>
+{
+    <String as DecodeScalar>::decode(scalar, ctx).map(|s| s.to_uppercase())
+}
+
+fn decode_requires(node: &Node, ctx: &mut Context)
+    -> Result<Version, DecodeError>
+{
+    if node.node_name.as_ref() != "requires" {
+        return Err(DecodeError::unexpected(ctx.span(&node), "node",
+                                           "expected `requires`"));
+    }
+    match &node.arguments[..] {
+        [scalar] => version::decode(scalar, ctx),
+        _ => Err(DecodeError::missing(ctx.span(&node),
+                                      "single argument is required")),
+    }
+}
+
+#[test]
+fn decode_argument_with() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Package {
+        #[kfl(argument)]
+        name: String,
+        #[kfl(argument, with = version)]
+        version: Version,
+    }
+    assert_decode!(r#"package "kfl" "0.17""#,
+                   Package { name: "kfl".into(),
+                             version: Version { major: 0, minor: 17 } });
+    assert_decode_error!(Package,
+        r#"package "kfl" "17""#,
+        "expected `major.minor`");
+}
+
+#[test]
+fn decode_property_with() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Package {
+        #[kfl(property, default, decode_with = version::decode)]
+        version: Option<Version>,
+        #[kfl(property, decode_with = decode_upper)]
+        license: String,
+    }
+    assert_decode!(r#"package version="1.2" license="mit""#,
+                   Package { version: Some(Version { major: 1, minor: 2 }),
+                             license: "MIT".into() });
+    assert_decode!(r#"package version=null license="mit""#,
+                   Package { version: None, license: "MIT".into() });
+    assert_decode!(r#"package license="mit""#,
+                   Package { version: None, license: "MIT".into() });
+}
+
+#[test]
+fn decode_arguments_with() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Supports {
+        #[kfl(arguments, decode_with = version::decode)]
+        versions: Vec<Version>,
+    }
+    assert_decode!(r#"supports "1.0" "1.1""#,
+                   Supports { versions: vec![
+                       Version { major: 1, minor: 0 },
+                       Version { major: 1, minor: 1 },
+                   ] });
+}
+
+#[test]
+fn decode_child_with() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Package {
+        #[kfl(child, default, decode_with = decode_requires)]
+        requires: Option<Version>,
+    }
+    assert_decode!(r#"package { requires "1.62"; }"#,
+                   Package { requires: Some(Version { major: 1, minor: 62 }) });
+    assert_decode!(r#"package"#, Package { requires: None });
+    assert_decode_error!(Package,
+        r#"package { requires "1.62"; requires "1.65"; }"#,
+        "duplicate node `requires`, single node expected");
+}
+
+#[test]
+fn decode_children_with() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Package {
+        #[kfl(children, decode_with = decode_requires)]
+        requires: Vec<Version>,
+    }
+    assert_decode!(r#"package { requires "1.62"; requires "1.65"; }"#,
+                   Package { requires: vec![
+                       Version { major: 1, minor: 62 },
+                       Version { major: 1, minor: 65 },
+                   ] });
+}
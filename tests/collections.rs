@@ -0,0 +1,101 @@
+mod common;
+
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use kfl::Decode;
+
+#[derive(Decode, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Tag(#[kfl(argument)] String);
+
+#[test]
+fn decode_set_children() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Post {
+        #[kfl(children)]
+        tags: BTreeSet<Tag>,
+    }
+    assert_decode!(
+        r#"post { tag "b"; tag "a"; tag "b"; }"#,
+        Post { tags: BTreeSet::from([Tag("a".into()), Tag("b".into())]) });
+}
+
+#[test]
+fn decode_hash_set_children() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Post {
+        #[kfl(children)]
+        tags: HashSet<Tag>,
+    }
+    assert_decode!(
+        r#"post { tag "a"; tag "b"; }"#,
+        Post { tags: HashSet::from([Tag("a".into()), Tag("b".into())]) });
+}
+
+#[test]
+fn decode_deque_and_boxed_slice_children() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Post {
+        #[kfl(children)]
+        tags: VecDeque<Tag>,
+    }
+    #[derive(Decode, Debug, PartialEq)]
+    struct Page {
+        #[kfl(children)]
+        tags: Box<[Tag]>,
+    }
+    assert_decode!(
+        r#"post { tag "b"; tag "a"; }"#,
+        Post { tags: VecDeque::from([Tag("b".into()), Tag("a".into())]) });
+    assert_decode!(
+        r#"page { tag "b"; tag "a"; }"#,
+        Page { tags: Box::from([Tag("b".into()), Tag("a".into())]) });
+}
+
+#[test]
+fn decode_tuple_arguments() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Range {
+        #[kfl(arguments)]
+        bounds: (u32, u32),
+    }
+    assert_decode!(r#"range 1 10"#, Range { bounds: (1, 10) });
+    assert_decode_error!(Range,
+        r#"range 1 10 20"#,
+        "expected 2 arguments, found 3");
+    assert_decode_error!(Range,
+        r#"range 1"#,
+        "expected 2 arguments, found 1");
+}
+
+#[test]
+fn decode_array_arguments() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Color {
+        #[kfl(argument)]
+        name: String,
+        #[kfl(arguments)]
+        rgb: [u8; 3],
+    }
+    assert_decode!(r#"color "red" 255 0 0"#,
+                   Color { name: "red".into(), rgb: [255, 0, 0] });
+    assert_decode_error!(Color,
+        r#"color "red" 255 0"#,
+        "expected 3 arguments, found 2");
+}
+
+#[test]
+fn decode_tuple_node() {
+    let range: (u32, u32) = kfl::decode("<test>", r#"- 1 10"#).unwrap();
+    assert_eq!(range, (1, 10));
+    assert_decode_error!((u32, u32),
+        r#"- 1 10 20"#,
+        "expected 2 arguments, found 3");
+    assert_decode_error!([u32; 2],
+        r#"- 1 10 x=1"#,
+        "unexpected property `x`");
+    assert_decode_error!((u32, u32),
+        r#"range 1 10"#,
+        "unexpected node `range`");
+    assert_decode_error!([u32; 2],
+        r#"(range)- 1 10"#,
+        "no type name expected for this node");
+}
@@ -1,6 +1,11 @@
 #[allow(dead_code)]
 pub fn hint_same_type<T>(_lhs: &T, _rhs: &T) {}
 
+#[allow(dead_code)]
+pub fn parse(text: &str) -> Vec<kfl::ast::Node> {
+    kfl::parse(&mut kfl::context::Context::default(), text).unwrap()
+}
+
 #[macro_export]
 macro_rules! assert_decode {
     ($input:literal, $output:expr) => {
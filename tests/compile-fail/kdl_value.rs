@@ -0,0 +1,5 @@
+use kfl::kdl;
+
+fn main() {
+    let _nodes = kdl! { node 'c'; };
+}
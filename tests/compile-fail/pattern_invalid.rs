@@ -0,0 +1,9 @@
+use kfl::Decode;
+
+#[derive(Decode)]
+struct Name {
+    #[kfl(argument, pattern = "[a-z")]
+    value: String,
+}
+
+fn main() {}
@@ -0,0 +1,117 @@
+mod common;
+
+#[cfg(feature = "chrono")]
+#[test]
+fn decode_chrono() {
+    use chrono::{
+        DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime,
+        TimeZone, Utc,
+    };
+    use kfl::Decode;
+
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        date: NaiveDate,
+        #[kfl(argument)]
+        time: NaiveTime,
+        #[kfl(property)]
+        local: NaiveDateTime,
+        #[kfl(property)]
+        utc: DateTime<Utc>,
+        #[kfl(property)]
+        offset: DateTime<FixedOffset>,
+        #[kfl(property)]
+        timeout: Duration,
+    }
+    let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    let time = NaiveTime::from_hms_opt(9, 30, 0).unwrap();
+    let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+    assert_decode!(
+        r#"node (date)"2024-03-01" (time)"09:30:00"
+                local=(date-time)"2024-03-01T09:30:00"
+                utc="2024-03-01T11:30:00+02:00"
+                offset="2024-03-01T09:30:00+02:00"
+                timeout=(duration)"1h30m""#,
+        Node { date, time, local: date.and_time(time),
+               utc: Utc.from_utc_datetime(&date.and_time(time)),
+               offset: offset.from_local_datetime(&date.and_time(time))
+                   .unwrap(),
+               timeout: Duration::minutes(90) });
+    assert_decode_error!(Node,
+        r#"node (date-time)"2024-03-01" "09:30:00" local="2024-03-01T09:30:00"
+                utc="2024-03-01T09:30:00Z" offset="2024-03-01T09:30:00Z"
+                timeout="1s""#,
+        "date or no type for NaiveDate, found date-time");
+    assert_decode_error!(Node,
+        r#"node "2024-03-01" "09:30:00" local="2024-03-01T09:30:00"
+                utc="2024-03-01T09:30:00" offset="2024-03-01T09:30:00Z"
+                timeout="1s""#,
+        "premature end of input");
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn decode_time() {
+    use kfl::Decode;
+    use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time,
+               UtcOffset};
+
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        date: Date,
+        #[kfl(argument)]
+        time: Time,
+        #[kfl(property)]
+        local: PrimitiveDateTime,
+        #[kfl(property)]
+        offset: OffsetDateTime,
+        #[kfl(property)]
+        timeout: Duration,
+    }
+    let date = Date::from_calendar_date(2024, Month::March, 1).unwrap();
+    let time = Time::from_hms_milli(9, 30, 0, 250).unwrap();
+    let offset = UtcOffset::from_hms(2, 0, 0).unwrap();
+    assert_decode!(
+        r#"node (date)"2024-03-01" "09:30:00.25"
+                local=(date-time)"2024-03-01T09:30:00.25"
+                offset="2024-03-01T09:30:00.25+02:00"
+                timeout=(s)90"#,
+        Node { date, time, local: PrimitiveDateTime::new(date, time),
+               offset: PrimitiveDateTime::new(date, time).assume_offset(offset),
+               timeout: Duration::seconds(90) });
+    assert_decode_error!(Node,
+        r#"node (time)"2024-03-01" "09:30:00" local="2024-03-01T09:30:00"
+                offset="2024-03-01T09:30:00Z" timeout="1s""#,
+        "date or no type for Date, found time");
+}
+
+#[cfg(feature = "jiff")]
+#[test]
+fn decode_jiff() {
+    use jiff::{SignedDuration, Timestamp, civil::{Date, DateTime, Time}};
+    use kfl::Decode;
+
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        date: Date,
+        #[kfl(argument)]
+        time: Time,
+        #[kfl(property)]
+        local: DateTime,
+        #[kfl(property)]
+        at: Timestamp,
+        #[kfl(property)]
+        timeout: SignedDuration,
+    }
+    let date = Date::constant(2024, 3, 1);
+    let time = Time::constant(9, 30, 0, 0);
+    assert_decode!(
+        r#"node (date)"2024-03-01" "09:30:00" local="2024-03-01T09:30:00"
+                at=(date-time)"2024-03-01T09:30:00Z" timeout="PT1H30M""#,
+        Node { date, time, local: date.to_datetime(time),
+               at: Timestamp::from_second(1_709_285_400).unwrap(),
+               timeout: SignedDuration::from_mins(90) });
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use kfl::diff::{self, Change};
+
+use common::parse;
+
+#[test]
+fn ignores_formatting() {
+    let old = parse(r#"server "main" port=0x50 host="a" { tls; }"#);
+    let new = parse("server \"main\" host=a port=80 {\n    tls\n}");
+    assert!(diff::diff(&old, &new).is_empty());
+}
+
+#[test]
+fn report() {
+    let old = parse(r#"
+        server "main" port=80 debug=true {
+            listen 80 "a" "b"
+            route "/api" { plugin "auth"; }
+            route "/old"
+            plugin "gzip"
+        }
+        (v1)log level="info"
+    "#);
+    let new = parse(r#"
+        (v2)log level="debug"
+        server "main" port=8080 tls=true {
+            plugin "gzip"
+            listen 80 "c"
+            route "/api" { plugin "auth"; plugin "cache"; }
+        }
+    "#);
+    let diff = diff::diff(&old, &new);
+    assert_eq!(diff.to_string(), r#"> log moved to the start
+~ log type: (v1) -> (v2)
+~ log property level: "info" -> "debug"
+~ server "main" property port: 80 -> 8080
+~ server "main" property debug: true -> missing
+~ server "main" property tls: missing -> true
+- server "main" > route "/old"
+> server "main" > plugin "gzip" moved to the start
+~ server "main" > listen 80 argument 1: "a" -> "c"
+~ server "main" > listen 80 argument 2: "b" -> missing
++ server "main" > route "/api" > plugin "cache" after plugin "auth"
+    plugin "cache"
+"#);
+    assert!(matches!(&diff.changes[6], Change::NodeRemoved { path }
+                     if path.to_string() == r#"server "main" > route "/old""#));
+
+    let mut patched = old.clone();
+    diff.apply(&mut patched).unwrap();
+    assert!(diff::diff(&patched, &new).is_empty());
+    assert_eq!(patched[0].to_string(), r#"(v2)log level="debug""#);
+}
+
+#[test]
+fn repeated_nodes() {
+    let old = parse("item\nitem\nitem 1\nitem");
+    let new = parse("item 1\nitem\nitem 2");
+    let diff = diff::diff(&old, &new);
+    assert_eq!(diff.to_string(), r#"- item[2]
+- item[1]
+> item 1 moved to the start
++ item 2 after item
+    item 2
+"#);
+    let mut patched = old.clone();
+    diff.apply(&mut patched).unwrap();
+    assert!(diff::diff(&patched, &new).is_empty());
+}
+
+#[test]
+fn apply_to_other_document() {
+    let old = parse("server {\n    route \"/api\" port=80\n}");
+    let new = parse("server {\n    route \"/api\" port=8080\n    route \"/v1\"\n}");
+    let diff = diff::diff(&old, &new);
+
+    let mut other = parse("server {\n    route \"/static\"\n    route \"/api\" port=1\n}");
+    diff.apply(&mut other).unwrap();
+    let text = other.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    assert_eq!(text, [
+        "server {\n  route \"/static\"\n  route \"/api\" port=8080\n  route \"/v1\"\n}",
+    ]);
+
+    let mut missing = parse(r#"server { route "/static"; }"#);
+    let err = diff.apply(&mut missing).unwrap_err();
+    assert_eq!(err.to_string(), r#"node `server > route "/api"` not found"#);
+}
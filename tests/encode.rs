@@ -506,11 +506,11 @@ fn encode_enum_named() {
         #[allow(dead_code)]
         Var3(u32),
     }
-    assert_encode!(Enum::Var0, r#"var0"#);
+    assert_encode!(Enum::Var0, r#"(enum)var0"#);
     assert_encode!(Enum::Var1 { name: "hello".into() },
-                   r#"var1 "hello""#);
+                   r#"(enum)var1 "hello""#);
     assert_encode!(Enum::Var2 { name: "hello".into() },
-                   r#"var2 name="hello""#);
+                   r#"(enum)var2 name="hello""#);
 //     assert_encode_error!(Enum,
 //         r#"something"#,
 //         "expected one of `var0`, `var1`, `var2`");
@@ -528,16 +528,38 @@ fn encode_enum_unnamed() {
         Var3(u32),
     }
     assert_encode!(Enum::Var0,
-                   r#"var0"#);
+                   r#"(enum)var0"#);
     assert_encode!(Enum::Var1("hello".into()),
-                   r#"var1 "hello""#);
+                   r#"(enum)var1 "hello""#);
     assert_encode!(Enum::Var2("hello".into()),
-                   r#"var2 name="hello""#);
+                   r#"(enum)var2 name="hello""#);
     // assert_encode_error!(Enum,
     //     r#"something"#,
     //     "expected one of `var0`, `var1`, `var2`");
 }
 
+#[test]
+fn encode_enum_roundtrip() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    enum Action {
+        Allow(#[kfl(argument)] String),
+        Deny,
+    }
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Rules {
+        #[kfl(children)]
+        actions: Vec<Action>,
+    }
+    let rules = kfl::decode::<Rules>("<test>",
+        r#"rules { allow "a.local"; (action)deny; }"#).unwrap();
+    assert_encode!(rules, r#"rules {
+  (action)allow "a.local"
+  (action)deny
+}"#);
+    let text = kfl::encode("<test>", &rules).unwrap();
+    assert_eq!(kfl::decode::<Rules>("<test>", &text).unwrap(), rules);
+}
+
 // #[test]
 // fn encode_enum() {
 //     #[derive(Decode, Encode, Debug, PartialEq)]
@@ -0,0 +1,41 @@
+mod common;
+
+use kfl::{Decode, DecodePartial, Encode, EncodePartial};
+use kfl::context::TypeAnnotations;
+
+#[test]
+fn encode_without_annotations() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        small: u8,
+        #[kfl(property)]
+        ratio: f64,
+    }
+    assert_encode!(Node { small: 1, ratio: 0.5 }, r#"node 1 ratio=0.5"#);
+}
+
+#[test]
+fn encode_with_annotations() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        small: u8,
+        #[kfl(property)]
+        ratio: f64,
+        #[kfl(property)]
+        name: String,
+    }
+    #[derive(DecodePartial, EncodePartial, Default, Debug, PartialEq)]
+    struct Document {
+        #[kfl(child, default)]
+        node: Option<Node>,
+    }
+    let document = Document {
+        node: Some(Node { small: 1, ratio: 0.5, name: "a".into() }),
+    };
+    let output = kfl::encode_with_context("<test>", &document, |ctx| {
+        ctx.set(TypeAnnotations);
+    }).unwrap();
+    assert_eq!(output, "- {\n  node (u8)1 ratio=(f64)0.5 name=\"a\"\n}");
+}
@@ -0,0 +1,21 @@
+mod common;
+
+use kfl::{Decode, Encode};
+
+#[test]
+fn encode_optional_argument_before_required() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Copy {
+        #[kfl(argument)]
+        source: String,
+        #[kfl(argument, default)]
+        mode: Option<u32>,
+        #[kfl(argument)]
+        target: String,
+    }
+    assert_encode!(Copy { source: "a".into(), mode: None, target: "b".into() },
+                   r#"copy "a" "b""#);
+    assert_encode!(Copy { source: "a".into(), mode: Some(644),
+                          target: "b".into() },
+                   r#"copy "a" 644 "b""#);
+}
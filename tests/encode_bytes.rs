@@ -0,0 +1,35 @@
+mod common;
+
+use kfl::{Decode, Encode};
+
+#[test]
+fn encode_bytes() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        data: Vec<u8>,
+        #[kfl(property)]
+        boxed: Box<[u8]>,
+        #[kfl(property)]
+        array: [u8; 2],
+    }
+    assert_encode!(Node { data: b"hi".to_vec(), boxed: b"hi"[..].into(),
+                          array: *b"hi" },
+                   r#"node (base64)"aGk=" boxed=(base64)"aGk=" array=(base64)"aGk=""#);
+}
+
+#[test]
+fn encode_bytes_with() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Key {
+        #[kfl(argument, with = kfl::bytes::hex)]
+        fingerprint: [u8; 4],
+        #[kfl(property, default, with = kfl::bytes::base64)]
+        salt: Option<Vec<u8>>,
+    }
+    assert_encode!(Key { fingerprint: [0xde, 0xad, 0xbe, 0xef], salt: None },
+                   r#"key (hex)"deadbeef" salt=null"#);
+    assert_encode!(Key { fingerprint: [0, 1, 2, 3],
+                         salt: Some(b"hi".to_vec()) },
+                   r#"key (hex)"00010203" salt=(base64)"aGk=""#);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use kfl::{Decode, Encode};
+use kfl::ast::{Node, Scalar};
+use kfl::context::Context;
+use kfl::errors::{DecodeError, EncodeError};
+use kfl::traits::{DecodeScalar, EncodeScalar};
+
+#[derive(Debug, PartialEq)]
+pub struct Version {
+    major: u32,
+    minor: u32,
+}
+
+mod version {
+    use super::*;
+
+    pub fn decode(scalar: &Scalar, ctx: &mut Context)
+        -> Result<Version, DecodeError>
+    {
+        let text = <String as DecodeScalar>::decode(scalar, ctx)?;
+        let (major, minor) = text.split_once('.').unwrap();
+        Ok(Version {
+            major: major.parse().unwrap(),
+            minor: minor.parse().unwrap(),
+        })
+    }
+
+    pub fn encode(value: &Version, ctx: &mut Context)
+        -> Result<Scalar, EncodeError>
+    {
+        let text = format!("{}.{}", value.major, value.minor);
+        <String as EncodeScalar>::encode(&text, ctx)
+    }
+}
+
+fn decode_requires(node: &Node, ctx: &mut Context)
+    -> Result<Version, DecodeError>
+{
+    version::decode(&node.arguments[0], ctx)
+}
+
+fn encode_requires(value: &Version, ctx: &mut Context)
+    -> Result<Node, EncodeError>
+{
+    let mut node = Node::new("requires");
+    node.arguments.push(version::encode(value, ctx)?);
+    Ok(node)
+}
+
+#[test]
+fn encode_scalars_with() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Package {
+        #[kfl(argument, with = version)]
+        version: Version,
+        #[kfl(property, default, with = version)]
+        msrv: Option<Version>,
+    }
+    assert_encode!(Package { version: Version { major: 0, minor: 17 },
+                             msrv: None },
+                   r#"package "0.17""#);
+    assert_encode!(Package { version: Version { major: 0, minor: 17 },
+                             msrv: Some(Version { major: 1, minor: 62 }) },
+                   r#"package "0.17" msrv="1.62""#);
+}
+
+#[test]
+fn encode_children_with() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Package {
+        #[kfl(children, decode_with = decode_requires,
+              encode_with = encode_requires)]
+        requires: Vec<Version>,
+    }
+    assert_encode!(Package { requires: vec![Version { major: 1, minor: 62 }] },
+r#"package {
+  requires "1.62"
+}"#);
+}
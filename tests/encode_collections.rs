@@ -0,0 +1,62 @@
+mod common;
+
+use std::collections::{BTreeSet, VecDeque};
+use kfl::{Decode, Encode};
+
+#[derive(Decode, Encode, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Tag(#[kfl(argument)] String);
+
+#[test]
+fn encode_set_children() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Post {
+        #[kfl(children)]
+        tags: BTreeSet<Tag>,
+    }
+    assert_encode!(
+        Post { tags: BTreeSet::from([Tag("b".into()), Tag("a".into())]) },
+r#"post {
+  tag "a"
+  tag "b"
+}"#);
+}
+
+#[test]
+fn encode_deque_children() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Post {
+        #[kfl(children)]
+        tags: VecDeque<Tag>,
+    }
+    assert_encode!(
+        Post { tags: VecDeque::from([Tag("b".into()), Tag("a".into())]) },
+r#"post {
+  tag "b"
+  tag "a"
+}"#);
+}
+
+#[test]
+fn encode_tuple_arguments() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Range {
+        #[kfl(arguments)]
+        bounds: (u32, u32),
+    }
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Color {
+        #[kfl(arguments)]
+        rgb: [u8; 3],
+    }
+    assert_encode!(Range { bounds: (1, 10) }, r#"range 1 10"#);
+    assert_encode!(Color { rgb: [255, 0, 0] }, r#"color 255 0 0"#);
+}
+
+#[test]
+fn encode_tuple_node() {
+    assert_encode!((1u32, 10u32), r#"- 1 10"#);
+    assert_encode!([1u8, 2, 3], r#"- 1 2 3"#);
+    let text = kfl::encode("<test>", &(1u32, "a".to_string())).unwrap();
+    assert_eq!(kfl::decode::<(u32, String)>("<test>", &text).unwrap(),
+               (1, "a".to_string()));
+}
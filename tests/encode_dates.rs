@@ -0,0 +1,106 @@
+mod common;
+
+#[cfg(feature = "chrono")]
+#[test]
+fn encode_chrono() {
+    use chrono::{
+        DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime,
+        TimeZone, Utc,
+    };
+    use kfl::{Decode, Encode};
+
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        date: NaiveDate,
+        #[kfl(argument)]
+        time: NaiveTime,
+        #[kfl(property)]
+        local: NaiveDateTime,
+        #[kfl(property)]
+        offset: DateTime<FixedOffset>,
+        #[kfl(property)]
+        timeout: Duration,
+        #[kfl(property)]
+        utc: DateTime<Utc>,
+    }
+    let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    let time = NaiveTime::from_hms_milli_opt(9, 30, 0, 250).unwrap();
+    let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+    let node = Node {
+        date, time: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+        local: date.and_time(time),
+        offset: offset.from_local_datetime(&date.and_time(time)).unwrap(),
+        timeout: Duration::minutes(90),
+        utc: Utc.from_utc_datetime(&date.and_time(time)),
+    };
+    assert_encode!(node,
+        r#"node "2024-03-01" "09:30:00" local="2024-03-01T09:30:00.250" offset="2024-03-01T09:30:00.250+02:00" timeout="1h30m" utc="2024-03-01T09:30:00.250+00:00""#);
+    let text = kfl::encode("<test>", &node).unwrap();
+    assert_eq!(kfl::decode::<Node>("<test>", &text).unwrap(), node);
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn encode_time() {
+    use kfl::{Decode, Encode};
+    use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        date: Date,
+        #[kfl(argument)]
+        time: Time,
+        #[kfl(property)]
+        local: PrimitiveDateTime,
+        #[kfl(property)]
+        offset: OffsetDateTime,
+        #[kfl(property)]
+        timeout: Duration,
+    }
+    let date = Date::from_calendar_date(2024, Month::March, 1).unwrap();
+    let time = Time::from_hms_milli(9, 30, 0, 250).unwrap();
+    let node = Node {
+        date, time: Time::from_hms(9, 30, 0).unwrap(),
+        local: PrimitiveDateTime::new(date, time),
+        offset: PrimitiveDateTime::new(date, time).assume_utc(),
+        timeout: Duration::seconds(90),
+    };
+    assert_encode!(node,
+        r#"node "2024-03-01" "09:30:00" local="2024-03-01T09:30:00.25" offset="2024-03-01T09:30:00.25Z" timeout="1m30s""#);
+    let text = kfl::encode("<test>", &node).unwrap();
+    assert_eq!(kfl::decode::<Node>("<test>", &text).unwrap(), node);
+}
+
+#[cfg(feature = "jiff")]
+#[test]
+fn encode_jiff() {
+    use jiff::{SignedDuration, Timestamp, civil::{Date, DateTime, Time}};
+    use kfl::{Decode, Encode};
+
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        date: Date,
+        #[kfl(argument)]
+        time: Time,
+        #[kfl(property)]
+        at: Timestamp,
+        #[kfl(property)]
+        local: DateTime,
+        #[kfl(property)]
+        timeout: SignedDuration,
+    }
+    let date = Date::constant(2024, 3, 1);
+    let time = Time::constant(9, 30, 0, 0);
+    let node = Node {
+        date, time, at: Timestamp::from_second(1_709_285_400).unwrap(),
+        local: date.to_datetime(time),
+        timeout: SignedDuration::from_mins(90),
+    };
+    assert_encode!(node,
+        r#"node "2024-03-01" "09:30:00" at="2024-03-01T09:30:00Z" local="2024-03-01T09:30:00" timeout="PT1H30M""#);
+    let text = kfl::encode("<test>", &node).unwrap();
+    assert_eq!(kfl::decode::<Node>("<test>", &text).unwrap(), node);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+#[cfg(all(feature = "url", feature = "uuid", feature = "semver",
+          feature = "ipnet"))]
+#[test]
+fn encode_ext_scalars() {
+    use ipnet::IpNet;
+    use kfl::{Decode, Encode};
+    use semver::{Version, VersionReq};
+    use url::Url;
+    use uuid::Uuid;
+
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        id: Uuid,
+        #[kfl(property)]
+        allow: IpNet,
+        #[kfl(property)]
+        home: Url,
+        #[kfl(property)]
+        requires: VersionReq,
+        #[kfl(property)]
+        version: Version,
+    }
+    assert_encode!(
+        Node { id: Uuid::from_u128(0x67e55044_10b1_426f_9247_bb680e5fe0c8),
+               allow: "10.0.0.0/8".parse().unwrap(),
+               home: Url::parse("https://example.com").unwrap(),
+               requires: VersionReq::parse(">=1.2, <2").unwrap(),
+               version: Version::new(1, 2, 3) },
+        r#"node "67e55044-10b1-426f-9247-bb680e5fe0c8" allow="10.0.0.0/8" home="https://example.com/" requires=">=1.2, <2" version="1.2.3""#);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn encode_regex() {
+    use kfl::Encode;
+    use regex::Regex;
+
+    #[derive(Encode, Debug)]
+    struct Node {
+        #[kfl(argument)]
+        pattern: Regex,
+    }
+    assert_encode!(Node { pattern: Regex::new(r#"^"\w+"$"#).unwrap() },
+                   r#"node "^\"\\w+\"$""#);
+}
@@ -95,3 +95,34 @@ r#"parent {
     //     r#"something "world""#,
     //     "unexpected node `something`");
 }
+
+#[test]
+fn encode_flatten_properties() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Ca(#[kfl(argument)] String);
+    #[derive(DecodePartial, EncodePartial, Debug, Default, PartialEq)]
+    struct TlsOptions {
+        #[kfl(property)]
+        cert: String,
+        #[kfl(child, default)]
+        ca: Option<Ca>,
+    }
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Server {
+        #[kfl(property)]
+        port: u16,
+        #[kfl(flatten)]
+        tls: TlsOptions,
+    }
+    assert_encode!(
+        Server {
+            port: 443,
+            tls: TlsOptions {
+                cert: "a.pem".into(),
+                ca: Some(Ca("root.pem".into())),
+            }
+        },
+r#"server port=443 cert="a.pem" {
+  ca "root.pem"
+}"#);
+}
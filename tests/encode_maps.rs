@@ -0,0 +1,84 @@
+mod common;
+
+use std::collections::BTreeMap;
+use kfl::{Decode, Encode};
+
+#[test]
+fn encode_children_by_name() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Env {
+        #[kfl(children(key = name))]
+        vars: BTreeMap<String, String>,
+    }
+    let mut vars = BTreeMap::new();
+    vars.insert("BAR".into(), "2".into());
+    vars.insert("FOO".into(), "1".into());
+    assert_encode!(Env { vars },
+r#"env {
+  BAR "2"
+  FOO "1"
+}"#);
+}
+
+#[test]
+fn encode_children_by_argument() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Upstream {
+        #[kfl(property)]
+        port: u16,
+    }
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Proxy {
+        #[kfl(children(key = argument))]
+        upstreams: BTreeMap<String, Upstream>,
+    }
+    let mut upstreams = BTreeMap::new();
+    upstreams.insert("api".into(), Upstream { port: 8080 });
+    assert_encode!(Proxy { upstreams },
+r#"proxy {
+  upstream "api" port=8080
+}"#);
+}
+
+#[test]
+fn encode_typed_properties() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Limits {
+        #[kfl(properties)]
+        values: BTreeMap<String, u32>,
+    }
+    let mut values = BTreeMap::new();
+    values.insert("cpu".into(), 2);
+    values.insert("memory".into(), 512);
+    assert_encode!(Limits { values }, r#"limits cpu=2 memory=512"#);
+}
+
+#[test]
+fn encode_quoted_names() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Env {
+        #[kfl(properties)]
+        labels: BTreeMap<String, String>,
+        #[kfl(children(key = name))]
+        vars: BTreeMap<String, String>,
+    }
+    let mut labels = BTreeMap::new();
+    labels.insert("1st".into(), "a".into());
+    labels.insert("a=b".into(), "c".into());
+    labels.insert("key".into(), "d".into());
+    let mut vars = BTreeMap::new();
+    vars.insert("-1".into(), "1".into());
+    vars.insert("MY VAR".into(), "2".into());
+    vars.insert("say \"hi\"".into(), "3".into());
+    vars.insert("null".into(), "4".into());
+    let env = Env { labels, vars };
+    assert_encode!(env,
+r#"env "1st"="a" "a=b"="c" key="d" {
+  "-1" "1"
+  "MY VAR" "2"
+  "null" "4"
+  "say \"hi\"" "3"
+}"#);
+    let text = kfl::encode("<test>", &env).unwrap();
+    assert_eq!(kfl::decode::<Env>("<test>", &text).unwrap(), env);
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use kfl::{Decode, Encode};
+
+#[test]
+fn encode_null_string() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(property, default)]
+        name: Option<String>,
+    }
+    assert_encode!(Node { name: None }, r#"node name=null"#);
+    assert_encode!(Node { name: Some("null".into()) }, r#"node name="null""#);
+}
+
+#[test]
+fn encode_nullable() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Layer {
+        #[kfl(argument, default)]
+        port: Option<Option<u16>>,
+        #[kfl(property, default)]
+        label: Option<Option<String>>,
+    }
+    assert_encode!(Layer { port: None, label: None }, r#"layer"#);
+    assert_encode!(Layer { port: Some(None), label: Some(None) },
+                   r#"layer null label=null"#);
+    assert_encode!(Layer { port: Some(Some(80)),
+                           label: Some(Some("edge".into())) },
+                   r#"layer 80 label="edge""#);
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use std::num::{NonZeroU8, Wrapping};
+
+use kfl::{Decode, DecodePartial, Encode, EncodePartial};
+use kfl::context::Radix;
+
+#[derive(Decode, Encode, Debug, PartialEq)]
+struct Node {
+    #[kfl(argument)]
+    small: i8,
+    #[kfl(argument)]
+    large: u128,
+    #[kfl(argument)]
+    workers: NonZeroU8,
+    #[kfl(argument)]
+    counter: Wrapping<u32>,
+}
+
+#[derive(DecodePartial, EncodePartial, Default, Debug, PartialEq)]
+struct Document {
+    #[kfl(child, default)]
+    node: Option<Node>,
+}
+
+fn node() -> Node {
+    Node {
+        small: -10,
+        large: u128::MAX,
+        workers: NonZeroU8::new(8).unwrap(),
+        counter: Wrapping(255),
+    }
+}
+
+#[test]
+fn encode_decimal() {
+    assert_encode!(node(),
+        r#"node -10 340282366920938463463374607431768211455 8 255"#);
+}
+
+#[test]
+fn encode_radix() {
+    let document = Document { node: Some(node()) };
+    let output = kfl::encode_with_context("<test>", &document, |ctx| {
+        ctx.set(Radix::Hexadecimal);
+    }).unwrap();
+    assert_eq!(output,
+        "- {\n  node -0xa 0xffffffffffffffffffffffffffffffff 0x8 0xff\n}");
+    let output = kfl::encode_with_context("<test>", &document, |ctx| {
+        ctx.set(Radix::Binary);
+    }).unwrap();
+    assert_eq!(output,
+        format!("- {{\n  node -0b1010 0b{} 0b1000 0b11111111\n}}",
+                "1".repeat(128)));
+}
+
+#[cfg(feature = "bigdecimal")]
+#[test]
+fn encode_big_decimal() {
+    use std::str::FromStr;
+    use bigdecimal::BigDecimal;
+
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Price {
+        #[kfl(argument)]
+        amount: BigDecimal,
+    }
+    assert_encode!(Price { amount: BigDecimal::from_str("12345.6789")
+                                       .unwrap() },
+                   r#"price 12345.6789"#);
+}
+
+#[cfg(feature = "num-bigint")]
+#[test]
+fn encode_big_integer() {
+    use num_bigint::BigInt;
+
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        value: BigInt,
+    }
+    assert_encode!(Node { value: -BigInt::from(10u8).pow(30) },
+                   r#"node -1000000000000000000000000000000"#);
+}
+
+#[test]
+fn encode_non_finite() {
+    use kfl::ast::Scalar;
+
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Ratio(#[kfl(argument)] f64, #[kfl(argument)] f32);
+    fn error(ratio: Ratio) -> String {
+        let err = kfl::encode("<test>", &ratio).unwrap_err();
+        <kfl::Error as miette::Diagnostic>::related(&err).unwrap()
+            .map(|e| e.to_string()).collect::<Vec<_>>()
+            .join("\n")
+    }
+    assert_encode!(Ratio(0.5, -1.5), r#"ratio 0.5 -1.5"#);
+    assert_eq!(error(Ratio(f64::NAN, 0.0)), "NaN can't be represented in KDL");
+    assert_eq!(error(Ratio(0.0, f32::NEG_INFINITY)),
+               "-inf can't be represented in KDL");
+    assert_eq!(Scalar::try_from(0.25f32).unwrap(), Scalar::bare("0.25"));
+    assert!(Scalar::try_from(f64::INFINITY).is_err());
+}
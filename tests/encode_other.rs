@@ -0,0 +1,37 @@
+mod common;
+
+use std::collections::BTreeMap;
+use kfl::{Decode, Encode, ast::{Node, Scalar}};
+
+#[test]
+fn encode_other_variant() {
+    #[derive(Decode, Encode, Debug)]
+    enum Plugin {
+        Builtin(#[kfl(argument)] String),
+        #[kfl(other)]
+        Other(Node),
+    }
+    let mut node = Node::new("custom");
+    node.arguments.push(Scalar::bare("1"));
+    assert_encode!(Plugin::Other(node), r#"custom 1"#);
+}
+
+#[test]
+fn encode_other_fields() {
+    #[derive(Decode, Encode, Debug)]
+    struct Parent {
+        #[kfl(argument)]
+        name: String,
+        #[kfl(other(arguments))]
+        rest_args: Vec<Scalar>,
+        #[kfl(other(properties))]
+        rest_props: BTreeMap<Box<str>, Scalar>,
+        #[kfl(other)]
+        rest: Vec<Node>,
+    }
+    let parent = kfl::decode::<Parent>("<test>",
+        r#"parent "a" 2 key=3 { unknown 4; }"#).unwrap();
+    assert_encode!(parent, r#"parent "a" 2 key=3 {
+  unknown 4
+}"#);
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use kfl::ast::Node;
+use kfl::context::Context;
+use kfl::{Decode, Encode};
+
+#[test]
+fn encode_in_field_order() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Server {
+        #[kfl(property)]
+        zone: String,
+        #[kfl(property)]
+        port: u16,
+        #[kfl(property)]
+        host: String,
+    }
+    assert_encode!(Server { zone: "b".into(), port: 80, host: "a".into() },
+                   r#"server zone="b" port=80 host="a""#);
+}
+
+#[test]
+fn print_in_source_order() {
+    let input = r#"server zone="b" port=80 host="a" port=8080"#;
+    let nodes = kfl::parse(&mut Context::default(), input).unwrap();
+    assert_eq!(nodes[0].to_string(), input);
+    let mut node = nodes[0].clone();
+    node.insert_prop("port", 443);
+    node.insert_prop("debug", true);
+    assert_eq!(node.to_string(),
+               r#"server zone="b" port=443 host="a" debug=true"#);
+    let node = Node::build("server").prop("zone", "b")
+        .prop("port", 80).prop("zone", "c");
+    assert_eq!(node.to_string(), r#"server zone="c" port=80"#);
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use kfl::{Decode, DecodePartial, Encode, EncodePartial};
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+#[test]
+fn encode_skip_encode_if() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Server {
+        #[kfl(arguments, skip_encode_if = Vec::is_empty)]
+        hosts: Vec<String>,
+        #[kfl(property, default, skip_encode_if = is_zero)]
+        workers: u32,
+        #[kfl(property, default, skip_encode_if = Option::is_none)]
+        name: Option<String>,
+    }
+    assert_encode!(Server { hosts: vec![], workers: 0, name: None },
+                   r#"server"#);
+    assert_encode!(Server { hosts: vec!["a".into()], workers: 4,
+                            name: Some("main".into()) },
+                   r#"server "a" workers=4 name="main""#);
+}
+
+#[test]
+fn encode_defaults() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    #[kfl(encode_defaults)]
+    struct Node {
+        #[kfl(argument, default)]
+        id: Option<u32>,
+        #[kfl(property, default = "unnamed".into())]
+        name: String,
+    }
+    assert_encode!(Node { id: None, name: "unnamed".into() },
+                   r#"node null name="unnamed""#);
+    assert_encode!(Node { id: Some(1), name: "first".into() },
+                   r#"node 1 name="first""#);
+}
+
+#[test]
+fn encode_omit_defaults() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument, default)]
+        id: Option<u32>,
+        #[kfl(property, default = "unnamed".into())]
+        name: String,
+    }
+    assert_encode!(Node { id: None, name: "unnamed".into() },
+                   r#"node"#);
+    assert_encode!(Node { id: Some(1), name: "first".into() },
+                   r#"node 1 name="first""#);
+}
+
+#[test]
+fn encode_empty_children() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Child(#[kfl(argument)] String);
+    #[derive(DecodePartial, EncodePartial, Debug, Default, PartialEq)]
+    struct Intermediate {
+        #[kfl(child, default)]
+        child: Option<Child>,
+        #[kfl(children, default)]
+        children: Vec<Child>,
+    }
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Parent {
+        #[kfl(children)]
+        items: Vec<Child>,
+        #[kfl(flatten)]
+        intermediate: Intermediate,
+    }
+    assert_encode!(Parent { items: vec![],
+                            intermediate: Intermediate::default() },
+                   r#"parent"#);
+}
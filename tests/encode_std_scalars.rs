@@ -0,0 +1,39 @@
+mod common;
+
+use std::ffi::OsString;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+use kfl::{Decode, Encode};
+
+#[test]
+fn encode_std_scalars() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        separator: char,
+        #[kfl(argument)]
+        program: OsString,
+        #[kfl(property)]
+        address: IpAddr,
+        #[kfl(property)]
+        v6: Ipv6Addr,
+        #[kfl(property)]
+        timeout: Duration,
+    }
+    assert_encode!(Node { separator: '"', program: "/bin/sh".into(),
+                          address: Ipv4Addr::LOCALHOST.into(),
+                          v6: Ipv6Addr::LOCALHOST,
+                          timeout: Duration::from_millis(5_400_250) },
+                   r#"node "\"" "/bin/sh" address="127.0.0.1" v6="::1" timeout="1h30m250ms""#);
+}
+
+#[test]
+fn encode_zero_duration() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        timeout: Duration,
+    }
+    assert_encode!(Node { timeout: Duration::ZERO }, r#"node "0s""#);
+}
@@ -55,11 +55,11 @@ fn print_enum() {
         // Opt(Option<Arg>),
         Extra(#[kfl(argument, default)] Option<String>, u32),
     }
-    assert_encode!(Enum::Unit, r#"unit"#);
-    assert_encode!(Enum::Arg(123), r#"arg 123"#);
+    assert_encode!(Enum::Unit, r#"(enum)unit"#);
+    assert_encode!(Enum::Arg(123), r#"(enum)arg 123"#);
     // assert_encode!(r#"opt 123"#, Enum::Opt(Some(Arg(123))));
     // assert_encode!(r#"opt"#, Enum::Opt(None));
-    assert_encode!(Enum::Extra(None, 0), r#"extra"#);
+    assert_encode!(Enum::Extra(None, 0), r#"(enum)extra"#);
     // assert_encode_error!(Enum,
     //     r#"unit something="world""#,
     //     "unexpected property `something`");
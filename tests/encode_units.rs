@@ -0,0 +1,35 @@
+mod common;
+
+use std::time::Duration;
+
+use kfl::{Decode, DecodeScalar, Encode, EncodeScalar};
+use kfl::units::{ByteSize, HumanDuration, Rate};
+
+#[derive(DecodeScalar, EncodeScalar, Debug, PartialEq)]
+#[kfl(units = kfl::units::Millis)]
+struct Interval(u64);
+
+#[test]
+fn encode_units() {
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        size: ByteSize,
+        #[kfl(property)]
+        interval: Interval,
+        #[kfl(property)]
+        limit: Rate,
+        #[kfl(property)]
+        timeout: HumanDuration,
+    }
+    assert_encode!(Node { size: ByteSize(512 << 20),
+                          interval: Interval(90_000),
+                          limit: Rate::new(10, Duration::from_secs(60)),
+                          timeout: Duration::from_secs(5400).into() },
+                   r#"node "512MiB" interval="90s" limit="10/min" timeout="1h30m""#);
+    assert_encode!(Node { size: ByteSize(1_500),
+                          interval: Interval(0),
+                          limit: Rate::new(5, Duration::from_secs(30)),
+                          timeout: Duration::ZERO.into() },
+                   r#"node "1500B" interval="0ms" limit="5/30s" timeout="0s""#);
+}
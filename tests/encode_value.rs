@@ -0,0 +1,51 @@
+mod common;
+
+use kfl::value::{Document, Literal, Node, Value};
+
+#[test]
+fn encode_document() {
+    let mut server = Node::new("server");
+    server.push_arg("main");
+    server.push_arg(Value::typed("u16", Literal::Integer(8080)));
+    server.set_prop("ratio", 0.5);
+    server.set_prop("name", None::<&str>);
+    let mut tls = Node::new("tls");
+    tls.set_prop("enabled", true);
+    let mut cert = Node::new("cert");
+    cert.type_name = Some("file".into());
+    cert.push_arg("a.pem");
+    tls.push_child(cert);
+    server.push_child(tls);
+    let doc = Document::from(vec![server]);
+    assert_encode_children!(doc,
+        "- {\n  server \"main\" (u16)8080 ratio=0.5 name=null {\n    tls enabled=true {\n      (file)cert \"a.pem\"\n    }\n  }\n}");
+}
+
+#[test]
+fn roundtrip() {
+    let input = r#"
+        server "main" (u16)8080 0x1F 1.5 true null "null" {
+            (tls)listen 443 {
+                cert "a.pem"
+            }
+        }
+        log
+    "#;
+    let doc: Document = kfl::decode_children("<test>", input).unwrap();
+    let text = doc.to_string();
+    assert_eq!(text, "server \"main\" (u16)8080 31 1.5 true null \"null\" {\n  \
+        (tls)listen 443 {\n    cert \"a.pem\"\n  }\n}\nlog\n");
+    let again: Document = kfl::decode_children("<test>", &text).unwrap();
+    assert_eq!(doc, again);
+}
+
+#[test]
+fn encode_non_finite() {
+    let mut node = Node::new("node");
+    node.push_arg(f64::INFINITY);
+    let doc = Document::from(vec![node.clone()]);
+    assert!(kfl::encode_children("<test>", &doc).is_err());
+    assert!(Value::from(f64::NAN).get::<f64>().is_err());
+    node.arguments[0] = Value::from(1.5);
+    assert_eq!(node.to_string(), "node 1.5");
+}
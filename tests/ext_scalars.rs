@@ -0,0 +1,97 @@
+mod common;
+
+#[cfg(feature = "url")]
+#[test]
+fn decode_url() {
+    use kfl::Decode;
+    use url::Url;
+
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        url: Url,
+    }
+    assert_decode!(r#"node (url)"https://example.com/kdl""#,
+                   Node { url: Url::parse("https://example.com/kdl").unwrap() });
+    assert_decode_error!(Node,
+        r#"node "example.com""#,
+        "relative URL without a base");
+    assert_decode_error!(Node,
+        r#"node (uuid)"https://example.com""#,
+        "url or no type for Url, found uuid");
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn decode_uuid() {
+    use kfl::Decode;
+    use uuid::Uuid;
+
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        id: Uuid,
+    }
+    let id = Uuid::from_u128(0x67e55044_10b1_426f_9247_bb680e5fe0c8);
+    assert_decode!(r#"node (uuid)"67e55044-10b1-426f-9247-bb680e5fe0c8""#,
+                   Node { id });
+    assert_decode!(r#"node "67e5504410b1426f9247bb680e5fe0c8""#, Node { id });
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn decode_regex() {
+    use kfl::Decode;
+    use regex::Regex;
+
+    #[derive(Decode, Debug)]
+    struct Node {
+        #[kfl(argument)]
+        pattern: Regex,
+    }
+    let node: Node = kfl::decode("<test>", r#"node (regex)"^a+$""#).unwrap();
+    assert!(node.pattern.is_match("aaa"));
+    assert!(kfl::decode::<Node>("<test>", r#"node "(""#).is_err());
+}
+
+#[cfg(feature = "semver")]
+#[test]
+fn decode_semver() {
+    use kfl::Decode;
+    use semver::{Version, VersionReq};
+
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        version: Version,
+        #[kfl(property)]
+        requires: VersionReq,
+    }
+    assert_decode!(r#"node "1.2.3-beta.1" requires=">=1.2, <2""#,
+                   Node { version: Version::parse("1.2.3-beta.1").unwrap(),
+                          requires: VersionReq::parse(">=1.2, <2").unwrap() });
+}
+
+#[cfg(feature = "ipnet")]
+#[test]
+fn decode_ipnet() {
+    use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+    use kfl::Decode;
+
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        any: IpNet,
+        #[kfl(property)]
+        v4: Ipv4Net,
+        #[kfl(property)]
+        v6: Ipv6Net,
+    }
+    assert_decode!(r#"node "fd00::/8" v4="10.0.0.0/8" v6="::1/128""#,
+                   Node { any: "fd00::/8".parse().unwrap(),
+                          v4: "10.0.0.0/8".parse().unwrap(),
+                          v6: "::1/128".parse().unwrap() });
+    assert_decode_error!(Node,
+        r#"node "10.0.0.0/8" v4="10.0.0.0/33" v6="::1/128""#,
+        "invalid IP address syntax");
+}
@@ -95,3 +95,147 @@ fn decode_flatten_flatten() {
         r#"something "world""#,
         "unexpected node `something`");
 }
+
+#[test]
+fn decode_flatten_properties() {
+    #[derive(DecodePartial, Debug, Default, PartialEq)]
+    struct TlsOptions {
+        #[kfl(property)]
+        cert: Option<String>,
+        #[kfl(property)]
+        key: Option<String>,
+    }
+    #[derive(Decode, Debug, PartialEq)]
+    struct Listen {
+        #[kfl(argument)]
+        addr: String,
+        #[kfl(flatten)]
+        tls: TlsOptions,
+    }
+    assert_decode!(
+        r#"listen "0.0.0.0:443" cert="a.pem" key="a.key""#,
+        Listen {
+            addr: "0.0.0.0:443".into(),
+            tls: TlsOptions {
+                cert: Some("a.pem".into()),
+                key: Some("a.key".into()),
+            }
+        });
+    assert_decode!(
+        r#"listen "0.0.0.0:80""#,
+        Listen {
+            addr: "0.0.0.0:80".into(),
+            tls: TlsOptions::default(),
+        });
+    assert_decode_error!(Listen,
+        r#"listen "0.0.0.0:443" certificate="a.pem""#,
+        "unexpected property `certificate`");
+}
+
+#[test]
+fn decode_flatten_properties_and_children() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Ca(#[kfl(argument)] String);
+    #[derive(DecodePartial, Debug, Default, PartialEq)]
+    struct TlsOptions {
+        #[kfl(property)]
+        cert: Option<String>,
+        #[kfl(child, default)]
+        ca: Option<Ca>,
+    }
+    #[derive(Decode, Debug, PartialEq)]
+    struct Server {
+        #[kfl(property)]
+        port: u16,
+        #[kfl(flatten)]
+        tls: TlsOptions,
+    }
+    assert_decode!(
+        r#"server port=443 cert="a.pem" { ca "root.pem"; }"#,
+        Server {
+            port: 443,
+            tls: TlsOptions {
+                cert: Some("a.pem".into()),
+                ca: Some(Ca("root.pem".into())),
+            }
+        });
+}
+
+#[test]
+fn decode_flatten_required_property() {
+    #[derive(DecodePartial, Debug, Default, PartialEq)]
+    struct Key {
+        #[kfl(property)]
+        key: String,
+    }
+    #[derive(DecodePartial, Debug, Default, PartialEq)]
+    struct TlsOptions {
+        #[kfl(property)]
+        cert: String,
+        #[kfl(flatten)]
+        key: Key,
+    }
+    #[derive(Decode, Debug, PartialEq)]
+    struct Server {
+        #[kfl(property)]
+        port: u16,
+        #[kfl(flatten)]
+        tls: TlsOptions,
+    }
+    assert_decode!(
+        r#"server port=443 cert="a.pem" key="a.key""#,
+        Server {
+            port: 443,
+            tls: TlsOptions {
+                cert: "a.pem".into(),
+                key: Key { key: "a.key".into() },
+            }
+        });
+    assert_decode_error!(Server,
+        r#"server port=443 key="a.key""#,
+        "property `cert` is required");
+    assert_decode_error!(Server,
+        r#"server port=443 cert="a.pem""#,
+        "property `key` is required");
+}
+
+#[test]
+fn decode_flatten_default_property() {
+    #[derive(DecodePartial, Debug, Default, PartialEq)]
+    struct Limits {
+        #[kfl(property, default = 1024)]
+        max_body: u32,
+    }
+    #[derive(DecodePartial, Debug, Default, PartialEq)]
+    struct Options {
+        #[kfl(property, default = "localhost".into())]
+        host: String,
+        #[kfl(property, default)]
+        workers: u32,
+        #[kfl(flatten)]
+        limits: Limits,
+    }
+    #[derive(Decode, Debug, PartialEq)]
+    struct Server {
+        #[kfl(flatten)]
+        options: Options,
+    }
+    assert_decode!(
+        r#"server"#,
+        Server {
+            options: Options {
+                host: "localhost".into(),
+                workers: 0,
+                limits: Limits { max_body: 1024 },
+            }
+        });
+    assert_decode!(
+        r#"server host="example.org" workers=4 max_body=0"#,
+        Server {
+            options: Options {
+                host: "example.org".into(),
+                workers: 4,
+                limits: Limits { max_body: 0 },
+            }
+        });
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use std::collections::{BTreeMap, HashMap};
+use kfl::Decode;
+
+#[test]
+fn decode_children_by_name() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Env {
+        #[kfl(children(key = name))]
+        vars: BTreeMap<String, String>,
+    }
+    let mut vars = BTreeMap::new();
+    vars.insert("FOO".into(), "1".into());
+    vars.insert("BAR".into(), "2".into());
+    assert_decode!(r#"env { FOO "1"; BAR "2"; }"#, Env { vars });
+    assert_decode!(r#"env"#, Env { vars: BTreeMap::new() });
+    assert_decode_error!(Env,
+        r#"env { FOO "1"; FOO "2"; }"#,
+        "duplicate key `FOO`");
+    assert_decode_error!(Env,
+        r#"env { FOO "1" "2"; }"#,
+        "unexpected argument");
+}
+
+#[test]
+fn decode_children_by_argument() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Upstream {
+        #[kfl(property)]
+        port: u16,
+    }
+    #[derive(Decode, Debug, PartialEq)]
+    struct Proxy {
+        #[kfl(children(key = argument))]
+        upstreams: HashMap<String, Upstream>,
+    }
+    let mut upstreams = HashMap::new();
+    upstreams.insert("api".into(), Upstream { port: 8080 });
+    upstreams.insert("web".into(), Upstream { port: 3000 });
+    assert_decode!(
+        r#"proxy { upstream "api" port=8080; upstream "web" port=3000; }"#,
+        Proxy { upstreams });
+    assert_decode_error!(Proxy,
+        r#"proxy { upstream "api" port=1; upstream "api" port=2; }"#,
+        "duplicate key `api`");
+    assert_decode_error!(Proxy,
+        r#"proxy { upstream "api"; }"#,
+        "property `port` is required");
+}
+
+#[test]
+fn decode_typed_properties() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Limits {
+        #[kfl(properties)]
+        values: HashMap<String, u32>,
+    }
+    let mut values = HashMap::new();
+    values.insert("cpu".into(), 2);
+    values.insert("memory".into(), 512);
+    assert_decode!(r#"limits cpu=2 memory=512"#, Limits { values });
+}
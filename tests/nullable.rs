@@ -0,0 +1,62 @@
+mod common;
+
+use kfl::Decode;
+
+#[test]
+fn decode_null_string() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(property, default)]
+        name: Option<String>,
+    }
+    assert_decode!(r#"node"#, Node { name: None });
+    assert_decode!(r#"node name=null"#, Node { name: None });
+    assert_decode!(r#"node name="null""#, Node { name: Some("null".into()) });
+}
+
+#[test]
+fn decode_nullable_property() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Layer {
+        #[kfl(property, default)]
+        port: Option<Option<u16>>,
+        #[kfl(property, default)]
+        label: Option<Option<String>>,
+    }
+    assert_decode!(r#"layer"#, Layer { port: None, label: None });
+    assert_decode!(r#"layer port=null label=null"#,
+                   Layer { port: Some(None), label: Some(None) });
+    assert_decode!(r#"layer port=8080 label="null""#,
+                   Layer { port: Some(Some(8080)),
+                           label: Some(Some("null".into())) });
+}
+
+#[test]
+fn decode_nullable_argument() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Layer {
+        #[kfl(argument)]
+        name: String,
+        #[kfl(argument, default)]
+        port: Option<Option<u16>>,
+    }
+    assert_decode!(r#"layer "base""#,
+                   Layer { name: "base".into(), port: None });
+    assert_decode!(r#"layer "base" null"#,
+                   Layer { name: "base".into(), port: Some(None) });
+    assert_decode!(r#"layer "base" 80"#,
+                   Layer { name: "base".into(), port: Some(Some(80)) });
+}
+
+#[test]
+fn decode_nullable_validate() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Layer {
+        #[kfl(property, default, range(min = 1))]
+        workers: Option<Option<u32>>,
+    }
+    assert_decode!(r#"layer workers=null"#, Layer { workers: Some(None) });
+    assert_decode_error!(Layer,
+        r#"layer workers=0"#,
+        "value must be at least 1, found 0");
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use std::num::{NonZeroI32, NonZeroU8, Wrapping};
+
+use kfl::Decode;
+
+#[test]
+fn decode_wide_integers() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        small: i128,
+        #[kfl(argument)]
+        large: u128,
+    }
+    assert_decode!(r#"node -170141183460469231731687303715884105728 340282366920938463463374607431768211455"#,
+                   Node { small: i128::MIN, large: u128::MAX });
+    assert_decode!(r#"node (i128)-0x10 (u128)0b1_0000"#,
+                   Node { small: -16, large: 16 });
+}
+
+#[test]
+fn decode_integer_forms() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(arguments)]
+        values: Vec<i32>,
+    }
+    assert_decode!(r#"node 1_000 0x1f -0o17 +0b1010 1e3 2.5E2 1_0e-1"#,
+                   Node { values: vec![1000, 31, -15, 10, 1000, 250, 1] });
+    assert_decode_error!(Node,
+        r#"node 1.5"#,
+        "expected integer scalar, found 1.5");
+    assert_decode_error!(Node,
+        r#"node 1e20"#,
+        "number too large to fit in target type");
+}
+
+#[test]
+fn decode_float_forms() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(arguments)]
+        values: Vec<f64>,
+    }
+    assert_decode!(r#"node 1_000.5 1.5e3 -2E-1 0x10 0o10 -0b11"#,
+                   Node { values: vec![1000.5, 1500.0, -0.2, 16.0, 8.0, -3.0] });
+    assert_decode!(r#"node 0x20000000000000"#,
+                   Node { values: vec![9007199254740992.0] });
+    assert_decode_error!(Node,
+        r#"node 0x20000000000001"#,
+        "value `0x20000000000001` can't be represented exactly as f64");
+    assert_decode!(r#"node -0x8000_0000_0000_0000_0000_0000_0000_0000"#,
+                   Node { values: vec![-170141183460469231731687303715884105728.0] });
+    assert_decode_error!(Node,
+        r#"node 0x7fff_ffff_ffff_ffff_ffff_ffff_ffff_ffff"#,
+        "value `0x7fff_ffff_ffff_ffff_ffff_ffff_ffff_ffff` can't be \
+         represented exactly as f64");
+}
+
+#[test]
+fn decode_non_zero() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        workers: NonZeroU8,
+        #[kfl(property)]
+        offset: NonZeroI32,
+    }
+    assert_decode!(r#"node 4 offset=-1"#,
+                   Node { workers: NonZeroU8::new(4).unwrap(),
+                          offset: NonZeroI32::new(-1).unwrap() });
+    assert_decode_error!(Node,
+        r#"node 0 offset=1"#,
+        "value must not be zero");
+}
+
+#[test]
+fn decode_wrapping() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        counter: Wrapping<u16>,
+    }
+    assert_decode!(r#"node 0xffff"#, Node { counter: Wrapping(u16::MAX) });
+}
+
+#[cfg(feature = "bigdecimal")]
+#[test]
+fn decode_big_decimal() {
+    use std::str::FromStr;
+    use bigdecimal::BigDecimal;
+
+    #[derive(Decode, Debug, PartialEq)]
+    struct Price {
+        #[kfl(argument)]
+        amount: BigDecimal,
+    }
+    assert_decode!(r#"price (decimal)12345678901234567890.000000000001"#,
+                   Price { amount: BigDecimal::from_str(
+                       "12345678901234567890.000000000001").unwrap() });
+    assert_decode!(r#"price 0xff"#,
+                   Price { amount: BigDecimal::from(255) });
+    assert_decode_error!(Price,
+        r#"price (f64)1.5"#,
+        "decimal or no type for BigDecimal, found f64");
+}
+
+#[cfg(feature = "num-bigint")]
+#[test]
+fn decode_big_integer() {
+    use std::str::FromStr;
+    use num_bigint::{BigInt, BigUint};
+
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        signed: BigInt,
+        #[kfl(argument)]
+        unsigned: BigUint,
+    }
+    assert_decode!(r#"node -340282366920938463463374607431768211456 1e40"#,
+                   Node {
+                       signed: BigInt::from_str(
+                           "-340282366920938463463374607431768211456").unwrap(),
+                       unsigned: BigUint::from(10u8).pow(40),
+                   });
+    assert_decode_error!(Node,
+        r#"node 1 -1"#,
+        "value must not be negative");
+}
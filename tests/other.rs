@@ -0,0 +1,96 @@
+mod common;
+
+use std::collections::BTreeMap;
+use kfl::{Decode, DecodePartial, ast::{Node, Scalar}};
+
+#[test]
+fn decode_other_variant() {
+    #[derive(Decode, Debug)]
+    enum Plugin {
+        Builtin(#[kfl(argument)] String),
+        #[kfl(other)]
+        Other(Node),
+    }
+    let plugin = kfl::decode::<Plugin>("<test>", r#"builtin "auth""#)
+        .unwrap();
+    assert!(matches!(plugin, Plugin::Builtin(name) if name == "auth"));
+    let plugin = kfl::decode::<Plugin>("<test>",
+                                       r#"custom "x" key=1 { child; }"#)
+        .unwrap();
+    match plugin {
+        Plugin::Other(node) => {
+            assert_eq!(node.node_name.as_ref(), "custom");
+            assert_eq!(node.arguments.len(), 1);
+            assert_eq!(node.properties.len(), 1);
+            assert_eq!(node.children().len(), 1);
+        }
+        plugin => panic!("unexpected {:?}", plugin),
+    }
+}
+
+#[test]
+fn decode_other_children() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Child(#[kfl(argument)] String);
+    #[derive(Decode, Debug)]
+    struct Parent {
+        #[kfl(other)]
+        rest: Vec<Node>,
+        #[kfl(children)]
+        children: Vec<Child>,
+    }
+    let parent = kfl::decode::<Parent>("<test>",
+        r#"parent { unknown 1; child "a"; another; child "b"; }"#)
+        .unwrap();
+    assert_eq!(parent.children,
+               vec![Child("a".into()), Child("b".into())]);
+    assert_eq!(parent.rest.iter()
+               .map(|node| node.node_name.as_ref())
+               .collect::<Vec<_>>(),
+               vec!["unknown", "another"]);
+}
+
+#[test]
+fn decode_other_partial() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Child(#[kfl(argument)] String);
+    #[derive(DecodePartial, Debug, Default)]
+    struct Document {
+        #[kfl(children)]
+        children: Vec<Child>,
+        #[kfl(other)]
+        rest: Vec<Node>,
+    }
+    let doc = kfl::decode_children::<Document>("<test>",
+        r#"child "a"; plugin "b""#).unwrap();
+    assert_eq!(doc.children, vec![Child("a".into())]);
+    assert_eq!(doc.rest.len(), 1);
+    assert_eq!(doc.rest[0].node_name.as_ref(), "plugin");
+}
+
+#[test]
+fn decode_other_properties_and_arguments() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Server {
+        #[kfl(argument)]
+        name: String,
+        #[kfl(other(arguments))]
+        rest_args: Vec<Scalar>,
+        #[kfl(property)]
+        port: u16,
+        #[kfl(other(properties))]
+        rest_props: BTreeMap<Box<str>, Scalar>,
+    }
+    let mut rest_props = BTreeMap::new();
+    rest_props.insert("host".into(), Scalar::string("localhost"));
+    assert_decode!(r#"server "web" 1 port=80 host="localhost""#,
+        Server {
+            name: "web".into(),
+            rest_args: vec![Scalar::bare("1")],
+            port: 80,
+            rest_props,
+        });
+    assert_decode_error!(Server,
+        r#"server "web" host="localhost""#,
+        "property `port` is required");
+}
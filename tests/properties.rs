@@ -0,0 +1,80 @@
+mod common;
+
+use kfl::ast::Scalar;
+use kfl::context::{Context, DuplicateProperties};
+use kfl::{Decode, DecodePartial};
+
+#[derive(Decode, Debug, PartialEq)]
+struct Server {
+    #[kfl(property)]
+    port: u16,
+    #[kfl(other(properties))]
+    rest: Vec<(Box<str>, Scalar)>,
+}
+
+#[derive(DecodePartial, Default, Debug, PartialEq)]
+struct Document {
+    #[kfl(child)]
+    server: Option<Server>,
+}
+
+fn decode(input: &str, policy: DuplicateProperties) -> Result<u16, String> {
+    kfl::decode_with_context::<Document, _>("<test>", input,
+                                            |ctx| ctx.set(policy))
+        .map(|doc| doc.server.unwrap().port)
+        .map_err(|err| {
+            <kfl::Error as miette::Diagnostic>::related(&err).unwrap()
+                .map(|e| e.to_string()).collect::<Vec<_>>()
+                .join("\n")
+        })
+}
+
+#[test]
+fn decode_in_source_order() {
+    let server: Server = kfl::decode("<test>",
+        r#"server zone="b" port=80 host="a""#).unwrap();
+    assert_eq!(server.rest, vec![
+        (Box::from("zone"), Scalar::string("b")),
+        (Box::from("host"), Scalar::string("a")),
+    ]);
+}
+
+#[test]
+fn last_wins() {
+    assert_decode!(r#"server port=80 port=8080"#,
+                   Server { port: 8080, rest: vec![] });
+    assert_eq!(decode("server port=80 port=8080",
+                      DuplicateProperties::LastWins),
+               Ok(8080));
+}
+
+#[test]
+fn first_wins() {
+    assert_eq!(decode("server port=80 port=8080",
+                      DuplicateProperties::FirstWins),
+               Ok(80));
+    assert_eq!(decode("server port=80", DuplicateProperties::FirstWins),
+               Ok(80));
+}
+
+#[test]
+fn error_on_duplicates() {
+    assert_eq!(decode("server port=80 port=8080", DuplicateProperties::Error),
+               Err("duplicate property `port`".into()));
+    assert_eq!(decode("server port=80", DuplicateProperties::Error), Ok(80));
+}
+
+#[test]
+fn parsed_duplicates() {
+    let nodes = kfl::parse(&mut Context::default(),
+                           "server port=80 host=\"a\" port=8080").unwrap();
+    let properties = &nodes[0].properties;
+    assert_eq!(properties.len(), 2);
+    assert_eq!(properties.get("port"), Some(&Scalar::bare("8080")));
+    assert_eq!(properties.first("port"), Some(&Scalar::bare("80")));
+    assert_eq!(properties.entries().len(), 3);
+    let duplicates = properties.duplicates().collect::<Vec<_>>();
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(&*duplicates[0].name, "port");
+    assert_eq!(duplicates[0].span, kfl::span::Span(24, 33));
+}
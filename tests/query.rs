@@ -0,0 +1,135 @@
+mod common;
+
+use kfl::query::{self, Query};
+use kfl::span::Span;
+
+const DOCUMENT: &str = r#"
+    server "main" port=80 {
+        route path="/api" weight=10 {
+            plugin "auth"
+            group {
+                plugin "cache"
+            }
+        }
+        route path="/static" weight=2.5 {
+            plugin "gzip"
+        }
+        (proxy)upstream "api"
+    }
+    plugin "global"
+    route path="/health"
+"#;
+
+fn select(query: &str) -> Vec<String> {
+    let nodes = common::parse(DOCUMENT);
+    query::select(query, &nodes).unwrap().iter()
+        .map(|m| {
+            let arg = m.node.arguments.first()
+                .or_else(|| m.node.properties.get("path"))
+                .map(|s| s.literal.to_string())
+                .unwrap_or_default();
+            format!("{}:{}", m.path, arg)
+        })
+        .collect()
+}
+
+fn error(query: &str) -> (String, Span) {
+    let err = Query::parse(query).unwrap_err();
+    (err.to_string(), err.span)
+}
+
+#[test]
+fn names() {
+    assert_eq!(select("plugin"), [
+        "server/route/plugin:auth",
+        "server/route/group/plugin:cache",
+        "server/route/plugin:gzip",
+        "plugin:global",
+    ]);
+    assert_eq!(select("top() > plugin"), ["plugin:global"]);
+    assert_eq!(select("top()"), ["server:main", "plugin:global",
+                                 "route:/health"]);
+    assert_eq!(select("(proxy)"), ["server/upstream:api"]);
+    assert_eq!(select("()upstream"), ["server/upstream:api"]);
+    assert_eq!(select("group > []"), ["server/route/group/plugin:cache"]);
+}
+
+#[test]
+fn combinators() {
+    assert_eq!(select(r#"top() > server > route[path="/api"] >> plugin"#), [
+        "server/route/plugin:auth",
+        "server/route/group/plugin:cache",
+    ]);
+    assert_eq!(select("route > plugin"), [
+        "server/route/plugin:auth",
+        "server/route/plugin:gzip",
+    ]);
+    assert_eq!(select("plugin + group"), ["server/route/group:"]);
+    assert_eq!(select("server + plugin"), ["plugin:global"]);
+    assert_eq!(select("server ~ route"), ["route:/health"]);
+    assert_eq!(select("top() >> group"), ["server/route/group:"]);
+    assert_eq!(select("upstream || group"), [
+        "server/route/group:",
+        "server/upstream:api",
+    ]);
+}
+
+#[test]
+fn matchers() {
+    assert_eq!(select("[val() = \"main\"]"), ["server:main"]);
+    assert_eq!(select("[val(1)]"), Vec::<String>::new());
+    assert_eq!(select("[prop(port) = 80]"), ["server:main"]);
+    assert_eq!(select("[port = 80.0]"), ["server:main"]);
+    assert_eq!(select("route[weight > 5]"), ["server/route:/api"]);
+    assert_eq!(select("route[weight <= 2.5]"), ["server/route:/static"]);
+    assert_eq!(select("route[weight != 10]"), ["server/route:/static"]);
+    assert_eq!(select("route[path ^= \"/st\"]"), ["server/route:/static"]);
+    assert_eq!(select("route[path $= \"th\"]"), ["route:/health"]);
+    assert_eq!(select("route[path *= \"a\"]"), [
+        "server/route:/api",
+        "server/route:/static",
+        "route:/health",
+    ]);
+    assert_eq!(select("[name() = \"group\"]"), ["server/route/group:"]);
+    assert_eq!(select("[type() = \"proxy\"]"), ["server/upstream:api"]);
+    assert_eq!(select("route[weight][path]"), [
+        "server/route:/api",
+        "server/route:/static",
+    ]);
+}
+
+#[test]
+fn parsed_query() {
+    let nodes = common::parse(DOCUMENT);
+    let query: Query = "route[weight > 1] > plugin".parse().unwrap();
+    let first = query.first(&nodes).unwrap();
+    assert_eq!(first.path.to_string(), "server/route/plugin");
+    assert_eq!(first.path.depth(), 2);
+    assert_eq!(first.path.indices().collect::<Vec<_>>(), [0, 0, 0]);
+    assert_eq!(query.select(&nodes).len(), 2);
+}
+
+#[test]
+fn errors() {
+    assert_eq!(error("route["),
+               ("expected a name, found end of query".into(), Span(6, 6)));
+    assert_eq!(error("route[path = ]"),
+               ("expected a value, found `]`".into(), Span(13, 14)));
+    assert_eq!(error("route[path = bad]"),
+               ("expected a string, a number, `true`, `false` or `null`, \
+                 found `bad`".into(), Span(13, 16)));
+    assert_eq!(error("route plugin"),
+               ("expected `>`, `>>`, `+`, `~`, `||` or end of query, \
+                 found `p`".into(), Span(6, 7)));
+    assert_eq!(error("a > top()"),
+               ("`top()` is only allowed at the start of a selector".into(),
+                Span(4, 9)));
+    assert_eq!(error("top() + a"),
+               ("`top()` can only be followed by `>` or `>>`".into(),
+                Span(0, 7)));
+    assert_eq!(error("a[path = \"x]"),
+               ("unclosed string, expected `\"`".into(), Span(9, 12)));
+    assert_eq!(error("a >"),
+               ("expected a node name, `(`, `[` or `top()`, \
+                 found end of query".into(), Span(3, 3)));
+}
@@ -0,0 +1,156 @@
+use kfl::context::ScalarMode;
+use kfl::{Decode, DecodePartial};
+
+#[derive(Decode, Debug, PartialEq)]
+struct Server {
+    #[kfl(property)]
+    host: String,
+    #[kfl(property)]
+    port: u16,
+    #[kfl(property, default)]
+    ratio: f64,
+    #[kfl(property, default)]
+    tls: bool,
+}
+
+#[derive(DecodePartial, Default, Debug, PartialEq)]
+struct Document {
+    #[kfl(child)]
+    server: Option<Server>,
+}
+
+fn decode(input: &str, mode: ScalarMode) -> Result<Document, String> {
+    kfl::decode_with_context("<test>", input, |ctx| ctx.set(mode))
+        .map_err(|err| {
+            <kfl::Error as miette::Diagnostic>::related(&err).unwrap()
+                .map(|e| e.to_string()).collect::<Vec<_>>()
+                .join("\n")
+        })
+}
+
+#[test]
+fn lenient() {
+    let server = Server { host: "8080".into(), port: 8080, ratio: 0.5,
+                          tls: true };
+    let doc = decode(r#"server host=8080 port="8080" ratio="0.5" tls="true""#,
+                     ScalarMode::Lenient).unwrap();
+    assert_eq!(doc.server, Some(server));
+    let server = Server { host: "localhost".into(), port: 8080, ratio: 0.0,
+                          tls: false };
+    let doc: Document = kfl::decode_children(
+        "<test>", r#"server host="localhost" port="8080""#).unwrap();
+    assert_eq!(doc.server, Some(server));
+}
+
+#[test]
+fn strict() {
+    let server = Server { host: "localhost".into(), port: 8080, ratio: 0.5,
+                          tls: true };
+    let doc = decode(r#"server host="localhost" port=8080 ratio=0.5 tls=true"#,
+                     ScalarMode::Strict).unwrap();
+    assert_eq!(doc.server, Some(server));
+    assert_eq!(decode(r#"server host=8080 port=8080"#, ScalarMode::Strict),
+               Err("expected string scalar, found 8080".into()));
+    assert_eq!(decode(r#"server host="localhost" port="8080""#,
+                      ScalarMode::Strict),
+               Err(r#"expected integer scalar, found "8080""#.into()));
+    assert_eq!(decode(r#"server host="localhost" port=8080 ratio="0.5""#,
+                      ScalarMode::Strict),
+               Err(r#"expected decimal scalar, found "0.5""#.into()));
+    assert_eq!(decode(r#"server host="localhost" port=8080 tls="true""#,
+                      ScalarMode::Strict),
+               Err(r#"expected boolean scalar, found "true""#.into()));
+}
+
+#[test]
+fn strict_string_types() {
+    use std::net::Ipv4Addr;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use kfl::units::ByteSize;
+
+    #[derive(Decode, Debug, PartialEq)]
+    struct Listen {
+        #[kfl(property)]
+        addr: Ipv4Addr,
+        #[kfl(property, default)]
+        root: Option<PathBuf>,
+        #[kfl(property, default)]
+        timeout: Option<Duration>,
+        #[kfl(property, default)]
+        buffer: Option<ByteSize>,
+        #[kfl(property, default)]
+        key: Option<Vec<u8>>,
+    }
+    #[derive(DecodePartial, Default, Debug)]
+    struct Config {
+        #[kfl(child)]
+        listen: Option<Listen>,
+    }
+    fn decode(input: &str, mode: ScalarMode) -> Result<Listen, String> {
+        kfl::decode_with_context::<Config, _>("<test>", input,
+                                              |ctx| ctx.set(mode))
+            .map(|config| config.listen.unwrap())
+            .map_err(|err| {
+                <kfl::Error as miette::Diagnostic>::related(&err).unwrap()
+                    .map(|e| e.to_string()).collect::<Vec<_>>()
+                    .join("\n")
+            })
+    }
+    let listen = decode("listen addr=\"127.0.0.1\" root=\"/srv\" \
+                         timeout=(s)90 buffer=\"4KiB\" key=(hex)\"0aff\"",
+                        ScalarMode::Strict).unwrap();
+    assert_eq!(listen, Listen {
+        addr: Ipv4Addr::LOCALHOST,
+        root: Some("/srv".into()),
+        timeout: Some(Duration::from_secs(90)),
+        buffer: Some(ByteSize(4096)),
+        key: Some(vec![0x0a, 0xff]),
+    });
+    assert_eq!(decode(r#"listen addr=(ipv4)127"#, ScalarMode::Strict),
+               Err("expected string scalar, found 127".into()));
+    assert_eq!(decode(r#"listen addr="127.0.0.1" timeout=(s)"90""#,
+                      ScalarMode::Strict),
+               Err(r#"expected number scalar, found "90""#.into()));
+    assert_eq!(decode(r#"listen addr="127.0.0.1" key=(hex)1234"#,
+                      ScalarMode::Strict),
+               Err("expected string scalar, found 1234".into()));
+    let listen = decode(r#"listen addr="10.0.0.1" key=(hex)1234"#,
+                        ScalarMode::Lenient).unwrap();
+    assert_eq!(listen.key, Some(vec![0x12, 0x34]));
+}
+
+#[test]
+fn strict_string_types_round_trip() {
+    use std::net::SocketAddr;
+    use std::path::PathBuf;
+    use kfl::{Encode, EncodePartial};
+
+    #[derive(Decode, Encode, Debug, PartialEq)]
+    struct Listen {
+        #[kfl(property)]
+        addr: SocketAddr,
+        #[kfl(property)]
+        root: PathBuf,
+        #[cfg(feature = "http")]
+        #[kfl(property)]
+        upstream: http::Uri,
+    }
+    #[derive(DecodePartial, EncodePartial, Default, Debug, PartialEq)]
+    struct Config {
+        #[kfl(child)]
+        listen: Option<Listen>,
+    }
+    let config = Config {
+        listen: Some(Listen {
+            addr: "127.0.0.1:8080".parse().unwrap(),
+            root: "/srv/www".into(),
+            #[cfg(feature = "http")]
+            upstream: "http://10.0.0.2/".parse().unwrap(),
+        }),
+    };
+    let text = kfl::encode_children("<test>", &config).unwrap();
+    let decoded: Config = kfl::decode_with_context("<test>", &text,
+        |ctx| ctx.set(ScalarMode::Strict)).unwrap();
+    assert_eq!(decoded, config);
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use std::ffi::OsString;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+use kfl::Decode;
+
+#[test]
+fn decode_char() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        separator: char,
+    }
+    assert_decode!(r#"node ",""#, Node { separator: ',' });
+    assert_decode!(r#"node "é""#, Node { separator: 'é' });
+    assert_decode_error!(Node,
+        r#"node "ab""#,
+        "expected a single character, found `ab`");
+}
+
+#[test]
+fn decode_ip() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        any: IpAddr,
+        #[kfl(property)]
+        v4: Ipv4Addr,
+        #[kfl(property)]
+        v6: Ipv6Addr,
+    }
+    assert_decode!(r#"node (ipv6)"::1" v4=(ipv4)"10.0.0.1" v6="::""#,
+                   Node { any: Ipv6Addr::LOCALHOST.into(),
+                          v4: Ipv4Addr::new(10, 0, 0, 1),
+                          v6: Ipv6Addr::UNSPECIFIED });
+    assert_decode!(r#"node "127.0.0.1" v4="10.0.0.1" v6="::""#,
+                   Node { any: Ipv4Addr::LOCALHOST.into(),
+                          v4: Ipv4Addr::new(10, 0, 0, 1),
+                          v6: Ipv6Addr::UNSPECIFIED });
+    assert_decode_error!(Node,
+        r#"node (ipv4)"::1" v4="10.0.0.1" v6="::""#,
+        "invalid IPv4 address syntax");
+    assert_decode_error!(Node,
+        r#"node "::1" v4=(ipv6)"10.0.0.1" v6="::""#,
+        "ipv4 or no type for Ipv4Addr, found ipv6");
+}
+
+#[test]
+fn decode_duration() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(arguments)]
+        timeouts: Vec<Duration>,
+    }
+    assert_decode!(r#"node "1h30m" (duration)"1.5s" "2m 10s" (s)90 (ms)250"#,
+                   Node { timeouts: vec![
+                       Duration::from_secs(5400),
+                       Duration::from_millis(1500),
+                       Duration::from_secs(130),
+                       Duration::from_secs(90),
+                       Duration::from_millis(250),
+                   ] });
+    assert_decode_error!(Node,
+        r#"node "10 parsecs""#,
+        "invalid duration `10 parsecs`, expected e.g. `1h30m` or `(s)90`");
+    assert_decode_error!(Node,
+        r#"node 90"#,
+        "invalid duration `90`, expected e.g. `1h30m` or `(s)90`");
+}
+
+#[test]
+fn decode_os_string() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        program: OsString,
+    }
+    assert_decode!(r#"node "/bin/sh""#, Node { program: "/bin/sh".into() });
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use std::time::Duration;
+
+use kfl::{Decode, DecodeScalar};
+use kfl::units::{ByteSize, HumanDuration, Rate};
+
+#[derive(DecodeScalar, Debug, PartialEq)]
+#[kfl(units = kfl::units::Bytes)]
+struct MemoryLimit(u32);
+
+#[derive(DecodeScalar, Debug, PartialEq)]
+#[kfl(units = kfl::units::Seconds)]
+struct Timeout(u64);
+
+#[test]
+fn decode_byte_size() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        size: ByteSize,
+    }
+    assert_decode!(r#"node "512MiB""#, Node { size: ByteSize(512 << 20) });
+    assert_decode!(r#"node "1.5 GiB""#, Node { size: ByteSize(3 << 29) });
+    assert_decode!(r#"node "2kB""#, Node { size: ByteSize(2_000) });
+    assert_decode!(r#"node (MiB)512"#, Node { size: ByteSize(512 << 20) });
+    assert_decode!(r#"node 1024"#, Node { size: ByteSize(1024) });
+    assert_decode_error!(Node,
+        r#"node "1.5B""#,
+        "invalid quantity `1.5B`, expected a whole number of `B` or a number \
+         with one of the units B, kB, KB, MB, GB, TB, PB, EB, KiB, MiB, GiB, \
+         TiB, PiB, EiB");
+    assert_decode_error!(Node,
+        r#"node (ms)512"#,
+        "B, kB, KB, MB, GB, TB, PB, EB, KiB, MiB, GiB, TiB, PiB, EiB or no \
+         type for ByteSize, found ms");
+}
+
+#[test]
+fn decode_human_duration() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        timeout: HumanDuration,
+    }
+    assert_decode!(r#"node "1h30m""#,
+                   Node { timeout: Duration::from_secs(5400).into() });
+    assert_decode!(r#"node (ms)250"#,
+                   Node { timeout: Duration::from_millis(250).into() });
+    assert_decode!(r#"node 30"#,
+                   Node { timeout: Duration::from_secs(30).into() });
+}
+
+#[test]
+fn decode_rate() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(argument)]
+        limit: Rate,
+    }
+    assert_decode!(r#"node "10/min""#,
+                   Node { limit: Rate::new(10, Duration::from_secs(60)) });
+    assert_decode!(r#"node "100/s""#,
+                   Node { limit: Rate::new(100, Duration::from_secs(1)) });
+    assert_decode!(r#"node "5/30s""#,
+                   Node { limit: Rate::new(5, Duration::from_secs(30)) });
+    assert_decode_error!(Node,
+        r#"node "10/0s""#,
+        "invalid rate `10/0s`, expected e.g. `10/min` or `5/30s`");
+}
+
+#[test]
+fn decode_units_new_type() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Node {
+        #[kfl(property)]
+        memory: MemoryLimit,
+        #[kfl(property)]
+        timeout: Timeout,
+    }
+    assert_decode!(r#"node memory="64MiB" timeout=(min)2"#,
+                   Node { memory: MemoryLimit(64 << 20), timeout: Timeout(120) });
+    assert_decode_error!(Node,
+        r#"node memory="4GiB" timeout="1s""#,
+        "value `4294967296` is out of range for MemoryLimit");
+    assert_decode_error!(Node,
+        r#"node memory="1MiB" timeout="1.5s""#,
+        "invalid quantity `1.5s`, expected a whole number of `s` or a number \
+         with one of the units s, m, min, h, d");
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use kfl::Decode;
+
+fn check_port(port: &u16) -> Result<(), String> {
+    if *port == 0 {
+        return Err("port must not be zero".into());
+    }
+    Ok(())
+}
+
+fn check_listen(listen: &Listen) -> Result<(), &'static str> {
+    if listen.port.is_some() && listen.socket.is_some() {
+        return Err("`port` and `socket` are mutually exclusive");
+    }
+    Ok(())
+}
+
+#[derive(Decode, Debug, PartialEq)]
+#[kfl(validate = check_listen)]
+struct Listen {
+    #[kfl(property, default, validate = check_port)]
+    port: Option<u16>,
+    #[kfl(property, default)]
+    socket: Option<String>,
+}
+
+#[test]
+fn decode_validate_field() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Server {
+        #[kfl(argument, validate = check_port)]
+        port: u16,
+    }
+    assert_decode!(r#"server 8080"#, Server { port: 8080 });
+    assert_decode_error!(Server,
+        r#"server 0"#,
+        "port must not be zero");
+}
+
+#[test]
+fn decode_validate_container() {
+    assert_decode!(r#"listen port=80"#,
+                   Listen { port: Some(80), socket: None });
+    assert_decode!(r#"listen"#, Listen { port: None, socket: None });
+    assert_decode_error!(Listen,
+        r#"listen port=0"#,
+        "port must not be zero");
+    assert_decode_error!(Listen,
+        r#"listen port=80 socket="/run/app.sock""#,
+        "`port` and `socket` are mutually exclusive");
+}
+
+#[test]
+fn decode_range() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Pool {
+        #[kfl(argument, range(min = 1, max = 64))]
+        size: u32,
+        #[kfl(property, default = 0.5, range(max = 1.0))]
+        ratio: f64,
+    }
+    assert_decode!(r#"pool 8"#, Pool { size: 8, ratio: 0.5 });
+    assert_decode!(r#"pool 64 ratio=1.0"#, Pool { size: 64, ratio: 1.0 });
+    assert_decode_error!(Pool,
+        r#"pool 0"#,
+        "value must be at least 1, found 0");
+    assert_decode_error!(Pool,
+        r#"pool 65"#,
+        "value must be at most 64, found 65");
+    assert_decode_error!(Pool,
+        r#"pool 8 ratio=1.5"#,
+        "value must be at most 1, found 1.5");
+}
+
+#[test]
+fn decode_non_empty() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Route {
+        #[kfl(argument, non_empty)]
+        path: String,
+        #[kfl(arguments, non_empty)]
+        methods: Vec<String>,
+        #[kfl(children(key = name), non_empty)]
+        headers: BTreeMap<String, String>,
+    }
+    assert_decode!(r#"route "/" "GET" { accept "*/*"; }"#,
+                   Route {
+                       path: "/".into(),
+                       methods: vec!["GET".into()],
+                       headers: BTreeMap::from([
+                           ("accept".into(), "*/*".into())
+                       ]),
+                   });
+    assert_decode_error!(Route,
+        r#"route "" "GET" { accept "*/*"; }"#,
+        "value must not be empty");
+    assert_decode_error!(Route,
+        r#"route "/" { accept "*/*"; }"#,
+        "value must not be empty");
+    assert_decode_error!(Route,
+        r#"route "/" "GET""#,
+        "value must not be empty");
+}
+
+#[test]
+fn decode_validate_child() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Workers(#[kfl(argument)] u32);
+    fn check_workers(workers: &Workers) -> Result<(), String> {
+        if workers.0 > 16 {
+            return Err(format!("too many workers: {}", workers.0));
+        }
+        Ok(())
+    }
+    #[derive(Decode, Debug, PartialEq)]
+    struct Config {
+        #[kfl(child, default, validate = check_workers)]
+        workers: Option<Workers>,
+    }
+    assert_decode!(r#"config { workers 4; }"#,
+                   Config { workers: Some(Workers(4)) });
+    assert_decode!(r#"config"#, Config { workers: None });
+    assert_decode_error!(Config,
+        r#"config { workers 32; }"#,
+        "too many workers: 32");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn decode_pattern() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct User {
+        #[kfl(argument, pattern = "^[a-z][a-z0-9_]*$")]
+        name: String,
+    }
+    assert_decode!(r#"user "admin""#, User { name: "admin".into() });
+    assert_decode_error!(User,
+        r#"user "Admin""#,
+        "value `Admin` does not match pattern `^[a-z][a-z0-9_]*$`");
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::collections::HashSet;
+
+use kfl::Decode;
+use kfl::value::{Document, Literal, Node, Value};
+
+#[test]
+fn decode_values() {
+    let doc: Document = kfl::decode_children("<test>", r#"
+        server "main" (u16)8080 0x1F 1.5 true null "null" {
+            tls enabled=false
+        }
+    "#).unwrap();
+    let server = &doc["server"];
+    assert_eq!(server.arguments, vec![
+        Value::from("main"),
+        Value::typed("u16", Literal::Integer(8080)),
+        Value::from(31),
+        Value::from(1.5),
+        Value::from(true),
+        Value::new(Literal::Null),
+        Value::from("null"),
+    ]);
+    assert_eq!(server.get_arg::<u16>(1).unwrap(), 8080);
+    assert_eq!(server[0].as_str(), Some("main"));
+    assert_eq!(doc.prop_at("server/tls/enabled"), Some(&Value::from(false)));
+    assert_eq!(doc.at("server/tls").unwrap().name.as_ref(), "tls");
+    assert!(doc.at("server/missing").is_none());
+}
+
+#[test]
+fn typed_getters() {
+    let doc: Document = kfl::decode_children("<test>",
+        r#"server host="localhost" port=8080 ratio=0.5"#).unwrap();
+    let server = doc.get("server").unwrap();
+    assert_eq!(server.get_prop::<u16>("port").unwrap(), 8080);
+    assert_eq!(server.get_prop::<String>("host").unwrap(), "localhost");
+    assert_eq!(server.get_prop::<f64>("ratio").unwrap(), 0.5);
+    assert_eq!(server["port"].as_i128(), Some(8080));
+    assert_eq!(server.get_prop::<u8>("port").unwrap_err().to_string(),
+               "number too large to fit in target type");
+    assert_eq!(server.get_prop::<u16>("timeout").unwrap_err().to_string(),
+               "property `timeout` of node `server` is missing");
+}
+
+#[test]
+fn mixed_into_derive() {
+    #[derive(Decode, Debug, PartialEq)]
+    struct Plugin {
+        #[kfl(argument)]
+        name: String,
+        #[kfl(property)]
+        level: Value,
+        #[kfl(children)]
+        config: Vec<Node>,
+    }
+    let mut config = Node::new("cache");
+    config.set_prop("size", 10);
+    assert_decode!(r#"plugin "cache" level=(u8)3 { cache size=10; }"#,
+                   Plugin { name: "cache".into(),
+                            level: Value::typed("u8", Literal::Integer(3)),
+                            config: vec![config] });
+}
+
+#[test]
+fn equality_and_hash() {
+    let mut a = Node::new("node");
+    a.push_arg(1);
+    a.set_prop("x", "y");
+    let mut b = a.clone();
+    assert_eq!(a, b);
+    b.set_prop("x", "z");
+    assert_ne!(a, b);
+    let set: HashSet<Node> = [a.clone(), a, b].into_iter().collect();
+    assert_eq!(set.len(), 2);
+    assert_eq!(Value::from(f64::NAN), Value::from(f64::NAN));
+}
+
+#[test]
+fn large_unsigned() {
+    let doc: Document = kfl::decode_children("<test>", r#"
+        node 340282366920938463463374607431768211455
+        node 340282366920938463463374607431768211454
+        node 0xffffffffffffffffffffffffffffffff
+    "#).unwrap();
+    let args = doc.nodes.iter().map(|n| n.arg(0).unwrap().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(args[0].literal, Literal::Unsigned(u128::MAX));
+    assert_eq!(args[0].as_u128(), Some(u128::MAX));
+    assert_eq!(args[0].as_i128(), None);
+    assert_ne!(args[0], args[1]);
+    assert_eq!(args[0], args[2]);
+    assert!(args[1] < args[0]);
+    assert!(Value::from(i128::MAX) < args[1]);
+    let set: HashSet<Value> = args.iter().cloned().collect();
+    assert_eq!(set.len(), 2);
+    assert_eq!(Value::from(u128::MAX), args[0]);
+    assert_eq!(Value::from(5u128), Value::from(5));
+    assert_eq!(args[1].get::<u128>().unwrap(), u128::MAX - 1);
+    assert_eq!(doc.nodes[1].to_string(),
+               "node 340282366920938463463374607431768211454");
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use kfl::ast::{Node, Scalar};
+use kfl::visit::{self, Change, Fold, Path, Visit, VisitMut};
+
+const DOCUMENT: &str = r#"
+server "main" port=80 {
+    route "/api" upstream="api" {
+        backend "a.local"
+    }
+    route "/static"
+}
+upstream "api" {
+    backend "b.local"
+}
+"#;
+
+#[test]
+fn visit_paths() {
+    #[derive(Default)]
+    struct Collect {
+        nodes: Vec<(String, usize, Option<usize>)>,
+        scalars: Vec<(String, String)>,
+    }
+    impl Visit for Collect {
+        fn visit_node(&mut self, node: &Node, path: &Path) {
+            self.nodes.push((path.to_string(), path.depth(), path.index()));
+            visit::visit_node(self, node, path);
+        }
+        fn visit_scalar(&mut self, scalar: &Scalar, path: &Path) {
+            self.scalars.push((path.name().unwrap().into(),
+                               scalar.literal.to_string()));
+        }
+    }
+    let mut collect = Collect::default();
+    collect.visit_nodes(&common::parse(DOCUMENT), &Path::root());
+    let nodes = collect.nodes.iter()
+        .map(|(path, depth, index)| (path.as_str(), *depth, *index))
+        .collect::<Vec<_>>();
+    assert_eq!(nodes, [
+        ("server", 0, Some(0)),
+        ("server/route", 1, Some(0)),
+        ("server/route/backend", 2, Some(0)),
+        ("server/route", 1, Some(1)),
+        ("upstream", 0, Some(1)),
+        ("upstream/backend", 1, Some(0)),
+    ]);
+    let scalars = collect.scalars.iter()
+        .map(|(name, literal)| (name.as_str(), literal.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(scalars, [
+        ("server", "main"),
+        ("server", "80"),
+        ("route", "/api"),
+        ("route", "api"),
+        ("backend", "a.local"),
+        ("route", "/static"),
+        ("upstream", "api"),
+        ("backend", "b.local"),
+    ]);
+}
+
+#[test]
+fn visit_property() {
+    struct References(Vec<String>);
+    impl Visit for References {
+        fn visit_property(&mut self, name: &str, scalar: &Scalar, path: &Path)
+        {
+            if name == "upstream" {
+                self.0.push(format!("{} -> {}", path, scalar.literal));
+            }
+        }
+    }
+    let mut references = References(Vec::new());
+    references.visit_nodes(&common::parse(DOCUMENT), &Path::root());
+    assert_eq!(references.0, ["server/route -> api"]);
+}
+
+#[test]
+fn visit_mut_remove_and_replace() {
+    struct Rewrite;
+    impl VisitMut for Rewrite {
+        fn visit_node_mut(&mut self, node: &mut Node, path: &Path) -> Change {
+            match &*node.node_name {
+                "backend" if path.depth() > 1 => Change::Remove,
+                "upstream" => Change::Replace(vec![
+                    Node::build("upstream").arg("api"),
+                    Node::build("upstream").arg("cdn"),
+                ]),
+                _ => {
+                    visit::visit_node_mut(self, node, path);
+                    Change::Keep
+                }
+            }
+        }
+        fn visit_scalar_mut(&mut self, scalar: &mut Scalar, _: &Path) {
+            if scalar.literal.starts_with('/') {
+                *scalar = Scalar::string(&format!("/v1{}", scalar.literal));
+            }
+        }
+    }
+    let mut nodes = common::parse(DOCUMENT);
+    Rewrite.visit_nodes_mut(&mut nodes, &Path::root());
+    let text = nodes.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    assert_eq!(text, [
+        "server \"main\" port=80 {\n  route \"/v1/api\" upstream=\"api\" {\n  }\n  route \"/v1/static\"\n}",
+        "upstream \"api\"",
+        "upstream \"cdn\"",
+    ]);
+}
+
+#[test]
+fn fold() {
+    struct Flatten;
+    impl Fold for Flatten {
+        fn fold_node(&mut self, node: Node, path: &Path) -> Option<Node> {
+            if node.node_name.as_ref() == "route" && path.index() == Some(1) {
+                return None;
+            }
+            let mut node = visit::fold_node(self, node, path);
+            node.node_name = format!("{}-{}", path.depth(), node.node_name)
+                .into();
+            Some(node)
+        }
+        fn fold_property(&mut self, name: &str, scalar: Scalar, _: &Path)
+            -> Scalar
+        {
+            if name == "port" {
+                Scalar::bare("8080")
+            } else {
+                scalar
+            }
+        }
+    }
+    let nodes = Flatten.fold_nodes(common::parse(DOCUMENT), &Path::root());
+    let text = nodes.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    assert_eq!(text, [
+        "0-server \"main\" port=8080 {\n  1-route \"/api\" upstream=\"api\" {\n    2-backend \"a.local\"\n  }\n}",
+        "0-upstream \"api\" {\n  1-backend \"b.local\"\n}",
+    ]);
+}